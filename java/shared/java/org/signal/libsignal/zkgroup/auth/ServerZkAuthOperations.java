@@ -25,11 +25,11 @@ public class ServerZkAuthOperations {
     this.serverSecretParams = serverSecretParams;
   }
 
-  public AuthCredentialResponse issueAuthCredential(UUID uuid, int redemptionTime) {
+  public AuthCredentialResponse issueAuthCredential(UUID uuid, int redemptionTime) throws VerificationFailedException {
     return issueAuthCredential(new SecureRandom(), uuid, redemptionTime);
   }
 
-  public AuthCredentialResponse issueAuthCredential(SecureRandom secureRandom, UUID uuid, int redemptionTime) {
+  public AuthCredentialResponse issueAuthCredential(SecureRandom secureRandom, UUID uuid, int redemptionTime) throws VerificationFailedException {
     byte[] random      = new byte[RANDOM_LENGTH];
 
     secureRandom.nextBytes(random);
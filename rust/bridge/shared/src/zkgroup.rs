@@ -354,10 +354,10 @@ fn ServerSecretParams_IssueAuthCredentialDeterministic(
     randomness: &[u8; RANDOMNESS_LEN],
     uuid: Uuid,
     redemption_time: u32,
-) -> Serialized<AuthCredentialResponse> {
-    server_secret_params
-        .issue_auth_credential(*randomness, *uuid.as_bytes(), redemption_time)
-        .into()
+) -> Result<Serialized<AuthCredentialResponse>, ZkGroupVerificationFailure> {
+    Ok(server_secret_params
+        .issue_auth_credential(*randomness, *uuid.as_bytes(), redemption_time)?
+        .into())
 }
 
 #[bridge_fn_void]
@@ -23,8 +23,9 @@ fn benchmark_integration_auth(c: &mut Criterion) {
     // SERVER
     // Issue credential
     let randomness = zkgroup::TEST_ARRAY_32_2;
-    let auth_credential_response =
-        server_secret_params.issue_auth_credential(randomness, uid, redemption_time);
+    let auth_credential_response = server_secret_params
+        .issue_auth_credential(randomness, uid, redemption_time)
+        .unwrap();
 
     c.bench_function("issue_auth_credential", |b| {
         b.iter(|| server_secret_params.issue_auth_credential(randomness, uid, redemption_time))
@@ -277,9 +278,241 @@ pub fn benchmark_integration_profile(c: &mut Criterion) {
     });
 }
 
+pub fn benchmark_integration_pni(c: &mut Criterion) {
+    // SERVER
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    // CLIENT
+    let master_key = zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1);
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::derive_from_master_key(master_key);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let aci = zkgroup::TEST_ARRAY_16;
+    let pni = zkgroup::TEST_ARRAY_16_1;
+    let profile_key =
+        zkgroup::profiles::ProfileKey::create(zkgroup::common::constants::TEST_ARRAY_32_1);
+    let profile_key_commitment = profile_key.get_commitment(aci);
+
+    // Create context and request
+    let randomness = zkgroup::TEST_ARRAY_32_3;
+
+    let context = server_public_params.create_pni_credential_request_context(
+        randomness,
+        aci,
+        pni,
+        profile_key,
+    );
+
+    c.bench_function("create_pni_credential_request_context", |b| {
+        b.iter(|| {
+            server_public_params.create_pni_credential_request_context(
+                randomness,
+                aci,
+                pni,
+                profile_key,
+            )
+        })
+    });
+
+    let request = context.get_request();
+
+    // SERVER
+
+    let randomness = zkgroup::TEST_ARRAY_32_4;
+    let response = server_secret_params
+        .issue_pni_credential(randomness, &request, aci, pni, profile_key_commitment)
+        .unwrap();
+
+    c.bench_function("issue_pni_credential", |b| {
+        b.iter(|| {
+            server_secret_params
+                .issue_pni_credential(randomness, &request, aci, pni, profile_key_commitment)
+                .unwrap()
+        })
+    });
+
+    // CLIENT
+    let pni_credential = server_public_params
+        .receive_pni_credential(&context, &response)
+        .unwrap();
+
+    c.bench_function("receive_pni_credential", |b| {
+        b.iter(|| {
+            server_public_params
+                .receive_pni_credential(&context, &response)
+                .unwrap()
+        })
+    });
+
+    // Create presentation
+    let randomness = zkgroup::TEST_ARRAY_32_5;
+
+    let presentation_v2 = server_public_params.create_pni_credential_presentation_v2(
+        randomness,
+        group_secret_params,
+        pni_credential,
+    );
+
+    c.bench_function("create_pni_credential_presentation_v2", |b| {
+        b.iter(|| {
+            server_public_params.create_pni_credential_presentation_v2(
+                randomness,
+                group_secret_params,
+                pni_credential,
+            )
+        })
+    });
+
+    // SERVER
+    server_secret_params
+        .verify_pni_credential_presentation_v2(group_public_params, &presentation_v2)
+        .unwrap();
+
+    c.bench_function("verify_pni_credential_presentation_v2", |b| {
+        b.iter(|| {
+            server_secret_params
+                .verify_pni_credential_presentation_v2(group_public_params, &presentation_v2)
+        })
+    });
+}
+
+pub fn benchmark_integration_receipt(c: &mut Criterion) {
+    // SERVER
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    // CLIENT
+    let receipt_serial = zkgroup::TEST_ARRAY_16;
+    let randomness = zkgroup::TEST_ARRAY_32_1;
+
+    let context =
+        server_public_params.create_receipt_credential_request_context(randomness, receipt_serial);
+
+    c.bench_function("create_receipt_credential_request_context", |b| {
+        b.iter(|| {
+            server_public_params
+                .create_receipt_credential_request_context(randomness, receipt_serial)
+        })
+    });
+
+    let request = context.get_request();
+
+    // SERVER
+    let randomness = zkgroup::TEST_ARRAY_32_2;
+    let receipt_expiration_time = 86400 * 30;
+    let receipt_level = 3;
+    let response = server_secret_params.issue_receipt_credential(
+        randomness,
+        &request,
+        receipt_expiration_time,
+        receipt_level,
+    );
+
+    c.bench_function("issue_receipt_credential", |b| {
+        b.iter(|| {
+            server_secret_params.issue_receipt_credential(
+                randomness,
+                &request,
+                receipt_expiration_time,
+                receipt_level,
+            )
+        })
+    });
+
+    // CLIENT
+    let receipt_credential = server_public_params
+        .receive_receipt_credential(&context, &response)
+        .unwrap();
+
+    c.bench_function("receive_receipt_credential", |b| {
+        b.iter(|| {
+            server_public_params
+                .receive_receipt_credential(&context, &response)
+                .unwrap()
+        })
+    });
+
+    // Create presentation
+    let randomness = zkgroup::TEST_ARRAY_32_3;
+
+    let presentation = server_public_params
+        .create_receipt_credential_presentation(randomness, &receipt_credential);
+
+    c.bench_function("create_receipt_credential_presentation", |b| {
+        b.iter(|| {
+            server_public_params
+                .create_receipt_credential_presentation(randomness, &receipt_credential)
+        })
+    });
+
+    // SERVER
+    server_secret_params
+        .verify_receipt_credential_presentation(&presentation)
+        .unwrap();
+
+    c.bench_function("verify_receipt_credential_presentation", |b| {
+        b.iter(|| server_secret_params.verify_receipt_credential_presentation(&presentation))
+    });
+}
+
+pub fn benchmark_hot_group_cache(c: &mut Criterion) {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let master_key = zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1);
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::derive_from_master_key(master_key);
+    let group_public_params = group_secret_params.get_public_params();
+    let group_id = group_public_params.get_group_identifier();
+    let group_public_params_bytes = bincode::serialize(&group_public_params).unwrap();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let auth_credential_response = server_secret_params
+        .issue_auth_credential(zkgroup::TEST_ARRAY_32_2, uid, redemption_time)
+        .unwrap();
+    let auth_credential = server_public_params
+        .receive_auth_credential(uid, redemption_time, &auth_credential_response)
+        .unwrap();
+    let presentation = server_public_params.create_auth_credential_presentation(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        auth_credential,
+    );
+
+    c.bench_function("verify_auth_credential_presentation_parsing_group", |b| {
+        b.iter(|| {
+            let params = zkgroup::groups::GroupPublicParams::from_bytes_validated(
+                &group_public_params_bytes,
+            )
+            .unwrap();
+            server_secret_params
+                .verify_auth_credential_presentation(params, &presentation)
+                .unwrap();
+        })
+    });
+
+    let cache = {
+        let group_public_params_bytes = group_public_params_bytes.clone();
+        zkgroup::groups::HotGroupCache::new(16, move |_| Some(group_public_params_bytes.clone()))
+    };
+    c.bench_function("verify_auth_credential_presentation_hot_group_cache", |b| {
+        b.iter(|| {
+            cache
+                .verify_auth(&server_secret_params, &group_id, &presentation)
+                .unwrap();
+        })
+    });
+}
+
 criterion_group!(
     benches,
     benchmark_integration_profile,
-    benchmark_integration_auth
+    benchmark_integration_auth,
+    benchmark_integration_pni,
+    benchmark_integration_receipt,
+    benchmark_hot_group_cache
 );
 criterion_main!(benches);
@@ -8,7 +8,40 @@ pub mod groups;
 pub mod profiles;
 pub mod receipts;
 
+pub mod armor;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+pub mod compact;
+#[cfg(feature = "threading")]
+pub mod concurrent_verifier;
+pub mod diagnosis;
+pub mod envelope;
+pub mod issuance_summary;
+pub mod multi_presentation_verifier;
+pub mod params_bundle;
+pub mod presentation_file;
+pub mod proof_sizes;
 pub mod server_params;
+pub mod verifiable_presentation;
 
+pub use armor::Armored;
+#[cfg(feature = "cbor")]
+pub use cbor::CborSerializable;
+pub use compact::CompactSerializable;
+#[cfg(feature = "threading")]
+pub use concurrent_verifier::ConcurrentVerifier;
+pub use diagnosis::{diagnose_credential, CredentialKind, DiagnosisReport};
+pub use envelope::Envelope;
+pub use issuance_summary::IssuanceSummary;
+pub use multi_presentation_verifier::MultiPresentationVerifier;
+pub use params_bundle::ParamsBundle;
+pub use presentation_file::{write_framed_presentation, VerificationReport};
+pub use proof_sizes::{
+    estimate_presentation_size, proof_sizes, validate_presentation_framing, version_size_delta,
+    PresentationVersion,
+};
+pub use server_params::validate_signature_encoding;
+pub use server_params::AuthOnlyServerParams;
 pub use server_params::ServerPublicParams;
 pub use server_params::ServerSecretParams;
+pub use verifiable_presentation::{DecodedPresentation, VerifiablePresentation};
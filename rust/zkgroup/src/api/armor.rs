@@ -0,0 +1,226 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::api::groups::GroupPublicParams;
+use crate::api::ServerPublicParams;
+use crate::common::errors::*;
+use crate::common::serialization::deserialize_strict;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const LINE_LEN: usize = 64;
+
+/// A PEM-like text encoding, for config files and copying by hand.
+///
+/// The standard serialization is written as base64 between `-----BEGIN <LABEL>-----` and
+/// `-----END <LABEL>-----` lines, followed by a `=`-prefixed CRC-24 of the bytes, as in OpenPGP
+/// armor. Any single changed character is rejected: it either breaks the base64, or changes the
+/// bytes or the checksum in a way CRC-24 always detects.
+pub trait Armored: Serialize + DeserializeOwned {
+    const ARMOR_LABEL: &'static str;
+
+    fn to_armored(&self) -> String {
+        let bytes = bincode::serialize(self).expect("can serialize");
+        let body = base64_encode(&bytes);
+        let mut text = format!("-----BEGIN {}-----\n", Self::ARMOR_LABEL);
+        for line in body.as_bytes().chunks(LINE_LEN) {
+            text.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+            text.push('\n');
+        }
+        text.push('=');
+        text.push_str(&base64_encode(&crc24(&bytes).to_be_bytes()[1..]));
+        text.push('\n');
+        text.push_str(&format!("-----END {}-----\n", Self::ARMOR_LABEL));
+        text
+    }
+
+    /// Fails if the labels aren't for `Self`, the checksum doesn't match, or the bytes aren't a
+    /// valid serialization of `Self`. Surrounding whitespace and CRLF line endings are allowed.
+    fn from_armored(text: &str) -> Result<Self, ZkGroupDeserializationFailure> {
+        let mut lines = text.trim().lines().map(str::trim);
+        if lines.next() != Some(format!("-----BEGIN {}-----", Self::ARMOR_LABEL).as_str()) {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        if lines.next_back() != Some(format!("-----END {}-----", Self::ARMOR_LABEL).as_str()) {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        let checksum = lines
+            .next_back()
+            .and_then(|line| line.strip_prefix('='))
+            .ok_or(ZkGroupDeserializationFailure)?;
+        let body: String = lines.collect();
+
+        let bytes = base64_decode(&body)?;
+        let checksum = base64_decode(checksum)?;
+        if checksum[..] != crc24(&bytes).to_be_bytes()[1..] {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        deserialize_strict(&bytes)
+    }
+}
+
+impl Armored for ServerPublicParams {
+    const ARMOR_LABEL: &'static str = "ZKGROUP SERVER PUBLIC PARAMS";
+}
+
+impl Armored for GroupPublicParams {
+    const ARMOR_LABEL: &'static str = "ZKGROUP GROUP PUBLIC PARAMS";
+}
+
+/// The OpenPGP CRC-24 (RFC 4880, section 6.1).
+fn crc24(bytes: &[u8]) -> u32 {
+    const INIT: u32 = 0xb704ce;
+    const POLY: u32 = 0x1864cfb;
+    let mut crc = INIT;
+    for &byte in bytes {
+        crc ^= u32::from(byte) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= POLY;
+            }
+        }
+    }
+    crc & 0xffffff
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len() * 4 / 3 + 4);
+    for chunk in bytes.chunks(3) {
+        let mut group = [0u8; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (bits >> (18 - 6 * i)) & 0x3f;
+                text.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+/// Decodes padded standard base64, rejecting any encoding but the canonical one, so that every
+/// character of the text affects the decoded bytes.
+fn base64_decode(text: &str) -> Result<Vec<u8>, ZkGroupDeserializationFailure> {
+    let chunks = text.as_bytes().chunks_exact(4);
+    if !chunks.remainder().is_empty() {
+        return Err(ZkGroupDeserializationFailure);
+    }
+    let chunk_count = chunks.len();
+    let mut bytes = Vec::with_capacity(chunk_count * 3);
+    for (chunk_index, chunk) in chunks.enumerate() {
+        let is_last = chunk_index + 1 == chunk_count;
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && !is_last) {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        let mut bits = 0u32;
+        for &c in &chunk[..4 - padding] {
+            let value = BASE64_ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .ok_or(ZkGroupDeserializationFailure)?;
+            bits = (bits << 6) | value as u32;
+        }
+        bits <<= 6 * padding;
+        let [_, b0, b1, b2] = bits.to_be_bytes();
+        let decoded = [b0, b1, b2];
+        let len = 3 - padding;
+        if decoded[len..].iter().any(|&b| b != 0) {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        bytes.extend_from_slice(&decoded[..len]);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use crate::common::constants::*;
+
+    #[test]
+    fn test_base64_and_crc24() {
+        for (bytes, text) in &[
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(*text, base64_encode(bytes));
+            assert_eq!(*bytes, &base64_decode(text).unwrap()[..]);
+        }
+        // Non-canonical trailing bits and misplaced padding.
+        assert!(base64_decode("Zh==").is_err());
+        assert!(base64_decode("Zg==Zg==").is_err());
+        assert!(base64_decode("Zg=").is_err());
+
+        assert_eq!(0x21cf02, crc24(b"123456789"));
+    }
+
+    #[test]
+    fn test_armor_round_trip() {
+        let server_public_params =
+            api::ServerSecretParams::generate(TEST_ARRAY_32).get_public_params();
+        let text = server_public_params.to_armored();
+        assert!(text.starts_with("-----BEGIN ZKGROUP SERVER PUBLIC PARAMS-----\n"));
+        let parsed = ServerPublicParams::from_armored(&text.replace('\n', "\r\n")).unwrap();
+        assert_eq!(
+            bincode::serialize(&server_public_params).unwrap(),
+            bincode::serialize(&parsed).unwrap()
+        );
+
+        // The label must match the type.
+        assert!(GroupPublicParams::from_armored(&text).is_err());
+
+        let group_public_params =
+            api::groups::GroupSecretParams::generate(TEST_ARRAY_32_1).get_public_params();
+        let text = group_public_params.to_armored();
+        let parsed = GroupPublicParams::from_armored(&text).unwrap();
+        assert_eq!(
+            group_public_params.get_group_identifier(),
+            parsed.get_group_identifier()
+        );
+    }
+
+    #[test]
+    fn test_armor_rejects_single_character_changes() {
+        let text = api::groups::GroupSecretParams::generate(TEST_ARRAY_32_1)
+            .get_public_params()
+            .to_armored();
+        let header_len = text.find('\n').unwrap() + 1;
+        let footer_start = text.rfind("-----END").unwrap();
+        let mut replacements = BASE64_ALPHABET.to_vec();
+        replacements.push(b'=');
+        for i in header_len..footer_start {
+            if text.as_bytes()[i] == b'\n'
+                || (text.as_bytes()[i] == b'=' && text.as_bytes()[i - 1] == b'\n')
+            {
+                continue;
+            }
+            for &c in &replacements {
+                if c == text.as_bytes()[i] {
+                    continue;
+                }
+                let mut corrupted = text.clone().into_bytes();
+                corrupted[i] = c;
+                let corrupted = String::from_utf8(corrupted).unwrap();
+                assert!(
+                    GroupPublicParams::from_armored(&corrupted).is_err(),
+                    "accepted change at {}",
+                    i
+                );
+            }
+        }
+    }
+}
@@ -4,11 +4,27 @@
 //
 
 pub mod auth_credential;
+pub mod auth_credential_bare_presentation;
+pub mod auth_credential_device_tagged_presentation;
 pub mod auth_credential_presentation;
 pub mod auth_credential_response;
+pub mod auth_credential_timestamped_presentation;
+pub mod auth_credential_with_epoch;
+pub mod auth_credential_with_epoch_presentation;
+pub mod batch_auth_credential_response;
+pub mod membership_token;
+pub mod redacted_presentation;
 
 pub use auth_credential::AuthCredential;
+pub use auth_credential_bare_presentation::AuthCredentialBarePresentation;
+pub use auth_credential_device_tagged_presentation::AuthCredentialDeviceTaggedPresentation;
 pub use auth_credential_presentation::AnyAuthCredentialPresentation;
 pub use auth_credential_presentation::AuthCredentialPresentationV1;
 pub use auth_credential_presentation::AuthCredentialPresentationV2;
 pub use auth_credential_response::AuthCredentialResponse;
+pub use auth_credential_timestamped_presentation::AuthCredentialTimestampedPresentation;
+pub use auth_credential_with_epoch::AuthCredentialWithEpoch;
+pub use auth_credential_with_epoch_presentation::AuthCredentialWithEpochPresentation;
+pub use batch_auth_credential_response::BatchAuthCredentialResponse;
+pub use membership_token::MembershipToken;
+pub use redacted_presentation::RedactedPresentation;
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use curve25519_dalek::subtle::ConstantTimeEq;
 use serde::{Deserialize, Serialize};
 
 use crate::common::simple_types::*;
@@ -15,3 +16,30 @@ pub struct AuthCredential {
     pub(crate) uid: crypto::uid_struct::UidStruct,
     pub(crate) redemption_time: RedemptionTime,
 }
+
+impl AuthCredential {
+    /// A stable identifier for this credential, for correlating its issuance and use in logs.
+    ///
+    /// This is a `Sho` hash, under a label specific to the credential type, of exactly two things:
+    ///
+    /// - the credential's leading version byte, and
+    /// - the credential's `U` point, a random point the issuer chose and sent in the issuance
+    ///   response.
+    ///
+    /// It covers no secret (neither the scalar `t` nor the MAC `V`) and no attribute, such as the
+    /// uid or redemption time. The issuer can match the id to the response it sent; anyone else
+    /// learns nothing about the holder from it. Presentations never reveal `U`, so a verifier
+    /// can't link a presentation to the id.
+    pub fn credential_log_id(&self) -> [u8; 16] {
+        crypto::credentials::credential_log_id(
+            b"Signal_ZKGroup_20220301_AuthCredential_LogId",
+            self.reserved[0],
+            self.credential.U,
+        )
+    }
+
+    /// Checks (in constant time) whether this credential was issued for `expected`.
+    pub fn uid_matches(&self, expected: UidBytes) -> bool {
+        self.uid.bytes[..].ct_eq(&expected[..]).into()
+    }
+}
@@ -0,0 +1,26 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::simple_types::*;
+use crate::crypto;
+
+/// Proves possession of a valid auth credential without binding it to any group.
+///
+/// Unlike [`AuthCredentialPresentationV2`](super::AuthCredentialPresentationV2) this carries no
+/// uid ciphertext, so the verifier learns nothing about the holder beyond the redemption time.
+#[derive(Serialize, Deserialize)]
+pub struct AuthCredentialBarePresentation {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) proof: crypto::proofs::AuthCredentialBarePresentationProof,
+    pub(crate) redemption_time: RedemptionTime,
+}
+
+impl AuthCredentialBarePresentation {
+    pub fn get_redemption_time(&self) -> RedemptionTime {
+        self.redemption_time
+    }
+}
@@ -0,0 +1,43 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::api;
+use crate::common::simple_types::*;
+use crate::crypto;
+
+/// An auth credential presentation whose proof also covers a commitment to a client-chosen
+/// device tag.
+///
+/// See
+/// [`ServerPublicParams::create_auth_credential_presentation_v2_with_device_tag`](api::ServerPublicParams::create_auth_credential_presentation_v2_with_device_tag).
+#[derive(Serialize, Deserialize)]
+pub struct AuthCredentialDeviceTaggedPresentation {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) proof: crypto::proofs::AuthCredentialPresentationProofV2,
+    pub(crate) ciphertext: crypto::uid_encryption::Ciphertext,
+    pub(crate) redemption_time: RedemptionTime,
+    pub(crate) device_tag_commitment: [u8; 32],
+}
+
+impl AuthCredentialDeviceTaggedPresentation {
+    pub fn get_uuid_ciphertext(&self) -> api::groups::UuidCiphertext {
+        api::groups::UuidCiphertext {
+            reserved: Default::default(),
+            ciphertext: self.ciphertext,
+        }
+    }
+
+    pub fn get_redemption_time(&self) -> RedemptionTime {
+        self.redemption_time
+    }
+
+    /// Not trusted until the presentation has been verified; see
+    /// [`ServerSecretParams::verify_auth_credential_device_tagged_presentation`](api::ServerSecretParams::verify_auth_credential_device_tagged_presentation).
+    pub fn get_device_tag_commitment(&self) -> [u8; 32] {
+        self.device_tag_commitment
+    }
+}
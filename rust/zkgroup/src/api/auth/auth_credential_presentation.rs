@@ -6,6 +6,7 @@
 use crate::api;
 use crate::common::constants::*;
 use crate::common::errors::*;
+use crate::common::sho::*;
 use crate::common::simple_types::*;
 use crate::crypto;
 use serde::Serializer;
@@ -51,6 +52,33 @@ impl AuthCredentialPresentationV2 {
     pub fn get_redemption_time(&self) -> RedemptionTime {
         self.redemption_time
     }
+
+    /// Derives a pseudonym for rate-limiting the presenting member within this group.
+    ///
+    /// Uid encryption is deterministic per group, so the same member always gets the same token in
+    /// a given group, while tokens from different groups are unlinkable. Only meaningful once the
+    /// presentation has been verified against `group_public_params`.
+    pub fn derive_rate_limit_token(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+    ) -> [u8; 32] {
+        derive_rate_limit_token(self.ciphertext, group_public_params)
+    }
+}
+
+fn derive_rate_limit_token(
+    ciphertext: crypto::uid_encryption::Ciphertext,
+    group_public_params: api::groups::GroupPublicParams,
+) -> [u8; 32] {
+    let mut data = bincode::serialize(&ciphertext).expect("can serialize");
+    data.extend_from_slice(&group_public_params.get_group_identifier());
+    let mut sho = Sho::new(
+        b"Signal_ZKGroup_20220301_AuthCredentialPresentation_DeriveRateLimitToken",
+        &data,
+    );
+    let mut token = [0u8; 32];
+    token.copy_from_slice(&sho.squeeze(32));
+    token
 }
 
 pub enum AnyAuthCredentialPresentation {
@@ -60,14 +88,18 @@ pub enum AnyAuthCredentialPresentation {
 
 impl AnyAuthCredentialPresentation {
     pub fn new(presentation_bytes: &[u8]) -> Result<Self, ZkGroupDeserializationFailure> {
-        match presentation_bytes[0] {
-            PRESENTATION_VERSION_1 => {
+        match presentation_bytes.first() {
+            Some(&PRESENTATION_VERSION_1)
+                if presentation_bytes.len() == AUTH_CREDENTIAL_PRESENTATION_V1_LEN =>
+            {
                 match bincode::deserialize::<AuthCredentialPresentationV1>(presentation_bytes) {
                     Ok(presentation) => Ok(AnyAuthCredentialPresentation::V1(presentation)),
                     Err(_) => Err(ZkGroupDeserializationFailure),
                 }
             }
-            PRESENTATION_VERSION_2 => {
+            Some(&PRESENTATION_VERSION_2)
+                if presentation_bytes.len() == AUTH_CREDENTIAL_PRESENTATION_V2_LEN =>
+            {
                 match bincode::deserialize::<AuthCredentialPresentationV2>(presentation_bytes) {
                     Ok(presentation) => Ok(AnyAuthCredentialPresentation::V2(presentation)),
                     Err(_) => Err(ZkGroupDeserializationFailure),
@@ -98,6 +130,36 @@ impl AnyAuthCredentialPresentation {
             }
         }
     }
+
+    pub fn get_version(&self) -> api::PresentationVersion {
+        match self {
+            AnyAuthCredentialPresentation::V1(_) => api::PresentationVersion::AuthV1,
+            AnyAuthCredentialPresentation::V2(_) => api::PresentationVersion::AuthV2,
+        }
+    }
+
+    /// See [`AuthCredentialPresentationV2::derive_rate_limit_token`].
+    ///
+    /// V1 and V2 presentations from the same member in the same group get the same token.
+    pub fn derive_rate_limit_token(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+    ) -> [u8; 32] {
+        derive_rate_limit_token(self.get_uuid_ciphertext().ciphertext, group_public_params)
+    }
+
+    /// Packages the presentation with its pseudonym in `group_public_params`, for sharing outside
+    /// the group. See [`RedactedPresentation`](api::auth::RedactedPresentation) for what this does
+    /// and doesn't hide.
+    pub fn redact_for_sharing(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+    ) -> api::auth::RedactedPresentation {
+        api::auth::RedactedPresentation {
+            presentation: bincode::serialize(self).expect("can serialize"),
+            pseudonym: self.derive_rate_limit_token(group_public_params),
+        }
+    }
 }
 
 impl Serialize for AnyAuthCredentialPresentation {
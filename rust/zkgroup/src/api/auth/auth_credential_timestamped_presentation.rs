@@ -0,0 +1,40 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::api;
+use crate::common::simple_types::*;
+use crate::crypto;
+
+/// An auth credential presentation whose proof also covers the client's clock reading.
+///
+/// See
+/// [`ServerPublicParams::create_auth_credential_presentation_v2_timestamped`](api::ServerPublicParams::create_auth_credential_presentation_v2_timestamped).
+#[derive(Serialize, Deserialize)]
+pub struct AuthCredentialTimestampedPresentation {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) proof: crypto::proofs::AuthCredentialPresentationProofV2,
+    pub(crate) ciphertext: crypto::uid_encryption::Ciphertext,
+    pub(crate) redemption_time: RedemptionTime,
+    pub(crate) client_time: PresentationTimestamp,
+}
+
+impl AuthCredentialTimestampedPresentation {
+    pub fn get_uuid_ciphertext(&self) -> api::groups::UuidCiphertext {
+        api::groups::UuidCiphertext {
+            reserved: Default::default(),
+            ciphertext: self.ciphertext,
+        }
+    }
+
+    pub fn get_redemption_time(&self) -> RedemptionTime {
+        self.redemption_time
+    }
+
+    pub fn get_client_time(&self) -> PresentationTimestamp {
+        self.client_time
+    }
+}
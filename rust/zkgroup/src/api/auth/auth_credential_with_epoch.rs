@@ -0,0 +1,45 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use curve25519_dalek::subtle::ConstantTimeEq;
+use serde::{Deserialize, Serialize};
+
+use crate::common::simple_types::*;
+use crate::crypto;
+
+/// An auth credential bound to a revocation epoch.
+///
+/// The epoch is folded into the same attribute as the redemption time, so a credential with epoch
+/// 0 is indistinguishable from a plain [`AuthCredential`](super::AuthCredential).
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct AuthCredentialWithEpoch {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) credential: crypto::credentials::AuthCredential,
+    pub(crate) uid: crypto::uid_struct::UidStruct,
+    pub(crate) redemption_time: RedemptionTime,
+    pub(crate) epoch: AuthCredentialEpoch,
+}
+
+impl AuthCredentialWithEpoch {
+    /// See [`AuthCredential::credential_log_id`], which hashes the same fields of this type.
+    ///
+    /// [`AuthCredential::credential_log_id`]: crate::api::auth::AuthCredential::credential_log_id
+    pub fn credential_log_id(&self) -> [u8; 16] {
+        crypto::credentials::credential_log_id(
+            b"Signal_ZKGroup_20220301_AuthCredentialWithEpoch_LogId",
+            self.reserved[0],
+            self.credential.U,
+        )
+    }
+
+    /// Checks (in constant time) whether this credential was issued for `expected`.
+    pub fn uid_matches(&self, expected: UidBytes) -> bool {
+        self.uid.bytes[..].ct_eq(&expected[..]).into()
+    }
+
+    pub fn get_epoch(&self) -> AuthCredentialEpoch {
+        self.epoch
+    }
+}
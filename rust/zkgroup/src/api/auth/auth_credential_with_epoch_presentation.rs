@@ -0,0 +1,36 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::api;
+use crate::common::simple_types::*;
+use crate::crypto;
+
+#[derive(Serialize, Deserialize)]
+pub struct AuthCredentialWithEpochPresentation {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) proof: crypto::proofs::AuthCredentialPresentationProofV2,
+    pub(crate) ciphertext: crypto::uid_encryption::Ciphertext,
+    pub(crate) redemption_time: RedemptionTime,
+    pub(crate) epoch: AuthCredentialEpoch,
+}
+
+impl AuthCredentialWithEpochPresentation {
+    pub fn get_uuid_ciphertext(&self) -> api::groups::UuidCiphertext {
+        api::groups::UuidCiphertext {
+            reserved: Default::default(),
+            ciphertext: self.ciphertext,
+        }
+    }
+
+    pub fn get_redemption_time(&self) -> RedemptionTime {
+        self.redemption_time
+    }
+
+    pub fn get_epoch(&self) -> AuthCredentialEpoch {
+        self.epoch
+    }
+}
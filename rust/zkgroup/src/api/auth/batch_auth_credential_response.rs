@@ -0,0 +1,29 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use crate::common::simple_types::*;
+use crate::crypto;
+use serde::{Deserialize, Serialize};
+
+/// Several auth credentials for one uid, issued together under a single proof.
+///
+/// See
+/// [`ServerSecretParams::issue_auth_credential_batch`](crate::api::ServerSecretParams::issue_auth_credential_batch).
+#[derive(Serialize, Deserialize)]
+pub struct BatchAuthCredentialResponse {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) credentials: Vec<crypto::credentials::AuthCredential>,
+    pub(crate) proof: crypto::proofs::AuthCredentialBatchIssuanceProof,
+}
+
+impl BatchAuthCredentialResponse {
+    pub fn len(&self) -> usize {
+        self.credentials.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.credentials.is_empty()
+    }
+}
@@ -0,0 +1,32 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::simple_types::*;
+use crate::crypto;
+
+/// A short-lived token recording that the holder of a pseudonym presented a valid auth credential
+/// for a group, so the server can skip re-verifying the presentation until the token expires.
+///
+/// Only the issuing server can check a token. See
+/// [`ServerSecretParams::issue_membership_token`](crate::api::ServerSecretParams::issue_membership_token).
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct MembershipToken {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) token: crypto::credentials::MembershipToken,
+    pub(crate) pseudonym: [u8; 32],
+    pub(crate) expiration: u64,
+}
+
+impl MembershipToken {
+    pub fn get_pseudonym(&self) -> [u8; 32] {
+        self.pseudonym
+    }
+
+    pub fn get_expiration(&self) -> u64 {
+        self.expiration
+    }
+}
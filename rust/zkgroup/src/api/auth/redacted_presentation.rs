@@ -0,0 +1,43 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::auth::AnyAuthCredentialPresentation;
+use crate::common::errors::*;
+
+/// An auth presentation packaged for sharing outside the group, such as in an abuse report.
+///
+/// Nothing can be stripped without breaking verification: the proof covers the uid ciphertext
+/// and the redemption time, so the whole presentation is kept as is. What the recipient should
+/// know:
+///
+/// - Verifying it takes the issuing server's `ServerSecretParams` and the group's public params.
+///   Auth credentials are not publicly verifiable, so a third party can only check it through the
+///   server, e.g. with
+///   [`verify_redacted_presentation`](crate::api::ServerSecretParams::verify_redacted_presentation).
+/// - The uid ciphertext can still be decrypted by anyone with the group's `GroupSecretParams`,
+///   which every group member has.
+/// - The pseudonym is the presentation's
+///   [rate-limit token](AnyAuthCredentialPresentation::derive_rate_limit_token). It is the same
+///   for every presentation by the same member in the same group, so reports can be correlated
+///   without decrypting anything, and it can't be linked across groups.
+#[derive(Serialize, Deserialize)]
+pub struct RedactedPresentation {
+    pub(crate) presentation: Vec<u8>,
+    pub(crate) pseudonym: [u8; 32],
+}
+
+impl RedactedPresentation {
+    pub fn get_presentation(
+        &self,
+    ) -> Result<AnyAuthCredentialPresentation, ZkGroupDeserializationFailure> {
+        AnyAuthCredentialPresentation::new(&self.presentation)
+    }
+
+    pub fn get_pseudonym(&self) -> [u8; 32] {
+        self.pseudonym
+    }
+}
@@ -0,0 +1,131 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::api::auth::*;
+use crate::api::profiles::*;
+use crate::api::receipts::*;
+use crate::common::errors::*;
+
+/// A self-describing CBOR encoding, for long-term storage.
+///
+/// Each value is encoded as a CBOR map keyed by its field names, with nested structures
+/// (proofs, ciphertexts) encoded the same way. Points, scalars, and fixed-size byte fields are
+/// CBOR arrays of unsigned integers. Field names are part of the schema and will not change for
+/// existing types.
+///
+/// This is **not** the wire format; send the standard (bincode) serialization to other parties.
+pub trait CborSerializable: Serialize + DeserializeOwned {
+    fn to_cbor(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(self, &mut bytes).expect("can serialize");
+        bytes
+    }
+
+    /// Fails if `bytes` is not a single valid encoding of `Self`, including if there is trailing
+    /// data.
+    fn from_cbor(bytes: &[u8]) -> Result<Self, ZkGroupDeserializationFailure> {
+        let mut reader = bytes;
+        let value =
+            ciborium::de::from_reader(&mut reader).map_err(|_| ZkGroupDeserializationFailure)?;
+        if !reader.is_empty() {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        Ok(value)
+    }
+}
+
+impl CborSerializable for AuthCredentialBarePresentation {}
+impl CborSerializable for AuthCredentialDeviceTaggedPresentation {}
+impl CborSerializable for AuthCredentialPresentationV1 {}
+impl CborSerializable for AuthCredentialPresentationV2 {}
+impl CborSerializable for AuthCredentialResponse {}
+impl CborSerializable for AuthCredentialTimestampedPresentation {}
+impl CborSerializable for AuthCredentialWithEpochPresentation {}
+impl CborSerializable for BatchAuthCredentialResponse {}
+impl CborSerializable for PniCredentialPresentationV1 {}
+impl CborSerializable for PniCredentialPresentationV2 {}
+impl CborSerializable for PniCredentialResponse {}
+impl CborSerializable for ProfileKeyCredentialHidingPresentation {}
+impl CborSerializable for ProfileKeyCredentialPresentationV1 {}
+impl CborSerializable for ProfileKeyCredentialPresentationV2 {}
+impl CborSerializable for ProfileKeyCredentialResponse {}
+impl CborSerializable for ProfileKeyEqualityPresentation {}
+impl CborSerializable for ReceiptCredentialPresentation {}
+impl CborSerializable for ReceiptCredentialResponse {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{ServerPublicParams, ServerSecretParams};
+    use crate::common::constants::*;
+
+    fn assert_round_trip<T: CborSerializable>(value: &T) {
+        let cbor = value.to_cbor();
+        let decoded = T::from_cbor(&cbor).unwrap();
+        assert_eq!(
+            bincode::serialize(value).unwrap(),
+            bincode::serialize(&decoded).unwrap()
+        );
+
+        let mut with_trailing_data = cbor;
+        with_trailing_data.push(0);
+        assert!(T::from_cbor(&with_trailing_data).is_err());
+    }
+
+    #[test]
+    fn test_cbor_round_trip() {
+        let server_secret_params = ServerSecretParams::generate(TEST_ARRAY_32);
+        let server_public_params: ServerPublicParams = server_secret_params.get_public_params();
+        let group_secret_params = crate::api::groups::GroupSecretParams::generate(TEST_ARRAY_32_1);
+        let uid = TEST_ARRAY_16;
+        let redemption_time = 123456;
+
+        let response = server_secret_params
+            .issue_auth_credential(TEST_ARRAY_32_2, uid, redemption_time)
+            .unwrap();
+        assert_round_trip(&response);
+
+        let credential = server_public_params
+            .receive_auth_credential(uid, redemption_time, &response)
+            .unwrap();
+        let presentation = server_public_params.create_auth_credential_presentation_v2(
+            TEST_ARRAY_32_3,
+            group_secret_params,
+            credential,
+        );
+        assert_round_trip(&presentation);
+
+        // Field names are part of the schema.
+        let cbor = presentation.to_cbor();
+        assert!(cbor
+            .windows(b"redemption_time".len())
+            .any(|w| w == b"redemption_time"));
+
+        let context = server_public_params
+            .create_receipt_credential_request_context(TEST_ARRAY_32_4, TEST_ARRAY_16);
+        let response = server_secret_params.issue_receipt_credential(
+            TEST_ARRAY_32_5,
+            &context.get_request(),
+            86400,
+            1,
+        );
+        assert_round_trip(&response);
+        let credential = server_public_params
+            .receive_receipt_credential(&context, &response)
+            .unwrap();
+        assert_round_trip(
+            &server_public_params
+                .create_receipt_credential_presentation(TEST_ARRAY_32_5, &credential),
+        );
+
+        assert!(AuthCredentialPresentationV2::from_cbor(
+            &bincode::serialize(&presentation).unwrap()
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,93 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::api::auth::*;
+use crate::api::groups::*;
+use crate::api::profiles::*;
+use crate::api::receipts::*;
+use crate::api::server_params::RESERVED_PERSONALIZED;
+use crate::api::{AuthOnlyServerParams, ServerPublicParams, ServerSecretParams};
+use crate::common::constants::*;
+use crate::common::errors::*;
+use crate::common::serialization::deserialize_strict;
+
+/// A storage-only encoding that drops the leading reserved byte.
+///
+/// Only types whose leading byte is purely reserved implement this; presentations are excluded
+/// because their leading byte is a version tag. The reserved byte is always zero for values
+/// produced by this crate and is restored as zero on read. The exception is server params, whose
+/// reserved byte records whether a personalization follows; on read it is recovered from the
+/// length.
+///
+/// Compact blobs are **not** wire-compatible with the standard serialization and must not be sent
+/// to other parties or passed to the regular deserializers.
+pub trait CompactSerializable: Serialize + DeserializeOwned {
+    fn serialize_compact(&self) -> Vec<u8> {
+        let mut bytes = bincode::serialize(self).expect("can serialize");
+        bytes.drain(..RESERVED_LEN);
+        bytes
+    }
+
+    fn deserialize_compact(compact_bytes: &[u8]) -> Result<Self, ZkGroupDeserializationFailure> {
+        let mut bytes = Vec::with_capacity(RESERVED_LEN + compact_bytes.len());
+        bytes.extend_from_slice(&[0u8; RESERVED_LEN]);
+        bytes.extend_from_slice(compact_bytes);
+        bincode::deserialize(&bytes).map_err(|_| ZkGroupDeserializationFailure)
+    }
+}
+
+impl CompactSerializable for AuthCredential {}
+impl CompactSerializable for AuthOnlyServerParams {
+    fn deserialize_compact(compact_bytes: &[u8]) -> Result<Self, ZkGroupDeserializationFailure> {
+        deserialize_compact_server_params(compact_bytes)
+    }
+}
+impl CompactSerializable for AuthCredentialResponse {}
+impl CompactSerializable for BatchAuthCredentialResponse {}
+impl CompactSerializable for GroupPublicParams {}
+impl CompactSerializable for GroupSecretParams {}
+impl CompactSerializable for PniCredential {}
+impl CompactSerializable for PniCredentialRequestContext {}
+impl CompactSerializable for PniCredentialResponse {}
+impl CompactSerializable for ProfileKeyCiphertext {}
+impl CompactSerializable for ProfileKeyCommitment {}
+impl CompactSerializable for ProfileKeyCredential {}
+impl CompactSerializable for ProfileKeyCredentialRequest {}
+impl CompactSerializable for ProfileKeyCredentialRequestContext {}
+impl CompactSerializable for ProfileKeyCredentialResponse {}
+impl CompactSerializable for ReceiptCredential {}
+impl CompactSerializable for ReceiptCredentialRequest {}
+impl CompactSerializable for ReceiptCredentialRequestContext {}
+impl CompactSerializable for ReceiptCredentialResponse {}
+impl CompactSerializable for ServerPublicParams {
+    fn deserialize_compact(compact_bytes: &[u8]) -> Result<Self, ZkGroupDeserializationFailure> {
+        deserialize_compact_server_params(compact_bytes)
+    }
+}
+impl CompactSerializable for ServerSecretParams {
+    fn deserialize_compact(compact_bytes: &[u8]) -> Result<Self, ZkGroupDeserializationFailure> {
+        deserialize_compact_server_params(compact_bytes)
+    }
+}
+impl CompactSerializable for UuidCiphertext {}
+
+/// Exactly one of the two possible reserved bytes gives a value that uses every byte: without a
+/// personalization the extra bytes would be left over, and with one they would be missing.
+fn deserialize_compact_server_params<T: CompactSerializable>(
+    compact_bytes: &[u8],
+) -> Result<T, ZkGroupDeserializationFailure> {
+    [0, RESERVED_PERSONALIZED]
+        .iter()
+        .find_map(|&reserved| {
+            let mut bytes = Vec::with_capacity(RESERVED_LEN + compact_bytes.len());
+            bytes.push(reserved);
+            bytes.extend_from_slice(compact_bytes);
+            deserialize_strict(&bytes).ok()
+        })
+        .ok_or(ZkGroupDeserializationFailure)
+}
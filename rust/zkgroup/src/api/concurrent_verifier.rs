@@ -0,0 +1,165 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::api;
+use crate::api::auth::AnyAuthCredentialPresentation;
+use crate::api::groups::GroupPublicParams;
+use crate::common::errors::*;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Verifies auth credential presentations on a fixed set of worker threads, so that a burst of
+/// requests never runs more than `max_threads` proof verifications at once.
+///
+/// The threads are started by [`new`](Self::new) and reused by every call to
+/// [`verify_all`](Self::verify_all), including concurrent calls from different threads, which
+/// share the same bound. The threads exit when the verifier is dropped.
+pub struct ConcurrentVerifier {
+    jobs: Mutex<Option<mpsc::Sender<Job>>>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl ConcurrentVerifier {
+    /// Panics if `max_threads` is zero.
+    pub fn new(max_threads: usize) -> Self {
+        assert!(max_threads > 0, "at least one thread required");
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let queue = Arc::new(Mutex::new(queue));
+        let workers = (0..max_threads)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || loop {
+                    // The lock is released before the job runs, so other workers can pick up jobs.
+                    let job = match queue.lock() {
+                        Ok(queue) => queue.recv(),
+                        Err(_) => return,
+                    };
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => return,
+                    }
+                })
+            })
+            .collect();
+        Self {
+            jobs: Mutex::new(Some(jobs)),
+            workers,
+        }
+    }
+
+    /// Verifies each presentation against its group, returning results in the same order as
+    /// `items`.
+    pub fn verify_all(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        items: Vec<(GroupPublicParams, AnyAuthCredentialPresentation)>,
+    ) -> Vec<Result<(), ZkGroupVerificationFailure>> {
+        let jobs = self
+            .jobs
+            .lock()
+            .expect("jobs lock is never held across a panic")
+            .clone()
+            .expect("only taken on drop");
+        let server_secret_params = Arc::new(*server_secret_params);
+        let (results_sender, results) = mpsc::channel();
+
+        let count = items.len();
+        for (index, (group_public_params, presentation)) in items.into_iter().enumerate() {
+            let server_secret_params = Arc::clone(&server_secret_params);
+            let results_sender = results_sender.clone();
+            jobs.send(Box::new(move || {
+                let result = server_secret_params
+                    .verify_auth_credential_presentation(group_public_params, &presentation);
+                // The receiver only goes away if verify_all itself has panicked.
+                let _ = results_sender.send((index, result));
+            }))
+            .expect("workers run until the verifier is dropped");
+        }
+        drop(results_sender);
+
+        let mut ordered: Vec<_> = (0..count)
+            .map(|_| Err(ZkGroupVerificationFailure))
+            .collect();
+        for (index, result) in results {
+            ordered[index] = result;
+        }
+        ordered
+    }
+}
+
+impl Drop for ConcurrentVerifier {
+    fn drop(&mut self) {
+        // Closing the queue makes each worker exit once it is drained.
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.take();
+        }
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::constants::*;
+
+    #[test]
+    fn test_verify_all_preserves_order() {
+        let server_secret_params = api::ServerSecretParams::generate(TEST_ARRAY_32);
+        let server_public_params = server_secret_params.get_public_params();
+        let group_secret_params = api::groups::GroupSecretParams::generate(TEST_ARRAY_32_1);
+        let group_public_params = group_secret_params.get_public_params();
+        let other_group_public_params =
+            api::groups::GroupSecretParams::generate(TEST_ARRAY_32_2).get_public_params();
+
+        let response = server_secret_params
+            .issue_auth_credential(TEST_ARRAY_32_3, TEST_ARRAY_16, 123456)
+            .unwrap();
+        let credential = server_public_params
+            .receive_auth_credential(TEST_ARRAY_16, 123456, &response)
+            .unwrap();
+        let present = || {
+            server_public_params.create_auth_credential_presentation(
+                TEST_ARRAY_32_4,
+                group_secret_params,
+                credential,
+            )
+        };
+
+        let verifier = ConcurrentVerifier::new(2);
+        let expected = [true, false, true, true, false, false, true];
+        let items = expected
+            .iter()
+            .map(|&valid| {
+                let group = if valid {
+                    group_public_params
+                } else {
+                    other_group_public_params
+                };
+                (group, present())
+            })
+            .collect();
+        let results = verifier.verify_all(&server_secret_params, items);
+        assert_eq!(
+            results.iter().map(Result::is_ok).collect::<Vec<_>>(),
+            expected
+        );
+
+        // The pool is reused across calls.
+        assert!(verifier
+            .verify_all(&server_secret_params, vec![])
+            .is_empty());
+        let results = verifier.verify_all(
+            &server_secret_params,
+            vec![(group_public_params, present())],
+        );
+        assert!(results[0].is_ok());
+    }
+}
@@ -0,0 +1,227 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::fmt;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use serde::de::DeserializeOwned;
+
+use crate::api::auth::{AuthCredential, AuthCredentialWithEpoch};
+use crate::api::profiles::{PniCredential, ProfileKeyCredential};
+use crate::api::receipts::ReceiptCredential;
+use crate::common::constants::*;
+use crate::common::serialization::deserialize_strict;
+use crate::common::simple_types::*;
+
+const AUTH_CREDENTIAL_WITH_EPOCH_LEN: usize = AUTH_CREDENTIAL_LEN + 4;
+
+/// Every credential starts with a reserved byte, then the MAC's `t`, `U`, and `V`.
+const MAC_FIELDS: [(&str, usize); 3] = [("t", 32), ("U", 32), ("V", 32)];
+
+/// A client-held credential type, as recognized by [`diagnose_credential`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CredentialKind {
+    Auth,
+    AuthWithEpoch,
+    ProfileKey,
+    Pni,
+    Receipt,
+}
+
+impl CredentialKind {
+    fn from_len(len: usize) -> Option<Self> {
+        match len {
+            AUTH_CREDENTIAL_LEN => Some(Self::Auth),
+            AUTH_CREDENTIAL_WITH_EPOCH_LEN => Some(Self::AuthWithEpoch),
+            PROFILE_KEY_CREDENTIAL_LEN => Some(Self::ProfileKey),
+            PNI_CREDENTIAL_LEN => Some(Self::Pni),
+            RECEIPT_CREDENTIAL_LEN => Some(Self::Receipt),
+            _ => None,
+        }
+    }
+}
+
+/// What [`diagnose_credential`] could tell about a credential blob.
+///
+/// Only public fields are reported. The MAC, uids, profile keys, and receipt serials are never
+/// included, even when the blob deserializes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DiagnosisReport {
+    pub len: usize,
+    /// Credentials are recognized by length, since they carry no type tag.
+    pub kind: Option<CredentialKind>,
+    /// The leading reserved byte, which is zero in every credential this crate produces.
+    pub reserved: Option<u8>,
+    /// The first field that failed to decode, if any. "credential" means the MAC decoded but
+    /// something after it did not.
+    pub corrupt_field: Option<&'static str>,
+    pub deserialized: bool,
+    pub redemption_time: Option<RedemptionTime>,
+    pub epoch: Option<AuthCredentialEpoch>,
+    pub receipt_expiration_time: Option<ReceiptExpirationTime>,
+    pub receipt_level: Option<ReceiptLevel>,
+}
+
+impl DiagnosisReport {
+    /// A blob that is well-formed can only fail to present because of a mismatch with the
+    /// server's keys or the group, not because of corruption on the client.
+    pub fn is_well_formed(&self) -> bool {
+        self.deserialized && self.reserved == Some(0)
+    }
+}
+
+impl fmt::Display for DiagnosisReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            Some(kind) => writeln!(f, "type: {:?} ({} bytes)", kind, self.len)?,
+            None => writeln!(f, "type: unrecognized ({} bytes)", self.len)?,
+        }
+        if let Some(reserved) = self.reserved {
+            writeln!(f, "reserved byte: {}", reserved)?;
+        }
+        if let Some(field) = self.corrupt_field {
+            writeln!(f, "corrupt field: {}", field)?;
+        }
+        writeln!(f, "deserialized: {}", self.deserialized)?;
+        if let Some(redemption_time) = self.redemption_time {
+            writeln!(f, "redemption time: {}", redemption_time)?;
+        }
+        if let Some(epoch) = self.epoch {
+            writeln!(f, "epoch: {}", epoch)?;
+        }
+        if let Some(expiration) = self.receipt_expiration_time {
+            writeln!(f, "receipt expiration time: {}", expiration)?;
+        }
+        if let Some(level) = self.receipt_level {
+            writeln!(f, "receipt level: {}", level)?;
+        }
+        Ok(())
+    }
+}
+
+/// Inspects a serialized client credential without verifying it, for triaging credentials that
+/// fail to present.
+///
+/// Never fails: anything that can't be determined is left empty in the report.
+pub fn diagnose_credential(bytes: &[u8]) -> DiagnosisReport {
+    let mut report = DiagnosisReport {
+        len: bytes.len(),
+        kind: CredentialKind::from_len(bytes.len()),
+        reserved: bytes.first().copied(),
+        ..Default::default()
+    };
+    let kind = match report.kind {
+        Some(kind) => kind,
+        None => return report,
+    };
+
+    let mut start = RESERVED_LEN;
+    for (index, (name, len)) in MAC_FIELDS.iter().enumerate() {
+        let field = &bytes[start..start + len];
+        let ok = if index == 0 {
+            deserialize_strict::<Scalar>(field).is_ok()
+        } else {
+            deserialize_strict::<RistrettoPoint>(field).is_ok()
+        };
+        if !ok {
+            report.corrupt_field = Some(name);
+            return report;
+        }
+        start += len;
+    }
+
+    match kind {
+        CredentialKind::Auth => {
+            if let Some(credential) = parse::<AuthCredential>(bytes, &mut report) {
+                report.redemption_time = Some(credential.redemption_time);
+            }
+        }
+        CredentialKind::AuthWithEpoch => {
+            if let Some(credential) = parse::<AuthCredentialWithEpoch>(bytes, &mut report) {
+                report.redemption_time = Some(credential.redemption_time);
+                report.epoch = Some(credential.epoch);
+            }
+        }
+        CredentialKind::ProfileKey => {
+            parse::<ProfileKeyCredential>(bytes, &mut report);
+        }
+        CredentialKind::Pni => {
+            parse::<PniCredential>(bytes, &mut report);
+        }
+        CredentialKind::Receipt => {
+            if let Some(credential) = parse::<ReceiptCredential>(bytes, &mut report) {
+                report.receipt_expiration_time = Some(credential.receipt_expiration_time);
+                report.receipt_level = Some(credential.receipt_level);
+            }
+        }
+    }
+    report
+}
+
+fn parse<T: DeserializeOwned>(bytes: &[u8], report: &mut DiagnosisReport) -> Option<T> {
+    match deserialize_strict(bytes) {
+        Ok(value) => {
+            report.deserialized = true;
+            Some(value)
+        }
+        Err(_) => {
+            report.corrupt_field = Some("credential");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+
+    #[test]
+    fn test_diagnose_auth_credential() {
+        let server_secret_params = api::ServerSecretParams::generate(TEST_ARRAY_32);
+        let server_public_params = server_secret_params.get_public_params();
+        let response = server_secret_params
+            .issue_auth_credential(TEST_ARRAY_32_1, TEST_ARRAY_16, 123456)
+            .unwrap();
+        let credential = server_public_params
+            .receive_auth_credential(TEST_ARRAY_16, 123456, &response)
+            .unwrap();
+        let bytes = bincode::serialize(&credential).unwrap();
+
+        let report = diagnose_credential(&bytes);
+        assert_eq!(
+            DiagnosisReport {
+                len: AUTH_CREDENTIAL_LEN,
+                kind: Some(CredentialKind::Auth),
+                reserved: Some(0),
+                corrupt_field: None,
+                deserialized: true,
+                redemption_time: Some(123456),
+                ..Default::default()
+            },
+            report
+        );
+        assert!(report.is_well_formed());
+        assert!(report.to_string().contains("redemption time: 123456"));
+
+        // A bad U is pinpointed.
+        let mut corrupt = bytes.clone();
+        corrupt[RESERVED_LEN + 32..RESERVED_LEN + 64].copy_from_slice(&[0xff; 32]);
+        let report = diagnose_credential(&corrupt);
+        assert_eq!(Some("U"), report.corrupt_field);
+        assert!(!report.deserialized);
+        assert_eq!(None, report.redemption_time);
+
+        let mut corrupt = bytes.clone();
+        corrupt[0] = 1;
+        assert!(!diagnose_credential(&corrupt).is_well_formed());
+
+        let report = diagnose_credential(&bytes[..bytes.len() - 1]);
+        assert_eq!(None, report.kind);
+        assert!(!report.is_well_formed());
+        assert_eq!(DiagnosisReport::default(), diagnose_credential(&[]));
+    }
+}
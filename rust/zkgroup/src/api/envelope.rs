@@ -0,0 +1,293 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::api::auth::*;
+use crate::api::groups::*;
+use crate::api::profiles::*;
+use crate::api::receipts::*;
+use crate::api::{AuthOnlyServerParams, ServerPublicParams, ServerSecretParams};
+use crate::common::errors::*;
+use crate::common::serialization::deserialize_strict;
+
+pub const ENVELOPE_FORMAT_VERSION_1: u8 = 1;
+
+const ENVELOPE_HEADER_LEN: usize = 3;
+const ENVELOPE_TYPE_ID_MAX: u16 = 40;
+
+/// A type that can be stored in an [`Envelope`].
+///
+/// Type ids are part of the storage format: never reuse or renumber one. Every public type in
+/// [`api`](crate::api) that implements `Serialize` and `Deserialize` is registered here, which
+/// `tests/envelope_types.rs` enforces.
+pub trait EnvelopeContents: Serialize + DeserializeOwned {
+    const TYPE_ID: u16;
+}
+
+/// A self-describing wrapper around the standard serialization of a zkgroup type.
+///
+/// The encoding is a big-endian `u16` type id, a one-byte format version, and then the contents.
+/// Unwrapping checks the type id, so a blob can never be decoded as the wrong type, and rejects
+/// contents with trailing bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Envelope {
+    type_id: u16,
+    version: u8,
+    contents: Vec<u8>,
+}
+
+impl Envelope {
+    pub fn wrap<T: EnvelopeContents>(value: &T) -> Self {
+        Self {
+            type_id: T::TYPE_ID,
+            version: ENVELOPE_FORMAT_VERSION_1,
+            contents: bincode::serialize(value).expect("can serialize"),
+        }
+    }
+
+    pub fn unwrap<T: EnvelopeContents>(&self) -> Result<T, ZkGroupDeserializationFailure> {
+        if self.type_id != T::TYPE_ID {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        deserialize_strict(&self.contents)
+    }
+
+    pub fn type_id(&self) -> u16 {
+        self.type_id
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkGroupDeserializationFailure> {
+        if bytes.len() < ENVELOPE_HEADER_LEN {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        let type_id = u16::from_be_bytes([bytes[0], bytes[1]]);
+        let version = bytes[2];
+        if type_id == 0 || type_id > ENVELOPE_TYPE_ID_MAX || version != ENVELOPE_FORMAT_VERSION_1 {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        Ok(Self {
+            type_id,
+            version,
+            contents: bytes[ENVELOPE_HEADER_LEN..].to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(ENVELOPE_HEADER_LEN + self.contents.len());
+        bytes.extend_from_slice(&self.type_id.to_be_bytes());
+        bytes.push(self.version);
+        bytes.extend_from_slice(&self.contents);
+        bytes
+    }
+}
+
+impl EnvelopeContents for AuthCredential {
+    const TYPE_ID: u16 = 1;
+}
+
+impl EnvelopeContents for AuthCredentialPresentationV1 {
+    const TYPE_ID: u16 = 2;
+}
+
+impl EnvelopeContents for AuthCredentialPresentationV2 {
+    const TYPE_ID: u16 = 3;
+}
+
+impl EnvelopeContents for AuthCredentialResponse {
+    const TYPE_ID: u16 = 4;
+}
+
+impl EnvelopeContents for GroupMasterKey {
+    const TYPE_ID: u16 = 5;
+}
+
+impl EnvelopeContents for GroupPublicParams {
+    const TYPE_ID: u16 = 6;
+}
+
+impl EnvelopeContents for GroupSecretParams {
+    const TYPE_ID: u16 = 7;
+}
+
+impl EnvelopeContents for ProfileKeyCiphertext {
+    const TYPE_ID: u16 = 8;
+}
+
+impl EnvelopeContents for UuidCiphertext {
+    const TYPE_ID: u16 = 9;
+}
+
+impl EnvelopeContents for PniCredential {
+    const TYPE_ID: u16 = 10;
+}
+
+impl EnvelopeContents for PniCredentialPresentationV1 {
+    const TYPE_ID: u16 = 11;
+}
+
+impl EnvelopeContents for PniCredentialPresentationV2 {
+    const TYPE_ID: u16 = 12;
+}
+
+impl EnvelopeContents for PniCredentialRequestContext {
+    const TYPE_ID: u16 = 13;
+}
+
+impl EnvelopeContents for PniCredentialResponse {
+    const TYPE_ID: u16 = 14;
+}
+
+impl EnvelopeContents for ProfileKey {
+    const TYPE_ID: u16 = 15;
+}
+
+impl EnvelopeContents for ProfileKeyCommitment {
+    const TYPE_ID: u16 = 16;
+}
+
+impl EnvelopeContents for ProfileKeyCredential {
+    const TYPE_ID: u16 = 17;
+}
+
+impl EnvelopeContents for ProfileKeyCredentialPresentationV1 {
+    const TYPE_ID: u16 = 18;
+}
+
+impl EnvelopeContents for ProfileKeyCredentialPresentationV2 {
+    const TYPE_ID: u16 = 19;
+}
+
+impl EnvelopeContents for ProfileKeyCredentialRequest {
+    const TYPE_ID: u16 = 20;
+}
+
+impl EnvelopeContents for ProfileKeyCredentialRequestContext {
+    const TYPE_ID: u16 = 21;
+}
+
+impl EnvelopeContents for ProfileKeyCredentialResponse {
+    const TYPE_ID: u16 = 22;
+}
+
+impl EnvelopeContents for ProfileKeyEqualityPresentation {
+    const TYPE_ID: u16 = 23;
+}
+
+impl EnvelopeContents for ReceiptCredential {
+    const TYPE_ID: u16 = 24;
+}
+
+impl EnvelopeContents for ReceiptCredentialPresentation {
+    const TYPE_ID: u16 = 25;
+}
+
+impl EnvelopeContents for ReceiptCredentialRequest {
+    const TYPE_ID: u16 = 26;
+}
+
+impl EnvelopeContents for ReceiptCredentialRequestContext {
+    const TYPE_ID: u16 = 27;
+}
+
+impl EnvelopeContents for ReceiptCredentialResponse {
+    const TYPE_ID: u16 = 28;
+}
+
+impl EnvelopeContents for AuthOnlyServerParams {
+    const TYPE_ID: u16 = 29;
+}
+
+impl EnvelopeContents for ServerPublicParams {
+    const TYPE_ID: u16 = 30;
+}
+
+impl EnvelopeContents for ServerSecretParams {
+    const TYPE_ID: u16 = 31;
+}
+
+impl EnvelopeContents for AuthCredentialBarePresentation {
+    const TYPE_ID: u16 = 32;
+}
+
+impl EnvelopeContents for AuthCredentialDeviceTaggedPresentation {
+    const TYPE_ID: u16 = 33;
+}
+
+impl EnvelopeContents for AuthCredentialTimestampedPresentation {
+    const TYPE_ID: u16 = 34;
+}
+
+impl EnvelopeContents for AuthCredentialWithEpoch {
+    const TYPE_ID: u16 = 35;
+}
+
+impl EnvelopeContents for AuthCredentialWithEpochPresentation {
+    const TYPE_ID: u16 = 36;
+}
+
+impl EnvelopeContents for BatchAuthCredentialResponse {
+    const TYPE_ID: u16 = 37;
+}
+
+impl EnvelopeContents for MembershipToken {
+    const TYPE_ID: u16 = 38;
+}
+
+impl EnvelopeContents for RedactedPresentation {
+    const TYPE_ID: u16 = 39;
+}
+
+impl EnvelopeContents for ProfileKeyCredentialHidingPresentation {
+    const TYPE_ID: u16 = 40;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::constants::*;
+
+    #[test]
+    fn test_envelope() {
+        let server_secret_params = ServerSecretParams::generate(TEST_ARRAY_32);
+        let response = server_secret_params
+            .issue_auth_credential(TEST_ARRAY_32_1, TEST_ARRAY_16, 123456)
+            .unwrap();
+
+        let bytes = Envelope::wrap(&response).to_bytes();
+        assert_eq!(
+            AUTH_CREDENTIAL_RESPONSE_LEN + ENVELOPE_HEADER_LEN,
+            bytes.len()
+        );
+        let envelope = Envelope::from_bytes(&bytes).unwrap();
+        assert_eq!(AuthCredentialResponse::TYPE_ID, envelope.type_id());
+        let unwrapped: AuthCredentialResponse = envelope.unwrap().unwrap();
+        assert_eq!(
+            bincode::serialize(&response).unwrap(),
+            bincode::serialize(&unwrapped).unwrap()
+        );
+
+        // The type id must match the requested type.
+        assert!(envelope.unwrap::<ProfileKeyCredentialResponse>().is_err());
+
+        // Trailing bytes after the contents are rejected.
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(Envelope::from_bytes(&trailing)
+            .unwrap()
+            .unwrap::<AuthCredentialResponse>()
+            .is_err());
+
+        // Unknown type ids and versions are rejected.
+        let mut bad_type = bytes.clone();
+        bad_type[..2].copy_from_slice(&(ENVELOPE_TYPE_ID_MAX + 1).to_be_bytes());
+        assert!(Envelope::from_bytes(&bad_type).is_err());
+        let mut bad_version = bytes.clone();
+        bad_version[2] = ENVELOPE_FORMAT_VERSION_1 + 1;
+        assert!(Envelope::from_bytes(&bad_version).is_err());
+        assert!(Envelope::from_bytes(&bytes[..ENVELOPE_HEADER_LEN - 1]).is_err());
+    }
+}
@@ -4,11 +4,15 @@
 //
 
 pub mod group_params;
+pub mod group_params_index;
+pub mod hot_group_cache;
 pub mod profile_key_ciphertext;
 pub mod uuid_ciphertext;
 
 pub use group_params::GroupMasterKey;
 pub use group_params::GroupPublicParams;
 pub use group_params::GroupSecretParams;
+pub use group_params_index::GroupParamsIndex;
+pub use hot_group_cache::HotGroupCache;
 pub use profile_key_ciphertext::ProfileKeyCiphertext;
 pub use uuid_ciphertext::UuidCiphertext;
@@ -8,11 +8,14 @@ use std::convert::TryInto;
 use crate::api;
 use crate::common::constants::*;
 use crate::common::errors::*;
+use crate::common::serialization::deserialize_strict;
 use crate::common::sho::*;
 use crate::common::simple_types::*;
 use crate::crypto;
 use aead::{generic_array::GenericArray, Aead, NewAead};
 use aes_gcm_siv::Aes256GcmSiv;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::traits::Identity;
 use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, Serialize, Deserialize, Default)]
@@ -47,10 +50,11 @@ impl GroupMasterKey {
 const ENCRYPTED_BLOB_PADDING_LENGTH_SIZE: usize = std::mem::size_of::<u32>();
 
 impl GroupSecretParams {
-    pub fn generate(randomness: RandomnessBytes) -> Self {
+    pub fn generate(randomness: impl Into<RandomnessBytes>) -> Self {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20200424_Random_GroupSecretParams_Generate",
-            &randomness,
+            randomness.expose_bytes(),
         );
         let mut master_key: GroupMasterKey = Default::default();
         master_key
@@ -86,21 +90,51 @@ impl GroupSecretParams {
         self.master_key
     }
 
+    /// The stable identifier for this group, derived from the master key.
+    ///
+    /// This is carried in the public params as well, so a server holding only
+    /// [`GroupPublicParams`] computes the same value. Prefer this over hashing serialized params.
     pub fn get_group_identifier(&self) -> GroupIdentifierBytes {
         self.group_id
     }
 
+    /// A commitment to `device_tag` that is the same every time within this group.
+    ///
+    /// It is keyed by the master key, so the server can't test guesses at the tag, and a device's
+    /// commitments in different groups can't be linked.
+    pub(crate) fn device_tag_commitment(&self, device_tag: &[u8]) -> [u8; 32] {
+        let mut input = self.master_key.bytes.to_vec();
+        input.extend_from_slice(device_tag);
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_GroupSecretParams_DeviceTagCommitment",
+            &input,
+        );
+        sho.squeeze(32)
+            .try_into()
+            .expect("squeezed the right length")
+    }
+
     pub fn get_public_params(&self) -> GroupPublicParams {
         GroupPublicParams {
             reserved: Default::default(),
-            uid_enc_public_key: self.uid_enc_key_pair.get_public_key(),
-            profile_key_enc_public_key: self.profile_key_enc_key_pair.get_public_key(),
+            uid_enc_public_key: self.get_uid_enc_public_key(),
+            profile_key_enc_public_key: self.get_profile_key_enc_public_key(),
             group_id: self.group_id,
         }
     }
 
+    /// The public half of the uid encryption key, as carried in [`GroupPublicParams`].
+    pub fn get_uid_enc_public_key(&self) -> crypto::uid_encryption::PublicKey {
+        self.uid_enc_key_pair.get_public_key()
+    }
+
+    /// The public half of the profile key encryption key, as carried in [`GroupPublicParams`].
+    pub fn get_profile_key_enc_public_key(&self) -> crypto::profile_key_encryption::PublicKey {
+        self.profile_key_enc_key_pair.get_public_key()
+    }
+
     pub fn encrypt_uuid(&self, uid_bytes: UidBytes) -> api::groups::UuidCiphertext {
-        let uid = crypto::uid_struct::UidStruct::new(uid_bytes);
+        let uid = crypto::uid_struct::UidStruct::calculate(uid_bytes);
         self.encrypt_uid_struct(uid)
     }
 
@@ -123,6 +157,18 @@ impl GroupSecretParams {
         Ok(uid.to_bytes())
     }
 
+    /// Moves `ciphertext` from this group's key to `new_params`, e.g. when rotating group keys.
+    ///
+    /// Fails if `ciphertext` wasn't encrypted under this group's key.
+    pub fn reencrypt_uuid(
+        &self,
+        ciphertext: api::groups::UuidCiphertext,
+        new_params: &GroupSecretParams,
+    ) -> Result<api::groups::UuidCiphertext, ZkGroupVerificationFailure> {
+        let uid = self.uid_enc_key_pair.decrypt(ciphertext.ciphertext)?;
+        Ok(new_params.encrypt_uid_struct(uid))
+    }
+
     pub fn encrypt_profile_key(
         &self,
         profile_key: api::profiles::ProfileKey,
@@ -158,10 +204,15 @@ impl GroupSecretParams {
         })
     }
 
-    pub fn encrypt_blob(&self, randomness: RandomnessBytes, plaintext: &[u8]) -> Vec<u8> {
+    pub fn encrypt_blob(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        plaintext: &[u8],
+    ) -> Vec<u8> {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20200424_Random_GroupSecretParams_EncryptBlob",
-            &randomness,
+            randomness.expose_bytes(),
         );
         let nonce_vec = sho.squeeze(AESGCM_NONCE_LEN);
         let mut ciphertext_vec =
@@ -173,10 +224,11 @@ impl GroupSecretParams {
 
     pub fn encrypt_blob_with_padding(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         plaintext: &[u8],
         padding_len: u32,
     ) -> Vec<u8> {
+        let randomness = Randomness::new(randomness.into());
         let full_length =
             ENCRYPTED_BLOB_PADDING_LENGTH_SIZE + plaintext.len() + padding_len as usize;
         let mut padded_plaintext = Vec::with_capacity(full_length);
@@ -249,9 +301,39 @@ impl GroupSecretParams {
 }
 
 impl GroupPublicParams {
+    /// See [`GroupSecretParams::get_group_identifier`].
     pub fn get_group_identifier(&self) -> GroupIdentifierBytes {
         self.group_id
     }
+
+    /// Deserializes params received from an untrusted source.
+    ///
+    /// Rejects input of the wrong length, a nonzero reserved byte, non-canonical point encodings,
+    /// and anything [`validate`](Self::validate) rejects.
+    pub fn from_bytes_validated(bytes: &[u8]) -> Result<Self, ZkGroupDeserializationFailure> {
+        if bytes.len() != GROUP_PUBLIC_PARAMS_LEN {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        let params: Self = deserialize_strict(bytes)?;
+        if params.reserved != ReservedBytes::default() {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Checks that the encryption public keys are usable, before trusting these params.
+    ///
+    /// Deserialization already rejects non-canonical point encodings; this additionally rejects
+    /// keys that are the identity point, which a corrupted or tampered blob could contain.
+    pub fn validate(&self) -> Result<(), ZkGroupDeserializationFailure> {
+        if self.uid_enc_public_key.A == RistrettoPoint::identity()
+            || self.profile_key_enc_public_key.B == RistrettoPoint::identity()
+        {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -338,6 +420,59 @@ mod tests {
         assert!(calc_plaintext[..] == plaintext_vec[..]);
     }
 
+    #[test]
+    fn test_validate_public_params() {
+        let public_params = GroupSecretParams::generate(TEST_ARRAY_32).get_public_params();
+        public_params.validate().unwrap();
+
+        let mut bad_params = public_params;
+        bad_params.uid_enc_public_key.A = RistrettoPoint::identity();
+        assert!(bad_params.validate().is_err());
+
+        let mut bad_params = public_params;
+        bad_params.profile_key_enc_public_key.B = RistrettoPoint::identity();
+        assert!(bad_params.validate().is_err());
+
+        // The identity point has a canonical (all-zero) encoding, so it survives deserialization.
+        let mut bytes = bincode::serialize(&public_params).unwrap();
+        let uid_key_offset = RESERVED_LEN + GROUP_IDENTIFIER_LEN;
+        bytes[uid_key_offset..][..32].copy_from_slice(&[0u8; 32]);
+        let bad_params: GroupPublicParams = bincode::deserialize(&bytes).unwrap();
+        assert!(bad_params.validate().is_err());
+
+        // Non-canonical encodings don't.
+        bytes[uid_key_offset..][..32].copy_from_slice(&[0xffu8; 32]);
+        assert!(bincode::deserialize::<GroupPublicParams>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_public_params_from_bytes_validated() {
+        let public_params = GroupSecretParams::generate(TEST_ARRAY_32).get_public_params();
+        let bytes = bincode::serialize(&public_params).unwrap();
+        let parsed = GroupPublicParams::from_bytes_validated(&bytes).unwrap();
+        assert_eq!(bytes, bincode::serialize(&parsed).unwrap());
+
+        assert!(GroupPublicParams::from_bytes_validated(&bytes[..bytes.len() - 1]).is_err());
+        assert!(GroupPublicParams::from_bytes_validated(&[&bytes[..], &[0]].concat()).is_err());
+        assert!(GroupPublicParams::from_bytes_validated(&[]).is_err());
+
+        let mut reserved = bytes.clone();
+        reserved[0] = 1;
+        assert!(GroupPublicParams::from_bytes_validated(&reserved).is_err());
+
+        let uid_key_offset = RESERVED_LEN + GROUP_IDENTIFIER_LEN;
+        let profile_key_key_offset = uid_key_offset + 32;
+        for &offset in &[uid_key_offset, profile_key_key_offset] {
+            let mut identity = bytes.clone();
+            identity[offset..][..32].copy_from_slice(&[0u8; 32]);
+            assert!(GroupPublicParams::from_bytes_validated(&identity).is_err());
+
+            let mut non_canonical = bytes.clone();
+            non_canonical[offset..][..32].copy_from_slice(&[0xffu8; 32]);
+            assert!(GroupPublicParams::from_bytes_validated(&non_canonical).is_err());
+        }
+    }
+
     #[test]
     fn test_encrypt_with_padding() {
         let group_secret_params = GroupSecretParams::generate([0u8; RANDOMNESS_LEN]);
@@ -369,4 +504,17 @@ mod tests {
             assert_eq!(calc_plaintext[..], plaintext[..]);
         }
     }
+
+    #[test]
+    fn test_enc_public_keys_match_public_params() {
+        let group_secret_params = GroupSecretParams::generate(TEST_ARRAY_32);
+        let group_public_params = group_secret_params.get_public_params();
+        assert!(
+            group_secret_params.get_uid_enc_public_key() == group_public_params.uid_enc_public_key
+        );
+        assert!(
+            group_secret_params.get_profile_key_enc_public_key()
+                == group_public_params.profile_key_enc_public_key
+        );
+    }
 }
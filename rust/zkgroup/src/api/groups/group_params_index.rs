@@ -0,0 +1,76 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+use crate::api;
+use crate::api::groups::GroupPublicParams;
+use crate::common::errors::*;
+use crate::common::simple_types::*;
+
+/// A set of group public params, keyed by group identifier, for servers that verify
+/// presentations across many groups.
+#[derive(Clone, Default)]
+pub struct GroupParamsIndex {
+    params: HashMap<GroupIdentifierBytes, GroupPublicParams>,
+}
+
+impl GroupParamsIndex {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds `params` under their own group identifier, returning any params they replace.
+    pub fn insert(&mut self, params: GroupPublicParams) -> Option<GroupPublicParams> {
+        self.params.insert(params.get_group_identifier(), params)
+    }
+
+    pub fn get(&self, group_id: &GroupIdentifierBytes) -> Option<&GroupPublicParams> {
+        self.params.get(group_id)
+    }
+
+    pub fn remove(&mut self, group_id: &GroupIdentifierBytes) -> Option<GroupPublicParams> {
+        self.params.remove(group_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Looks up the params for `group_id` and verifies `presentation` against them.
+    pub fn verify_auth_presentation(
+        &self,
+        server: &api::ServerSecretParams,
+        group_id: &GroupIdentifierBytes,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+    ) -> Result<(), ZkGroupCheckFailure> {
+        let params = self
+            .get(group_id)
+            .ok_or(ZkGroupCheckFailure::UnknownGroup)?;
+        server.verify_auth_credential_presentation(*params, presentation)?;
+        Ok(())
+    }
+}
+
+impl FromIterator<GroupPublicParams> for GroupParamsIndex {
+    fn from_iter<T: IntoIterator<Item = GroupPublicParams>>(iter: T) -> Self {
+        let mut index = Self::new();
+        index.extend(iter);
+        index
+    }
+}
+
+impl Extend<GroupPublicParams> for GroupParamsIndex {
+    fn extend<T: IntoIterator<Item = GroupPublicParams>>(&mut self, iter: T) {
+        for params in iter {
+            self.insert(params);
+        }
+    }
+}
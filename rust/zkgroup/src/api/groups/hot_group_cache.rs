@@ -0,0 +1,175 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::api;
+use crate::api::groups::GroupPublicParams;
+use crate::common::errors::*;
+use crate::common::simple_types::*;
+
+type GroupParamsLoader = dyn Fn(&GroupIdentifierBytes) -> Option<Vec<u8>> + Send + Sync;
+
+/// A bounded cache of parsed and validated group public params, for servers that verify many
+/// presentations against a few hot groups.
+///
+/// Groups not in the cache are fetched as serialized params from the loader given to
+/// [`new`](Self::new), and parsed with [`GroupPublicParams::from_bytes_validated`]. Once
+/// `capacity` groups are cached, loading another evicts the least recently used.
+pub struct HotGroupCache {
+    capacity: usize,
+    loader: Box<GroupParamsLoader>,
+    entries: Mutex<LruEntries>,
+}
+
+#[derive(Default)]
+struct LruEntries {
+    params: HashMap<GroupIdentifierBytes, (GroupPublicParams, u64)>,
+    clock: u64,
+}
+
+impl LruEntries {
+    fn get(&mut self, group_id: &GroupIdentifierBytes) -> Option<GroupPublicParams> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.params.get_mut(group_id).map(|(params, last_used)| {
+            *last_used = clock;
+            *params
+        })
+    }
+
+    fn insert(&mut self, params: GroupPublicParams, capacity: usize) {
+        let group_id = params.get_group_identifier();
+        if !self.params.contains_key(&group_id) && self.params.len() >= capacity {
+            let least_recent = self
+                .params
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(group_id, _)| *group_id);
+            if let Some(least_recent) = least_recent {
+                self.params.remove(&least_recent);
+            }
+        }
+        self.clock += 1;
+        self.params.insert(group_id, (params, self.clock));
+    }
+}
+
+impl HotGroupCache {
+    /// Panics if `capacity` is zero.
+    pub fn new(
+        capacity: usize,
+        loader: impl Fn(&GroupIdentifierBytes) -> Option<Vec<u8>> + Send + Sync + 'static,
+    ) -> Self {
+        assert!(capacity > 0, "cache must hold at least one group");
+        Self {
+            capacity,
+            loader: Box::new(loader),
+            entries: Default::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().params.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the params for `group_id`, loading them if they aren't cached.
+    ///
+    /// Loaded params that don't parse, or that belong to a different group, are not cached.
+    pub fn get(&self, group_id: &GroupIdentifierBytes) -> Option<GroupPublicParams> {
+        if let Some(params) = self.lock().get(group_id) {
+            return Some(params);
+        }
+        // The loader runs without the lock held, so a slow load doesn't stall hot groups.
+        let bytes = (self.loader)(group_id)?;
+        let params = GroupPublicParams::from_bytes_validated(&bytes).ok()?;
+        if params.get_group_identifier() != *group_id {
+            return None;
+        }
+        self.lock().insert(params, self.capacity);
+        Some(params)
+    }
+
+    /// Looks up the params for `group_id` and verifies `presentation` against them.
+    pub fn verify_auth(
+        &self,
+        server: &api::ServerSecretParams,
+        group_id: &GroupIdentifierBytes,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+    ) -> Result<(), ZkGroupCheckFailure> {
+        let params = self
+            .get(group_id)
+            .ok_or(ZkGroupCheckFailure::UnknownGroup)?;
+        server.verify_auth_credential_presentation(params, presentation)?;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruEntries> {
+        self.entries
+            .lock()
+            .expect("cache lock is never held across a panic")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::constants::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let groups: Vec<_> = [TEST_ARRAY_32, TEST_ARRAY_32_1, TEST_ARRAY_32_2]
+            .iter()
+            .map(|&randomness| {
+                api::groups::GroupSecretParams::generate(randomness).get_public_params()
+            })
+            .collect();
+        let loads = Arc::new(AtomicUsize::new(0));
+        let cache = {
+            let groups = groups.clone();
+            let loads = Arc::clone(&loads);
+            HotGroupCache::new(2, move |group_id| {
+                loads.fetch_add(1, Ordering::SeqCst);
+                groups
+                    .iter()
+                    .find(|params| params.get_group_identifier() == *group_id)
+                    .map(|params| bincode::serialize(params).unwrap())
+            })
+        };
+        let ids: Vec<_> = groups.iter().map(|p| p.get_group_identifier()).collect();
+
+        assert!(cache.get(&ids[0]).is_some());
+        assert!(cache.get(&ids[1]).is_some());
+        assert!(cache.get(&ids[0]).is_some());
+        assert_eq!(2, loads.load(Ordering::SeqCst));
+
+        // Group 1 is least recently used, so loading group 2 evicts it.
+        assert!(cache.get(&ids[2]).is_some());
+        assert_eq!(2, cache.len());
+        assert!(cache.get(&ids[0]).is_some());
+        assert_eq!(3, loads.load(Ordering::SeqCst));
+        assert!(cache.get(&ids[1]).is_some());
+        assert_eq!(4, loads.load(Ordering::SeqCst));
+
+        assert!(cache.get(&[0xff; 32]).is_none());
+        assert_eq!(2, cache.len());
+    }
+
+    #[test]
+    fn test_rejects_params_for_another_group() {
+        let params = api::groups::GroupSecretParams::generate(TEST_ARRAY_32).get_public_params();
+        let cache = HotGroupCache::new(1, move |_| Some(bincode::serialize(&params).unwrap()));
+        assert!(cache.get(&[0xff; 32]).is_none());
+        assert!(cache.is_empty());
+        assert!(cache.get(&params.get_group_identifier()).is_some());
+    }
+}
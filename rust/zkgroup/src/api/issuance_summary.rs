@@ -0,0 +1,40 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+/// Counts of the credentials issued in one batch, for the server to sign with
+/// [`ServerSecretParams::sign_issuance_summary`](crate::api::ServerSecretParams::sign_issuance_summary).
+///
+/// The nonce identifies the batch, so that a signed summary can't be replayed as another batch's.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct IssuanceSummary {
+    pub batch_nonce: [u8; 32],
+    pub auth: u64,
+    pub profile_key: u64,
+    pub pni: u64,
+    pub receipt: u64,
+}
+
+impl IssuanceSummary {
+    pub const SERIALIZED_LEN: usize = 32 + 4 * 8;
+
+    /// The canonical encoding: the nonce, then each count as a big-endian `u64`, in field order.
+    pub fn to_bytes(&self) -> [u8; Self::SERIALIZED_LEN] {
+        let mut bytes = [0u8; Self::SERIALIZED_LEN];
+        bytes[..32].copy_from_slice(&self.batch_nonce);
+        let counts = [self.auth, self.profile_key, self.pni, self.receipt];
+        for (chunk, count) in bytes[32..].chunks_exact_mut(8).zip(counts.iter()) {
+            chunk.copy_from_slice(&count.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// The message the server signs, with the issuance summary key rather than the key used by
+    /// [`ServerSecretParams::sign`](crate::api::ServerSecretParams::sign).
+    pub(crate) fn signed_message(&self) -> Vec<u8> {
+        let mut message = b"Signal_ZKGroup_20220301_IssuanceSummary".to_vec();
+        message.extend_from_slice(&self.to_bytes());
+        message
+    }
+}
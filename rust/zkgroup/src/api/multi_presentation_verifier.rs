@@ -0,0 +1,67 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use crate::api;
+use crate::api::auth::AnyAuthCredentialPresentation;
+use crate::api::groups::GroupPublicParams;
+use crate::api::profiles::AnyProfileKeyCredentialPresentation;
+use crate::api::receipts::ReceiptCredentialPresentation;
+use crate::common::errors::*;
+
+/// Verifies the auth, profile key, and receipt presentations of a combined request together.
+///
+/// Each proof is still checked separately, since they prove different statements; this only
+/// shares the server and group params across the three checks. All three are always checked, and
+/// the request is accepted only if every one verifies.
+#[derive(Copy, Clone)]
+pub struct MultiPresentationVerifier<'a> {
+    server_secret_params: &'a api::ServerSecretParams,
+    group_public_params: GroupPublicParams,
+}
+
+impl<'a> MultiPresentationVerifier<'a> {
+    pub fn new(
+        server_secret_params: &'a api::ServerSecretParams,
+        group_public_params: GroupPublicParams,
+    ) -> Self {
+        Self {
+            server_secret_params,
+            group_public_params,
+        }
+    }
+
+    /// On failure, the error records which of the presentations did not verify.
+    pub fn verify(
+        &self,
+        auth_presentation: &AnyAuthCredentialPresentation,
+        profile_key_presentation: &AnyProfileKeyCredentialPresentation,
+        receipt_presentation: &ReceiptCredentialPresentation,
+    ) -> Result<(), ZkGroupCheckFailure> {
+        let auth = self
+            .server_secret_params
+            .verify_auth_credential_presentation(self.group_public_params, auth_presentation)
+            .is_err();
+        let profile_key = self
+            .server_secret_params
+            .verify_profile_key_credential_presentation(
+                self.group_public_params,
+                profile_key_presentation,
+            )
+            .is_err();
+        let receipt = self
+            .server_secret_params
+            .verify_receipt_credential_presentation(receipt_presentation)
+            .is_err();
+        if auth || profile_key || receipt {
+            Err(ZkGroupCheckFailure::Presentations {
+                auth,
+                profile_key,
+                receipt,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::io::{Read, Write};
+
+use crate::api;
+use crate::common::constants::*;
+use crate::common::errors::*;
+use crate::common::serialization::deserialize_strict;
+
+const PARAMS_BUNDLE_TAG_SERVER_PUBLIC_PARAMS: u8 = 1;
+const PARAMS_BUNDLE_TAG_GROUP_PUBLIC_PARAMS: u8 = 2;
+
+/// A collection of public params shipped together as a single blob.
+///
+/// The wire format is a big-endian `u32` element count, followed by that many elements. Each
+/// element is a one-byte kind tag, a big-endian `u32` length, and the standard serialization of the
+/// params. At most one `ServerPublicParams` may be present.
+#[derive(Clone, Default)]
+pub struct ParamsBundle {
+    pub server_public_params: Option<api::ServerPublicParams>,
+    pub group_public_params: Vec<api::groups::GroupPublicParams>,
+}
+
+impl ParamsBundle {
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self, ZkGroupDeserializationFailure> {
+        let mut bundle = Self::default();
+        let count = read_u32(reader)?;
+        for _ in 0..count {
+            let mut tag = [0u8; 1];
+            reader
+                .read_exact(&mut tag)
+                .map_err(|_| ZkGroupDeserializationFailure)?;
+            let len = read_u32(reader)? as usize;
+            let len_ok = match tag[0] {
+                // Personalized params carry 32 more bytes.
+                PARAMS_BUNDLE_TAG_SERVER_PUBLIC_PARAMS => {
+                    len == SERVER_PUBLIC_PARAMS_LEN || len == SERVER_PUBLIC_PARAMS_LEN + 32
+                }
+                PARAMS_BUNDLE_TAG_GROUP_PUBLIC_PARAMS => len == GROUP_PUBLIC_PARAMS_LEN,
+                _ => return Err(ZkGroupDeserializationFailure),
+            };
+            if !len_ok {
+                return Err(ZkGroupDeserializationFailure);
+            }
+            let mut bytes = vec![0u8; len];
+            reader
+                .read_exact(&mut bytes)
+                .map_err(|_| ZkGroupDeserializationFailure)?;
+
+            if tag[0] == PARAMS_BUNDLE_TAG_SERVER_PUBLIC_PARAMS {
+                if bundle.server_public_params.is_some() {
+                    return Err(ZkGroupDeserializationFailure);
+                }
+                let params = deserialize_strict::<api::ServerPublicParams>(&bytes)?;
+                bundle.server_public_params = Some(params);
+            } else {
+                let params = bincode::deserialize::<api::groups::GroupPublicParams>(&bytes)
+                    .map_err(|_| ZkGroupDeserializationFailure)?;
+                bundle.group_public_params.push(params);
+            }
+        }
+        Ok(bundle)
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let count = self.server_public_params.iter().count() + self.group_public_params.len();
+        writer.write_all(&(count as u32).to_be_bytes())?;
+        if let Some(params) = &self.server_public_params {
+            let bytes = bincode::serialize(params).expect("can serialize");
+            write_element(writer, PARAMS_BUNDLE_TAG_SERVER_PUBLIC_PARAMS, &bytes)?;
+        }
+        for params in &self.group_public_params {
+            let bytes = bincode::serialize(params).expect("can serialize");
+            write_element(writer, PARAMS_BUNDLE_TAG_GROUP_PUBLIC_PARAMS, &bytes)?;
+        }
+        Ok(())
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, ZkGroupDeserializationFailure> {
+    let mut bytes = [0u8; 4];
+    reader
+        .read_exact(&mut bytes)
+        .map_err(|_| ZkGroupDeserializationFailure)?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+fn write_element<W: Write>(writer: &mut W, tag: u8, bytes: &[u8]) -> std::io::Result<()> {
+    writer.write_all(&[tag])?;
+    writer.write_all(&(bytes.len() as u32).to_be_bytes())?;
+    writer.write_all(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let server_secret_params = api::ServerSecretParams::generate(TEST_ARRAY_32);
+        let server_public_params = server_secret_params.get_public_params();
+        let group_public_params = [TEST_ARRAY_32_1, TEST_ARRAY_32_2]
+            .iter()
+            .map(|randomness| api::groups::GroupSecretParams::generate(*randomness))
+            .map(|params| params.get_public_params())
+            .collect::<Vec<_>>();
+
+        let bundle = ParamsBundle {
+            server_public_params: Some(server_public_params),
+            group_public_params,
+        };
+        let mut bytes = vec![];
+        bundle.write_to(&mut bytes).unwrap();
+
+        let parsed = ParamsBundle::read_from(&mut &bytes[..]).unwrap();
+        assert_eq!(
+            bincode::serialize(&server_public_params).unwrap(),
+            bincode::serialize(&parsed.server_public_params.unwrap()).unwrap()
+        );
+        assert_eq!(2, parsed.group_public_params.len());
+        for (expected, actual) in bundle
+            .group_public_params
+            .iter()
+            .zip(&parsed.group_public_params)
+        {
+            assert_eq!(
+                expected.get_group_identifier(),
+                actual.get_group_identifier()
+            );
+        }
+
+        // Truncated input must be rejected.
+        assert!(ParamsBundle::read_from(&mut &bytes[..bytes.len() - 1]).is_err());
+
+        // So must an unknown tag.
+        let mut bad_tag = bytes.clone();
+        bad_tag[4] = 0xff;
+        assert!(ParamsBundle::read_from(&mut &bad_tag[..]).is_err());
+    }
+}
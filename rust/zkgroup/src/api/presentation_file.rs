@@ -0,0 +1,103 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::io::{self, Read, Write};
+
+/// Records longer than this are treated as corruption rather than read into memory.
+const MAX_FRAMED_PRESENTATION_LEN: usize = 64 * 1024;
+
+/// The outcome of verifying a file of framed presentations.
+///
+/// See [`ServerSecretParams::verify_file`](crate::api::ServerSecretParams::verify_file).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerificationReport {
+    pub total: usize,
+    pub verified: usize,
+    /// The byte offset of each record that failed to deserialize or verify, in file order.
+    pub failure_offsets: Vec<u64>,
+}
+
+/// Appends one presentation to a presentation file: a big-endian `u32` length, then the
+/// presentation's serialization.
+pub fn write_framed_presentation<W: Write>(writer: &mut W, presentation: &[u8]) -> io::Result<()> {
+    if presentation.len() > MAX_FRAMED_PRESENTATION_LEN {
+        return Err(io::ErrorKind::InvalidInput.into());
+    }
+    writer.write_all(&(presentation.len() as u32).to_be_bytes())?;
+    writer.write_all(presentation)
+}
+
+/// Reads framed records from `reader` one at a time, passing each to `verify`.
+///
+/// A bad record is reported and skipped. A truncated final record or an implausible length is an
+/// error, since the framing after it can't be trusted.
+pub(crate) fn verify_framed<R: Read>(
+    reader: &mut R,
+    mut verify: impl FnMut(&[u8]) -> bool,
+) -> io::Result<VerificationReport> {
+    let mut report = VerificationReport::default();
+    let mut offset = 0u64;
+    let mut record = Vec::new();
+    loop {
+        let mut len = [0u8; 4];
+        match reader.read(&mut len[..1])? {
+            0 => return Ok(report),
+            _ => reader.read_exact(&mut len[1..])?,
+        }
+        let len = u32::from_be_bytes(len) as usize;
+        if len > MAX_FRAMED_PRESENTATION_LEN {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+        record.resize(len, 0);
+        reader.read_exact(&mut record)?;
+
+        report.total += 1;
+        if verify(&record) {
+            report.verified += 1;
+        } else {
+            report.failure_offsets.push(offset);
+        }
+        offset += 4 + len as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_framed() {
+        let mut bytes = vec![];
+        for record in &[&b"good"[..], b"bad", b"", b"good"] {
+            write_framed_presentation(&mut bytes, record).unwrap();
+        }
+        let report = verify_framed(&mut &bytes[..], |record| record == b"good").unwrap();
+        assert_eq!(
+            VerificationReport {
+                total: 4,
+                verified: 2,
+                failure_offsets: vec![8, 15],
+            },
+            report
+        );
+
+        assert_eq!(
+            VerificationReport::default(),
+            verify_framed(&mut &[][..], |_| true).unwrap()
+        );
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            verify_framed(&mut &bytes[..bytes.len() - 1], |_| true)
+                .unwrap_err()
+                .kind()
+        );
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            verify_framed(&mut &[0xff; 8][..], |_| true)
+                .unwrap_err()
+                .kind()
+        );
+    }
+}
@@ -10,10 +10,12 @@ pub mod pni_credential_response;
 pub mod profile_key;
 pub mod profile_key_commitment;
 pub mod profile_key_credential;
+pub mod profile_key_credential_hiding_presentation;
 pub mod profile_key_credential_presentation;
 pub mod profile_key_credential_request;
 pub mod profile_key_credential_request_context;
 pub mod profile_key_credential_response;
+pub mod profile_key_equality_presentation;
 pub mod profile_key_version;
 
 pub use pni_credential::PniCredential;
@@ -25,10 +27,12 @@ pub use pni_credential_response::PniCredentialResponse;
 pub use profile_key::ProfileKey;
 pub use profile_key_commitment::ProfileKeyCommitment;
 pub use profile_key_credential::ProfileKeyCredential;
+pub use profile_key_credential_hiding_presentation::ProfileKeyCredentialHidingPresentation;
 pub use profile_key_credential_presentation::AnyProfileKeyCredentialPresentation;
 pub use profile_key_credential_presentation::ProfileKeyCredentialPresentationV1;
 pub use profile_key_credential_presentation::ProfileKeyCredentialPresentationV2;
 pub use profile_key_credential_request::ProfileKeyCredentialRequest;
 pub use profile_key_credential_request_context::ProfileKeyCredentialRequestContext;
 pub use profile_key_credential_response::ProfileKeyCredentialResponse;
+pub use profile_key_equality_presentation::ProfileKeyEqualityPresentation;
 pub use profile_key_version::ProfileKeyVersion;
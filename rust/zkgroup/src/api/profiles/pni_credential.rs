@@ -3,8 +3,12 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use crate::common::errors::*;
 use crate::common::simple_types::*;
 use crate::crypto;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
 use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, Serialize, Deserialize)]
@@ -15,3 +19,32 @@ pub struct PniCredential {
     pub(crate) pni_bytes: UidBytes,
     pub(crate) profile_key_bytes: ProfileKeyBytes,
 }
+
+impl PniCredential {
+    /// Checks locally for corruption that would otherwise only show up as a failed presentation.
+    ///
+    /// This rejects identifiers that can never be credentialed and a degenerate credential. It
+    /// cannot check the credential's MAC against the stored identifiers, since that needs the
+    /// issuer's secret key; the issuance proof checked in
+    /// [`receive_pni_credential`](crate::api::ServerPublicParams::receive_pni_credential) is the
+    /// only client-side check of the MAC.
+    pub fn validate(&self) -> Result<(), ZkGroupVerificationFailure> {
+        crypto::uid_struct::UidStruct::new(self.aci_bytes)?;
+        crypto::uid_struct::UidStruct::new(self.pni_bytes)?;
+        if self.credential.t == Scalar::zero() || self.credential.U == RistrettoPoint::identity() {
+            return Err(ZkGroupVerificationFailure);
+        }
+        Ok(())
+    }
+
+    /// See [`AuthCredential::credential_log_id`], which hashes the same fields of this type.
+    ///
+    /// [`AuthCredential::credential_log_id`]: crate::api::auth::AuthCredential::credential_log_id
+    pub fn credential_log_id(&self) -> [u8; 16] {
+        crypto::credentials::credential_log_id(
+            b"Signal_ZKGroup_20220301_PniCredential_LogId",
+            self.reserved[0],
+            self.credential.U,
+        )
+    }
+}
@@ -82,14 +82,18 @@ pub enum AnyPniCredentialPresentation {
 
 impl AnyPniCredentialPresentation {
     pub fn new(presentation_bytes: &[u8]) -> Result<Self, ZkGroupDeserializationFailure> {
-        match presentation_bytes[0] {
-            PRESENTATION_VERSION_1 => {
+        match presentation_bytes.first() {
+            Some(&PRESENTATION_VERSION_1)
+                if presentation_bytes.len() == PNI_CREDENTIAL_PRESENTATION_V1_LEN =>
+            {
                 match bincode::deserialize::<PniCredentialPresentationV1>(presentation_bytes) {
                     Ok(presentation) => Ok(AnyPniCredentialPresentation::V1(presentation)),
                     Err(_) => Err(ZkGroupDeserializationFailure),
                 }
             }
-            PRESENTATION_VERSION_2 => {
+            Some(&PRESENTATION_VERSION_2)
+                if presentation_bytes.len() == PNI_CREDENTIAL_PRESENTATION_V2_LEN =>
+            {
                 match bincode::deserialize::<PniCredentialPresentationV2>(presentation_bytes) {
                     Ok(presentation) => Ok(AnyPniCredentialPresentation::V2(presentation)),
                     Err(_) => Err(ZkGroupDeserializationFailure),
@@ -16,10 +16,11 @@ pub struct ProfileKey {
 }
 
 impl ProfileKey {
-    pub fn generate(randomness: RandomnessBytes) -> Self {
+    pub fn generate(randomness: impl Into<RandomnessBytes>) -> Self {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20200424_Random_ProfileKey_Generate",
-            &randomness,
+            randomness.expose_bytes(),
         );
         let mut bytes = [0u8; PROFILE_KEY_LEN];
         bytes.copy_from_slice(&sho.squeeze(PROFILE_KEY_LEN)[..]);
@@ -34,6 +35,10 @@ impl ProfileKey {
         self.bytes
     }
 
+    /// Commits to this profile key for `uid_bytes`.
+    ///
+    /// The result is a deterministic function of `(self, uid_bytes)`, which the server relies on
+    /// when it checks a credential request against the commitment stored with the profile.
     pub fn get_commitment(&self, uid_bytes: UidBytes) -> api::profiles::ProfileKeyCommitment {
         let profile_key = crypto::profile_key_struct::ProfileKeyStruct::new(self.bytes, uid_bytes);
         let commitment =
@@ -5,6 +5,7 @@
 
 use crate::common::simple_types::*;
 use crate::crypto;
+use curve25519_dalek::subtle::ConstantTimeEq;
 use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, Serialize, Deserialize)]
@@ -12,3 +13,13 @@ pub struct ProfileKeyCommitment {
     pub(crate) reserved: ReservedBytes,
     pub(crate) commitment: crypto::profile_key_commitment::Commitment,
 }
+
+impl ProfileKeyCommitment {
+    /// Whether both commitments are to the same profile key (and uid), compared in constant time.
+    ///
+    /// Commitments are deterministic, so this compares the committed points and ignores the
+    /// leading version byte, which may differ between encodings of the same commitment.
+    pub fn refers_to_same_key(&self, other: &ProfileKeyCommitment) -> bool {
+        bool::from(self.commitment.ct_eq(&other.commitment))
+    }
+}
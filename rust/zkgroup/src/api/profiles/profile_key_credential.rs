@@ -3,10 +3,30 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use crate::common::constants::*;
+use crate::common::errors::*;
+use crate::common::serialization::deserialize_strict;
+use crate::common::sho::*;
 use crate::common::simple_types::*;
 use crate::crypto;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::subtle::ConstantTimeEq;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+const STORAGE_TAG_LEN: usize = 32;
+
+/// The serialized fields of a [`ProfileKeyCredential`], in order, with their lengths.
+const FIELDS: [(&str, usize); 6] = [
+    ("reserved", RESERVED_LEN),
+    ("t", 32),
+    ("U", 32),
+    ("V", 32),
+    ("uid", UUID_LEN),
+    ("profile key", PROFILE_KEY_LEN),
+];
+
 #[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct ProfileKeyCredential {
     pub(crate) reserved: ReservedBytes,
@@ -14,3 +34,87 @@ pub struct ProfileKeyCredential {
     pub(crate) uid_bytes: UidBytes,
     pub(crate) profile_key_bytes: ProfileKeyBytes,
 }
+
+impl ProfileKeyCredential {
+    /// See [`AuthCredential::credential_log_id`], which hashes the same fields of this type.
+    ///
+    /// [`AuthCredential::credential_log_id`]: crate::api::auth::AuthCredential::credential_log_id
+    pub fn credential_log_id(&self) -> [u8; 16] {
+        crypto::credentials::credential_log_id(
+            b"Signal_ZKGroup_20220301_ProfileKeyCredential_LogId",
+            self.reserved[0],
+            self.credential.U,
+        )
+    }
+
+    /// Serializes the credential followed by a tag keyed by `key`, for storage where tampering must
+    /// be detected on read.
+    ///
+    /// `key` is a local storage key; it is unrelated to the credential's own keys.
+    pub fn serialize_with_tag(&self, key: &[u8; 32]) -> Vec<u8> {
+        let mut bytes = bincode::serialize(self).expect("can serialize");
+        let tag = storage_tag(key, &bytes);
+        bytes.extend_from_slice(&tag);
+        bytes
+    }
+
+    /// Checks the tag written by [`serialize_with_tag`](Self::serialize_with_tag), then
+    /// deserializes the credential.
+    pub fn deserialize_with_tag(
+        bytes: &[u8],
+        key: &[u8; 32],
+    ) -> Result<Self, ZkGroupDeserializationFailure> {
+        if bytes.len() < STORAGE_TAG_LEN {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        let (credential_bytes, tag) = bytes.split_at(bytes.len() - STORAGE_TAG_LEN);
+        if !bool::from(storage_tag(key, credential_bytes).ct_eq(tag)) {
+            return Err(ZkGroupDeserializationFailure);
+        }
+        deserialize_strict(credential_bytes)
+    }
+
+    /// Deserializes a stored credential, reporting which field is at fault if that fails.
+    ///
+    /// There is nothing that can be filled in for a truncated credential: the only reserved byte
+    /// comes first, and every later field is needed to present the credential. So this accepts
+    /// exactly what deserialization accepts, but says which field was truncated or corrupt.
+    pub fn try_recover(bytes: &[u8]) -> Result<Self, ZkGroupRecoveryFailure> {
+        let mut end = 0;
+        for (name, len) in FIELDS.iter() {
+            end += len;
+            if bytes.len() < end {
+                return Err(ZkGroupRecoveryFailure::Truncated(name));
+            }
+        }
+        if bytes.len() > end {
+            return Err(ZkGroupRecoveryFailure::TrailingBytes);
+        }
+
+        check_field::<Scalar>(bytes, 1)?;
+        check_field::<RistrettoPoint>(bytes, 2)?;
+        check_field::<RistrettoPoint>(bytes, 3)?;
+        deserialize_strict(bytes).map_err(|_| ZkGroupRecoveryFailure::Corrupt("credential"))
+    }
+}
+
+fn check_field<T: DeserializeOwned>(
+    bytes: &[u8],
+    index: usize,
+) -> Result<(), ZkGroupRecoveryFailure> {
+    let start: usize = FIELDS[..index].iter().map(|(_, len)| len).sum();
+    let (name, len) = FIELDS[index];
+    deserialize_strict::<T>(&bytes[start..start + len])
+        .map(|_| ())
+        .map_err(|_| ZkGroupRecoveryFailure::Corrupt(name))
+}
+
+fn storage_tag(key: &[u8; 32], credential_bytes: &[u8]) -> Vec<u8> {
+    let mut data = key.to_vec();
+    data.extend_from_slice(credential_bytes);
+    let mut sho = Sho::new(
+        b"Signal_ZKGroup_20220301_ProfileKeyCredential_StorageTag",
+        &data,
+    );
+    sho.squeeze(STORAGE_TAG_LEN)
+}
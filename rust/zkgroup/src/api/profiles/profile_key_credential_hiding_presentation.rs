@@ -0,0 +1,32 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::api;
+use crate::common::simple_types::*;
+use crate::crypto;
+
+/// Proves possession of a valid profile key credential for a group member without revealing any
+/// encryption of the profile key.
+///
+/// Unlike [`ProfileKeyCredentialPresentationV2`](super::ProfileKeyCredentialPresentationV2) this
+/// carries only the uid ciphertext, so the verifier learns membership and nothing about the
+/// profile key.
+#[derive(Serialize, Deserialize)]
+pub struct ProfileKeyCredentialHidingPresentation {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) proof: crypto::proofs::ProfileKeyCredentialHidingPresentationProof,
+    pub(crate) uid_enc_ciphertext: crypto::uid_encryption::Ciphertext,
+}
+
+impl ProfileKeyCredentialHidingPresentation {
+    pub fn get_uuid_ciphertext(&self) -> api::groups::UuidCiphertext {
+        api::groups::UuidCiphertext {
+            reserved: Default::default(),
+            ciphertext: self.uid_enc_ciphertext,
+        }
+    }
+}
@@ -66,15 +66,19 @@ pub enum AnyProfileKeyCredentialPresentation {
 
 impl AnyProfileKeyCredentialPresentation {
     pub fn new(presentation_bytes: &[u8]) -> Result<Self, ZkGroupDeserializationFailure> {
-        match presentation_bytes[0] {
-            PRESENTATION_VERSION_1 => {
+        match presentation_bytes.first() {
+            Some(&PRESENTATION_VERSION_1)
+                if presentation_bytes.len() == PROFILE_KEY_CREDENTIAL_PRESENTATION_V1_LEN =>
+            {
                 match bincode::deserialize::<ProfileKeyCredentialPresentationV1>(presentation_bytes)
                 {
                     Ok(presentation) => Ok(AnyProfileKeyCredentialPresentation::V1(presentation)),
                     Err(_) => Err(ZkGroupDeserializationFailure),
                 }
             }
-            PRESENTATION_VERSION_2 => {
+            Some(&PRESENTATION_VERSION_2)
+                if presentation_bytes.len() == PROFILE_KEY_CREDENTIAL_PRESENTATION_V2_LEN =>
+            {
                 match bincode::deserialize::<ProfileKeyCredentialPresentationV2>(presentation_bytes)
                 {
                     Ok(presentation) => Ok(AnyProfileKeyCredentialPresentation::V2(presentation)),
@@ -4,6 +4,7 @@
 //
 
 use crate::api;
+use crate::common::errors::*;
 use crate::common::simple_types::*;
 use crate::crypto;
 use serde::{Deserialize, Serialize};
@@ -30,4 +31,34 @@ impl ProfileKeyCredentialRequestContext {
             proof: self.proof.clone(),
         }
     }
+
+    /// Checks the request proof against this context's own uid, profile key, key pair, and
+    /// ciphertext, the same way the server will, to catch a corrupted context before sending it.
+    ///
+    /// This assumes server params without personalization; otherwise use
+    /// [`ServerPublicParams::validate_profile_key_credential_request_context`](api::ServerPublicParams::validate_profile_key_credential_request_context).
+    pub fn validate(&self) -> Result<(), ZkGroupVerificationFailure> {
+        self.validate_with_personalization(&[])
+    }
+
+    pub(crate) fn validate_with_personalization(
+        &self,
+        personalization: &[u8],
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        let profile_key_struct = crypto::profile_key_struct::ProfileKeyStruct::new(
+            self.profile_key_bytes,
+            self.uid_bytes,
+        );
+        let commitment = crypto::profile_key_commitment::CommitmentWithSecretNonce::new(
+            profile_key_struct,
+            self.uid_bytes,
+        )
+        .get_profile_key_commitment();
+        self.proof.verify(
+            self.key_pair.get_public_key(),
+            self.ciphertext_with_secret_nonce.get_ciphertext(),
+            commitment,
+            personalization,
+        )
+    }
 }
@@ -13,3 +13,9 @@ pub struct ProfileKeyCredentialResponse {
     pub(crate) blinded_credential: crypto::credentials::BlindedProfileKeyCredential,
     pub(crate) proof: crypto::proofs::ProfileKeyCredentialIssuanceProof,
 }
+
+impl ProfileKeyCredentialResponse {
+    pub fn get_blinded_credential(&self) -> crypto::credentials::BlindedProfileKeyCredential {
+        self.blinded_credential
+    }
+}
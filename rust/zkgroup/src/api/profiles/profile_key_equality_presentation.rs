@@ -0,0 +1,27 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::api;
+
+/// Two V2 presentations of profile key credentials, under the same group, that carry the same
+/// profile key ciphertext.
+///
+/// This is not a zero-knowledge proof of equality. It is just the two presentations side by side,
+/// and the verifier checks each one and then compares their profile key ciphertexts. That only
+/// works because profile key encryption is deterministic for a given group, profile key, and uid,
+/// so it has these limits:
+///
+/// - Both credentials must be for the same uid. Credentials for different uids never match, even
+///   when they hold the same profile key.
+/// - Both must be presented under the same group.
+/// - Anyone holding the group public params can see that the two ciphertexts match. They can also
+///   link either presentation to other presentations of the same uid and profile key in that group.
+#[derive(Serialize, Deserialize)]
+pub struct ProfileKeyEqualityPresentation {
+    pub(crate) first: api::profiles::ProfileKeyCredentialPresentationV2,
+    pub(crate) second: api::profiles::ProfileKeyCredentialPresentationV2,
+}
@@ -4,6 +4,7 @@
 //
 
 use crate::common::simple_types::*;
+use curve25519_dalek::subtle::{Choice, ConstantTimeEq};
 use serde::ser::SerializeTuple;
 use serde::{Serialize, Serializer};
 
@@ -24,3 +25,9 @@ impl Serialize for ProfileKeyVersion {
         seq.end()
     }
 }
+
+impl ConstantTimeEq for ProfileKeyVersion {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.bytes[..].ct_eq(&other.bytes[..])
+    }
+}
@@ -0,0 +1,571 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::api;
+use crate::api::CredentialKind;
+use crate::common::constants::*;
+use crate::common::errors::*;
+use crate::common::sho::Sho;
+use crate::common::simple_types::*;
+
+/// Returns the serialized size of every public response and presentation type, keyed by type name.
+///
+/// Sizes are measured by running a full issuance and presentation flow over fixed sample inputs,
+/// so they always reflect the current serialization. `BatchAuthCredentialResponse` grows with the
+/// batch and is measured at [`MAX_AUTH_CREDENTIAL_BATCH_SIZE`]. `RedactedPresentation` only wraps
+/// another presentation, so it has no entry of its own.
+pub fn proof_sizes() -> BTreeMap<&'static str, usize> {
+    let server_secret_params = api::ServerSecretParams::generate(TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params = api::groups::GroupSecretParams::generate(TEST_ARRAY_32_1);
+    let uid = TEST_ARRAY_16;
+    let pni = TEST_ARRAY_16_1;
+    let redemption_time = 123456;
+    let profile_key = api::profiles::ProfileKey::create(TEST_ARRAY_32_2);
+
+    // Each presentation gets its own randomness, as a real client's would.
+    let mut sho = Sho::new(b"Signal_ZKGroup_20220301_Random_ProofSizes", &[]);
+    let mut next_randomness = || -> RandomnessBytes {
+        let mut bytes = [0u8; RANDOMNESS_LEN];
+        bytes.copy_from_slice(&sho.squeeze(RANDOMNESS_LEN));
+        bytes
+    };
+
+    let mut sizes = BTreeMap::new();
+
+    let auth_credential_response = server_secret_params
+        .issue_auth_credential(TEST_ARRAY_32_3, uid, redemption_time)
+        .expect("sample uid is valid");
+    let auth_credential = server_public_params
+        .receive_auth_credential(uid, redemption_time, &auth_credential_response)
+        .expect("sample response is valid");
+    sizes.insert(
+        "AuthCredentialResponse",
+        serialized_len(&auth_credential_response),
+    );
+    sizes.insert(
+        "AuthCredentialPresentationV1",
+        serialized_len(
+            &server_public_params.create_auth_credential_presentation_v1(
+                next_randomness(),
+                group_secret_params,
+                auth_credential,
+            ),
+        ),
+    );
+    sizes.insert(
+        "AuthCredentialPresentationV2",
+        serialized_len(
+            &server_public_params.create_auth_credential_presentation_v2(
+                next_randomness(),
+                group_secret_params,
+                auth_credential,
+            ),
+        ),
+    );
+    sizes.insert(
+        "AuthCredentialBarePresentation",
+        serialized_len(
+            &server_public_params.create_bare_auth_presentation(next_randomness(), auth_credential),
+        ),
+    );
+    sizes.insert(
+        "AuthCredentialTimestampedPresentation",
+        serialized_len(
+            &server_public_params.create_auth_credential_presentation_v2_timestamped(
+                next_randomness(),
+                group_secret_params,
+                auth_credential,
+                1_650_000_000,
+            ),
+        ),
+    );
+    sizes.insert(
+        "AuthCredentialDeviceTaggedPresentation",
+        serialized_len(
+            &server_public_params.create_auth_credential_presentation_v2_with_device_tag(
+                next_randomness(),
+                group_secret_params,
+                auth_credential,
+                b"device",
+            ),
+        ),
+    );
+
+    let auth_credential_presentation = server_public_params.create_auth_credential_presentation(
+        next_randomness(),
+        group_secret_params,
+        auth_credential,
+    );
+    sizes.insert(
+        "MembershipToken",
+        serialized_len(
+            &server_secret_params
+                .issue_membership_token(
+                    TEST_ARRAY_32_5,
+                    group_secret_params.get_public_params(),
+                    &auth_credential_presentation,
+                    1_650_000_000,
+                )
+                .expect("sample presentation is valid"),
+        ),
+    );
+
+    let auth_credential_with_epoch_response = server_secret_params
+        .issue_auth_credential_with_epoch(TEST_ARRAY_32_3, uid, redemption_time, 1)
+        .expect("sample uid is valid");
+    let auth_credential_with_epoch = server_public_params
+        .receive_auth_credential_with_epoch(
+            uid,
+            redemption_time,
+            1,
+            &auth_credential_with_epoch_response,
+        )
+        .expect("sample response is valid");
+    sizes.insert(
+        "AuthCredentialWithEpochPresentation",
+        serialized_len(
+            &server_public_params.create_auth_credential_with_epoch_presentation(
+                next_randomness(),
+                group_secret_params,
+                auth_credential_with_epoch,
+            ),
+        ),
+    );
+
+    let redemption_times: Vec<_> = (0..MAX_AUTH_CREDENTIAL_BATCH_SIZE as RedemptionTime)
+        .map(|day| redemption_time + day)
+        .collect();
+    sizes.insert(
+        "BatchAuthCredentialResponse",
+        serialized_len(
+            &server_secret_params
+                .issue_auth_credential_batch(TEST_ARRAY_32_3, uid, &redemption_times)
+                .expect("sample batch is valid"),
+        ),
+    );
+
+    let context = server_public_params.create_profile_key_credential_request_context(
+        TEST_ARRAY_32_3,
+        uid,
+        profile_key,
+    );
+    let profile_key_credential_response = server_secret_params
+        .issue_profile_key_credential(
+            TEST_ARRAY_32_4,
+            &context.get_request(),
+            uid,
+            profile_key.get_commitment(uid),
+        )
+        .expect("sample request is valid");
+    let profile_key_credential = server_public_params
+        .receive_profile_key_credential(&context, &profile_key_credential_response)
+        .expect("sample response is valid");
+    sizes.insert(
+        "ProfileKeyCredentialResponse",
+        serialized_len(&profile_key_credential_response),
+    );
+    sizes.insert(
+        "ProfileKeyCredentialPresentationV1",
+        serialized_len(
+            &server_public_params.create_profile_key_credential_presentation_v1(
+                next_randomness(),
+                group_secret_params,
+                profile_key_credential,
+            ),
+        ),
+    );
+    sizes.insert(
+        "ProfileKeyCredentialPresentationV2",
+        serialized_len(
+            &server_public_params.create_profile_key_credential_presentation_v2(
+                next_randomness(),
+                group_secret_params,
+                profile_key_credential,
+            ),
+        ),
+    );
+    sizes.insert(
+        "ProfileKeyCredentialHidingPresentation",
+        serialized_len(
+            &server_public_params.create_profile_key_credential_presentation_hiding_pk(
+                next_randomness(),
+                group_secret_params,
+                profile_key_credential,
+            ),
+        ),
+    );
+    sizes.insert(
+        "ProfileKeyEqualityPresentation",
+        serialized_len(
+            &server_public_params.create_profile_key_equality_presentation(
+                next_randomness(),
+                group_secret_params,
+                profile_key_credential,
+                profile_key_credential,
+            ),
+        ),
+    );
+
+    let context = server_public_params.create_pni_credential_request_context(
+        TEST_ARRAY_32_3,
+        uid,
+        pni,
+        profile_key,
+    );
+    let pni_credential_response = server_secret_params
+        .issue_pni_credential(
+            TEST_ARRAY_32_4,
+            &context.get_request(),
+            uid,
+            pni,
+            profile_key.get_commitment(uid),
+        )
+        .expect("sample request is valid");
+    let pni_credential = server_public_params
+        .receive_pni_credential(&context, &pni_credential_response)
+        .expect("sample response is valid");
+    sizes.insert(
+        "PniCredentialResponse",
+        serialized_len(&pni_credential_response),
+    );
+    sizes.insert(
+        "PniCredentialPresentationV1",
+        serialized_len(&server_public_params.create_pni_credential_presentation_v1(
+            next_randomness(),
+            group_secret_params,
+            pni_credential,
+        )),
+    );
+    sizes.insert(
+        "PniCredentialPresentationV2",
+        serialized_len(&server_public_params.create_pni_credential_presentation_v2(
+            next_randomness(),
+            group_secret_params,
+            pni_credential,
+        )),
+    );
+
+    let context = server_public_params
+        .create_receipt_credential_request_context(TEST_ARRAY_32_3, TEST_ARRAY_16);
+    let receipt_credential_response = server_secret_params.issue_receipt_credential(
+        TEST_ARRAY_32_4,
+        &context.get_request(),
+        86400,
+        1,
+    );
+    let receipt_credential = server_public_params
+        .receive_receipt_credential(&context, &receipt_credential_response)
+        .expect("sample response is valid");
+    sizes.insert(
+        "ReceiptCredentialResponse",
+        serialized_len(&receipt_credential_response),
+    );
+    sizes.insert(
+        "ReceiptCredentialPresentation",
+        serialized_len(
+            &server_public_params
+                .create_receipt_credential_presentation(next_randomness(), &receipt_credential),
+        ),
+    );
+
+    sizes
+}
+
+/// A versioned presentation format, for comparing sizes across upgrades.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PresentationVersion {
+    AuthV1,
+    AuthV2,
+    ProfileKeyV1,
+    ProfileKeyV2,
+    PniV1,
+    PniV2,
+}
+
+impl PresentationVersion {
+    fn family(self) -> &'static str {
+        match self {
+            Self::AuthV1 | Self::AuthV2 => "Auth",
+            Self::ProfileKeyV1 | Self::ProfileKeyV2 => "ProfileKey",
+            Self::PniV1 | Self::PniV2 => "Pni",
+        }
+    }
+
+    /// The [`proof_sizes`] key of this version's presentation type.
+    #[cfg(test)]
+    fn type_name(self) -> &'static str {
+        match self {
+            Self::AuthV1 => "AuthCredentialPresentationV1",
+            Self::AuthV2 => "AuthCredentialPresentationV2",
+            Self::ProfileKeyV1 => "ProfileKeyCredentialPresentationV1",
+            Self::ProfileKeyV2 => "ProfileKeyCredentialPresentationV2",
+            Self::PniV1 => "PniCredentialPresentationV1",
+            Self::PniV2 => "PniCredentialPresentationV2",
+        }
+    }
+
+    /// The version byte a presentation of this version starts with.
+    pub(crate) fn version_byte(self) -> u8 {
+        match self {
+            Self::AuthV1 | Self::ProfileKeyV1 | Self::PniV1 => PRESENTATION_VERSION_1,
+            Self::AuthV2 | Self::ProfileKeyV2 | Self::PniV2 => PRESENTATION_VERSION_2,
+        }
+    }
+
+    fn serialized_len(self) -> usize {
+        match self {
+            Self::AuthV1 => AUTH_CREDENTIAL_PRESENTATION_V1_LEN,
+            Self::AuthV2 => AUTH_CREDENTIAL_PRESENTATION_V2_LEN,
+            Self::ProfileKeyV1 => PROFILE_KEY_CREDENTIAL_PRESENTATION_V1_LEN,
+            Self::ProfileKeyV2 => PROFILE_KEY_CREDENTIAL_PRESENTATION_V2_LEN,
+            Self::PniV1 => PNI_CREDENTIAL_PRESENTATION_V1_LEN,
+            Self::PniV2 => PNI_CREDENTIAL_PRESENTATION_V2_LEN,
+        }
+    }
+}
+
+/// Returns how many bytes larger (or, if negative, smaller) a `to` presentation is than a `from`
+/// presentation.
+///
+/// Returns `None` if the two versions belong to different credential families.
+pub fn version_size_delta(from: PresentationVersion, to: PresentationVersion) -> Option<isize> {
+    if from.family() != to.family() {
+        return None;
+    }
+    Some(to.serialized_len() as isize - from.serialized_len() as isize)
+}
+
+/// Returns the exact serialized size of a `version` presentation of a `kind` credential, without
+/// building one.
+///
+/// Receipt presentations are unversioned, so a [`CredentialKind::Receipt`] is always
+/// [`RECEIPT_CREDENTIAL_PRESENTATION_LEN`] bytes whatever `version` is. Otherwise this returns
+/// `None` if `kind` has no presentation of that version: the version is for another credential
+/// family, or `kind` is [`CredentialKind::AuthWithEpoch`] with anything but
+/// [`PresentationVersion::AuthV2`].
+///
+/// Only the standard presentation of each credential is covered. The bare, timestamped and
+/// device-tagged auth presentations and the hiding and equality profile key presentations are
+/// not; their sizes are listed in [`proof_sizes`].
+/// Presentations have no compact form, since their leading byte is a version tag; see
+/// [`CompactSerializable`](api::CompactSerializable).
+pub fn estimate_presentation_size(
+    version: PresentationVersion,
+    kind: CredentialKind,
+) -> Option<usize> {
+    use PresentationVersion::*;
+
+    match (kind, version) {
+        (CredentialKind::Auth, AuthV1 | AuthV2)
+        | (CredentialKind::ProfileKey, ProfileKeyV1 | ProfileKeyV2)
+        | (CredentialKind::Pni, PniV1 | PniV2) => Some(version.serialized_len()),
+        // A V2 presentation with the epoch appended.
+        (CredentialKind::AuthWithEpoch, AuthV2) => {
+            Some(AUTH_CREDENTIAL_PRESENTATION_V2_LEN + std::mem::size_of::<AuthCredentialEpoch>())
+        }
+        (CredentialKind::Receipt, _) => Some(RECEIPT_CREDENTIAL_PRESENTATION_LEN),
+        _ => None,
+    }
+}
+
+/// Cheaply rejects `bytes` unless its version byte and total length match a serialized
+/// presentation of `expected`.
+///
+/// This only looks at the length and first byte, so it is suitable as a filter before full
+/// deserialization; passing it says nothing about whether the contents decode.
+pub fn validate_presentation_framing(
+    bytes: &[u8],
+    expected: PresentationVersion,
+) -> Result<(), ZkGroupDeserializationFailure> {
+    if bytes.len() != expected.serialized_len() || bytes.first() != Some(&expected.version_byte()) {
+        return Err(ZkGroupDeserializationFailure);
+    }
+    Ok(())
+}
+
+fn serialized_len<T: Serialize>(value: &T) -> usize {
+    bincode::serialized_size(value).expect("can serialize") as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proof_sizes() {
+        let sizes = proof_sizes();
+        assert_eq!(19, sizes.len());
+        assert_eq!(
+            AUTH_CREDENTIAL_RESPONSE_LEN,
+            sizes["AuthCredentialResponse"]
+        );
+        assert_eq!(
+            AUTH_CREDENTIAL_PRESENTATION_V2_LEN,
+            sizes["AuthCredentialPresentationV2"]
+        );
+        assert_eq!(
+            PROFILE_KEY_CREDENTIAL_PRESENTATION_V2_LEN,
+            sizes["ProfileKeyCredentialPresentationV2"]
+        );
+        assert_eq!(
+            PNI_CREDENTIAL_PRESENTATION_V2_LEN,
+            sizes["PniCredentialPresentationV2"]
+        );
+        assert_eq!(
+            RECEIPT_CREDENTIAL_PRESENTATION_LEN,
+            sizes["ReceiptCredentialPresentation"]
+        );
+
+        // These carry a V2 proof plus what it is bound to.
+        assert_eq!(
+            AUTH_CREDENTIAL_PRESENTATION_V2_LEN + std::mem::size_of::<PresentationTimestamp>(),
+            sizes["AuthCredentialTimestampedPresentation"]
+        );
+        assert_eq!(
+            AUTH_CREDENTIAL_PRESENTATION_V2_LEN + 32,
+            sizes["AuthCredentialDeviceTaggedPresentation"]
+        );
+    }
+
+    #[test]
+    fn test_version_size_delta() {
+        use PresentationVersion::*;
+
+        assert_eq!(
+            Some(
+                AUTH_CREDENTIAL_PRESENTATION_V2_LEN as isize
+                    - AUTH_CREDENTIAL_PRESENTATION_V1_LEN as isize
+            ),
+            version_size_delta(AuthV1, AuthV2)
+        );
+        assert_eq!(
+            Some(
+                PROFILE_KEY_CREDENTIAL_PRESENTATION_V1_LEN as isize
+                    - PROFILE_KEY_CREDENTIAL_PRESENTATION_V2_LEN as isize
+            ),
+            version_size_delta(ProfileKeyV2, ProfileKeyV1)
+        );
+        assert_eq!(Some(0), version_size_delta(PniV2, PniV2));
+        assert_eq!(None, version_size_delta(AuthV2, ProfileKeyV2));
+        assert_eq!(None, version_size_delta(PniV1, AuthV1));
+    }
+
+    #[test]
+    fn test_estimate_presentation_size() {
+        use PresentationVersion::*;
+
+        let sizes = proof_sizes();
+        for &(version, kind) in &[
+            (AuthV1, CredentialKind::Auth),
+            (AuthV2, CredentialKind::Auth),
+            (ProfileKeyV1, CredentialKind::ProfileKey),
+            (ProfileKeyV2, CredentialKind::ProfileKey),
+            (PniV1, CredentialKind::Pni),
+            (PniV2, CredentialKind::Pni),
+        ] {
+            assert_eq!(
+                Some(sizes[version.type_name()]),
+                estimate_presentation_size(version, kind),
+                "{:?}",
+                version
+            );
+        }
+
+        let server_secret_params = api::ServerSecretParams::generate(TEST_ARRAY_32);
+        let server_public_params = server_secret_params.get_public_params();
+        let group_secret_params = api::groups::GroupSecretParams::generate(TEST_ARRAY_32_1);
+        let response = server_secret_params
+            .issue_auth_credential_with_epoch(TEST_ARRAY_32_2, TEST_ARRAY_16, 123456, 7)
+            .unwrap();
+        let credential = server_public_params
+            .receive_auth_credential_with_epoch(TEST_ARRAY_16, 123456, 7, &response)
+            .unwrap();
+        let presentation = server_public_params.create_auth_credential_with_epoch_presentation(
+            TEST_ARRAY_32_3,
+            group_secret_params,
+            credential,
+        );
+        assert_eq!(
+            Some(serialized_len(&presentation)),
+            estimate_presentation_size(AuthV2, CredentialKind::AuthWithEpoch)
+        );
+
+        assert_eq!(
+            None,
+            estimate_presentation_size(AuthV1, CredentialKind::AuthWithEpoch)
+        );
+        assert_eq!(
+            None,
+            estimate_presentation_size(AuthV2, CredentialKind::ProfileKey)
+        );
+        for &version in &[AuthV1, AuthV2, ProfileKeyV1, ProfileKeyV2, PniV1, PniV2] {
+            assert_eq!(
+                Some(sizes["ReceiptCredentialPresentation"]),
+                estimate_presentation_size(version, CredentialKind::Receipt),
+                "{:?}",
+                version
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_presentation_framing() {
+        use PresentationVersion::*;
+
+        let server_secret_params = api::ServerSecretParams::generate(TEST_ARRAY_32);
+        let server_public_params = server_secret_params.get_public_params();
+        let group_secret_params = api::groups::GroupSecretParams::generate(TEST_ARRAY_32_1);
+        let response = server_secret_params
+            .issue_auth_credential(TEST_ARRAY_32_2, TEST_ARRAY_16, 123456)
+            .unwrap();
+        let credential = server_public_params
+            .receive_auth_credential(TEST_ARRAY_16, 123456, &response)
+            .unwrap();
+        let v1 = bincode::serialize(
+            &server_public_params.create_auth_credential_presentation_v1(
+                TEST_ARRAY_32_3,
+                group_secret_params,
+                credential,
+            ),
+        )
+        .unwrap();
+        let v2 = bincode::serialize(
+            &server_public_params.create_auth_credential_presentation_v2(
+                TEST_ARRAY_32_4,
+                group_secret_params,
+                credential,
+            ),
+        )
+        .unwrap();
+
+        validate_presentation_framing(&v1, AuthV1).unwrap();
+        validate_presentation_framing(&v2, AuthV2).unwrap();
+        assert!(validate_presentation_framing(&v1, AuthV2).is_err());
+        assert!(validate_presentation_framing(&v2, AuthV1).is_err());
+        assert!(validate_presentation_framing(&v2[..v2.len() - 1], AuthV2).is_err());
+        assert!(validate_presentation_framing(&[], AuthV2).is_err());
+
+        // Right length, wrong version byte.
+        let mut relabeled = v2.clone();
+        relabeled[0] = PRESENTATION_VERSION_1;
+        assert!(validate_presentation_framing(&relabeled, AuthV2).is_err());
+
+        // The expected lengths match what is actually produced.
+        let sizes = proof_sizes();
+        for &version in &[AuthV1, AuthV2, ProfileKeyV1, ProfileKeyV2, PniV1, PniV2] {
+            assert_eq!(
+                version.serialized_len(),
+                sizes[version.type_name()],
+                "{:?}",
+                version
+            );
+        }
+    }
+}
@@ -18,6 +18,17 @@ pub struct ReceiptCredential {
 }
 
 impl ReceiptCredential {
+    /// See [`AuthCredential::credential_log_id`], which hashes the same fields of this type.
+    ///
+    /// [`AuthCredential::credential_log_id`]: crate::api::auth::AuthCredential::credential_log_id
+    pub fn credential_log_id(&self) -> [u8; 16] {
+        crypto::credentials::credential_log_id(
+            b"Signal_ZKGroup_20220301_ReceiptCredential_LogId",
+            self.reserved[0],
+            self.credential.U,
+        )
+    }
+
     pub fn get_receipt_expiration_time(&self) -> ReceiptExpirationTime {
         self.receipt_expiration_time
     }
@@ -0,0 +1,60 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use curve25519_dalek::subtle::{Choice, ConstantTimeEq};
+
+use crate::ReceiptSerialBytes;
+
+/// The receipt serials that have already been redeemed, for rejecting double spends.
+///
+/// Membership checks compare against every stored serial in constant time, so their timing
+/// depends only on the size of the set, not on which serials it contains. Lookups are linear; this
+/// suits a per-process cache of recent redemptions rather than a full history.
+#[derive(Clone, Default)]
+pub struct SpentSerialSet {
+    serials: Vec<ReceiptSerialBytes>,
+}
+
+impl SpentSerialSet {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn contains(&self, receipt_serial_bytes: &ReceiptSerialBytes) -> bool {
+        self.serials
+            .iter()
+            .fold(Choice::from(0), |found, spent| {
+                found | spent.ct_eq(receipt_serial_bytes)
+            })
+            .into()
+    }
+
+    /// Records `receipt_serial_bytes` as spent. Only call this after the presentation verifies.
+    ///
+    /// Returns `false` if the serial was already spent.
+    pub fn insert(&mut self, receipt_serial_bytes: ReceiptSerialBytes) -> bool {
+        if self.contains(&receipt_serial_bytes) {
+            return false;
+        }
+        self.serials.push(receipt_serial_bytes);
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.serials.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.serials.is_empty()
+    }
+}
+
+impl Extend<ReceiptSerialBytes> for SpentSerialSet {
+    fn extend<T: IntoIterator<Item = ReceiptSerialBytes>>(&mut self, iter: T) {
+        for receipt_serial_bytes in iter {
+            self.insert(receipt_serial_bytes);
+        }
+    }
+}
@@ -3,16 +3,213 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
-use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::subtle::{Choice, ConstantTimeEq};
+use serde::de::{self, DeserializeOwned, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::api;
 use crate::common::constants::*;
 use crate::common::errors::*;
+use crate::common::serialization::deserialize_strict;
 use crate::common::sho::*;
 use crate::common::simple_types::*;
 use crate::crypto;
 
-#[derive(Copy, Clone, Serialize, Deserialize)]
+type PersonalizationBytes = [u8; 32];
+
+fn derive_personalization(salt: &[u8]) -> Option<PersonalizationBytes> {
+    if salt.is_empty() {
+        return None;
+    }
+    let mut sho = Sho::new(b"Signal_ZKGroup_20220301_Personalization", salt);
+    let mut personalization = PersonalizationBytes::default();
+    personalization.copy_from_slice(&sho.squeeze(32));
+    Some(personalization)
+}
+
+fn personalization_message(personalization: &Option<PersonalizationBytes>) -> &[u8] {
+    match personalization {
+        Some(personalization) => personalization,
+        None => &[],
+    }
+}
+
+/// The proof message for a presentation bound to `data`: the personalization followed by a
+/// 32-byte hash of `data` under `label`.
+///
+/// Each kind of binding has its own `label`, so a presentation bound for one purpose fails
+/// verification as any other, or as an unbound presentation. That rests on the hashes, not on
+/// lengths: without a personalization the message is 32 bytes, as long as a personalization
+/// alone.
+fn bound_message(personalization: &[u8], label: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut sho = Sho::new(label, data);
+    let mut message = personalization.to_vec();
+    message.extend_from_slice(&sho.squeeze(32));
+    message
+}
+
+const NONCE_LABEL: &[u8] = b"Signal_ZKGroup_20220301_PresentationNonce";
+const CLIENT_TIME_LABEL: &[u8] = b"Signal_ZKGroup_20220301_PresentationClientTime";
+const DEVICE_TAG_LABEL: &[u8] = b"Signal_ZKGroup_20220301_PresentationDeviceTag";
+
+/// The proof message for a presentation bound to a context label, such as the endpoint it will be
+/// sent to.
+///
+/// Without a label this is just the personalization, so unlabeled presentations are unchanged.
+fn context_message(personalization: &[u8], context_label: Option<&[u8]>) -> Vec<u8> {
+    match context_label {
+        Some(context_label) => bound_message(
+            personalization,
+            b"Signal_ZKGroup_20220301_PresentationContextLabel",
+            context_label,
+        ),
+        None => personalization.to_vec(),
+    }
+}
+
+/// Returns `presentation`, which was made from `randomness`.
+///
+/// In test builds (and with the `test-util` feature) this first panics if `randomness` has already
+/// made a different presentation on this thread.
+fn checked_presentation<T: Serialize>(randomness: &Randomness, presentation: T) -> T {
+    #[cfg(any(test, feature = "test-util"))]
+    crate::test_util::check_presentation_randomness(randomness, &presentation);
+    #[cfg(not(any(test, feature = "test-util")))]
+    let _ = randomness;
+    presentation
+}
+
+/// Set in the reserved byte of serialized server params that carry a personalization, which then
+/// follows the keys. Params without one serialize exactly as before personalization existed, and
+/// params with one can't be read back without it.
+pub(crate) const RESERVED_PERSONALIZED: u8 = 1;
+
+/// Implements `Serialize` and `Deserialize` for a server params type as its reserved byte and
+/// `$field`s in order, then the personalization if there is one (see [`RESERVED_PERSONALIZED`]).
+macro_rules! impl_personalized_serde {
+    ($typ:ident { $($field:ident),+ $(,)? } $(derived { $($derived:ident: $derive:expr),+ $(,)? })?) => {
+        impl Serialize for $typ {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut reserved = self.reserved;
+                if self.personalization.is_some() {
+                    reserved[0] = RESERVED_PERSONALIZED;
+                }
+                let len = 1 + [$(stringify!($field)),+].len() + self.personalization.iter().count();
+                let mut tuple = serializer.serialize_tuple(len)?;
+                tuple.serialize_element(&reserved)?;
+                $(tuple.serialize_element(&self.$field)?;)+
+                if let Some(personalization) = &self.personalization {
+                    tuple.serialize_element(personalization)?;
+                }
+                tuple.end()
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $typ {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct ParamsVisitor;
+
+                impl<'de> Visitor<'de> for ParamsVisitor {
+                    type Value = $typ;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        formatter.write_str(stringify!($typ))
+                    }
+
+                    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<$typ, A::Error> {
+                        let mut next = 0;
+                        let mut reserved: ReservedBytes = next_element(&mut seq, &mut next, &self)?;
+                        $(let $field = next_element(&mut seq, &mut next, &self)?;)+
+                        let personalization = if reserved[0] == RESERVED_PERSONALIZED {
+                            reserved[0] = 0;
+                            Some(next_element(&mut seq, &mut next, &self)?)
+                        } else {
+                            None
+                        };
+                        $($(let $derived = $derive;)+)?
+                        Ok($typ {
+                            reserved,
+                            $($field,)+
+                            $($($derived,)+)?
+                            personalization,
+                        })
+                    }
+                }
+
+                let max_len = 2 + [$(stringify!($field)),+].len();
+                deserializer.deserialize_tuple(max_len, ParamsVisitor)
+            }
+        }
+    };
+}
+
+fn next_element<'de, A: SeqAccess<'de>, T: Deserialize<'de>>(
+    seq: &mut A,
+    next: &mut usize,
+    expected: &dyn de::Expected,
+) -> Result<T, A::Error> {
+    let element = seq
+        .next_element()?
+        .ok_or_else(|| de::Error::invalid_length(*next, expected))?;
+    *next += 1;
+    Ok(element)
+}
+
+/// Issuance summaries are signed with a key derived from the signing key pair, so that nothing
+/// passed to [`ServerSecretParams::sign`] can be passed off as a summary.
+const ISSUANCE_SUMMARY_SIGNING_DOMAIN: &[u8] = b"Signal_ZKGroup_20220301_IssuanceSummarySigningKey";
+
+/// Derived from the auth key pair so that existing serialized params gain membership tokens
+/// without a format change. Computed once whenever the auth key pair is set.
+fn derive_membership_token_key_pair(
+    auth_credentials_key_pair: &crypto::credentials::KeyPair<crypto::credentials::AuthCredential>,
+) -> crypto::credentials::KeyPair<crypto::credentials::MembershipToken> {
+    let mut sho = Sho::new(
+        b"Signal_ZKGroup_20220301_ServerSecretParams_MembershipTokenKeyPair",
+        &bincode::serialize(&auth_credentials_key_pair.get_secret_scalars())
+            .expect("can serialize"),
+    );
+    crypto::credentials::KeyPair::generate(&mut sho)
+}
+
+const EXTENSIBLE_TAG_AUTH_CREDENTIALS_KEY_PAIR: u8 = 1;
+const EXTENSIBLE_TAG_PROFILE_KEY_CREDENTIALS_KEY_PAIR: u8 = 2;
+const EXTENSIBLE_TAG_SIG_KEY_PAIR: u8 = 3;
+const EXTENSIBLE_TAG_RECEIPT_CREDENTIALS_KEY_PAIR: u8 = 4;
+const EXTENSIBLE_TAG_PNI_CREDENTIALS_KEY_PAIR: u8 = 5;
+const EXTENSIBLE_TAG_PERSONALIZATION: u8 = 6;
+
+const EXTENSIBLE_ENTRY_HEADER_LEN: usize = 5;
+
+fn write_extensible_entry<T: Serialize>(bytes: &mut Vec<u8>, tag: u8, value: &T) {
+    let value_bytes = bincode::serialize(value).expect("can serialize");
+    bytes.push(tag);
+    bytes.extend_from_slice(&(value_bytes.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&value_bytes);
+}
+
+fn read_extensible_entry<T: DeserializeOwned>(
+    slot: &mut Option<T>,
+    value_bytes: &[u8],
+) -> Result<(), ZkGroupDeserializationFailure> {
+    if slot.is_some() {
+        return Err(ZkGroupDeserializationFailure);
+    }
+    *slot = Some(deserialize_strict(value_bytes)?);
+    Ok(())
+}
+
+/// Serializes as the keys in order, followed by the personalization if there is one. The bridges'
+/// fixed-length encodings only hold params without a personalization.
+#[derive(Copy, Clone)]
 pub struct ServerSecretParams {
     pub(crate) reserved: ReservedBytes,
     pub(crate) auth_credentials_key_pair:
@@ -23,9 +220,13 @@ pub struct ServerSecretParams {
     receipt_credentials_key_pair:
         crypto::credentials::KeyPair<crypto::credentials::ReceiptCredential>,
     pni_credentials_key_pair: crypto::credentials::KeyPair<crypto::credentials::PniCredential>,
+    /// Not serialized; see [`derive_membership_token_key_pair`].
+    membership_token_key_pair: crypto::credentials::KeyPair<crypto::credentials::MembershipToken>,
+    personalization: Option<PersonalizationBytes>,
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize)]
+/// Serialized like [`ServerSecretParams`], including the personalization.
+#[derive(Copy, Clone)]
 pub struct ServerPublicParams {
     pub(crate) reserved: ReservedBytes,
     pub(crate) auth_credentials_public_key: crypto::credentials::PublicKey,
@@ -33,20 +234,83 @@ pub struct ServerPublicParams {
     sig_public_key: crypto::signature::PublicKey,
     receipt_credentials_public_key: crypto::credentials::PublicKey,
     pni_credentials_public_key: crypto::credentials::PublicKey,
+    personalization: Option<PersonalizationBytes>,
+}
+
+/// The subset of [`ServerSecretParams`] needed to verify auth credential presentations.
+///
+/// Auth credentials are MAC-based, so presentations can only be verified with the secret auth key
+/// pair; there is no public-only equivalent. This carries that key pair, the public signature
+/// key, and the personalization, and nothing else.
+#[derive(Copy, Clone)]
+pub struct AuthOnlyServerParams {
+    pub(crate) reserved: ReservedBytes,
+    pub(crate) auth_credentials_key_pair:
+        crypto::credentials::KeyPair<crypto::credentials::AuthCredential>,
+    sig_public_key: crypto::signature::PublicKey,
+    personalization: Option<PersonalizationBytes>,
+}
+
+impl_personalized_serde!(ServerSecretParams {
+    auth_credentials_key_pair,
+    profile_key_credentials_key_pair,
+    sig_key_pair,
+    receipt_credentials_key_pair,
+    pni_credentials_key_pair,
+} derived {
+    membership_token_key_pair: derive_membership_token_key_pair(&auth_credentials_key_pair),
+});
+
+impl_personalized_serde!(ServerPublicParams {
+    auth_credentials_public_key,
+    profile_key_credentials_public_key,
+    sig_public_key,
+    receipt_credentials_public_key,
+    pni_credentials_public_key,
+});
+
+impl_personalized_serde!(AuthOnlyServerParams {
+    auth_credentials_key_pair,
+    sig_public_key,
+});
+
+/// Distinguishes malformed notary signatures from well-formed ones before the (more expensive)
+/// call to `verify_signature`.
+pub fn validate_signature_encoding(
+    signature: &NotarySignatureBytes,
+) -> Result<(), ZkGroupDeserializationFailure> {
+    crypto::signature::validate_encoding(signature)
 }
 
 impl ServerSecretParams {
-    pub fn generate(randomness: RandomnessBytes) -> Self {
+    pub fn generate(randomness: impl Into<RandomnessBytes>) -> Self {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20200424_Random_ServerSecretParams_Generate",
-            &randomness,
+            randomness.expose_bytes(),
         );
+        Self::generate_from_sho(&mut sho)
+    }
 
-        let auth_credentials_key_pair = crypto::credentials::KeyPair::generate(&mut sho);
-        let profile_key_credentials_key_pair = crypto::credentials::KeyPair::generate(&mut sho);
-        let sig_key_pair = crypto::signature::KeyPair::generate(&mut sho);
-        let receipt_credentials_key_pair = crypto::credentials::KeyPair::generate(&mut sho);
-        let pni_credentials_key_pair = crypto::credentials::KeyPair::generate(&mut sho);
+    /// Derives params from a secret shared by every member of a federation, so that each member
+    /// gets byte-identical params and can verify credentials issued by the others.
+    ///
+    /// The derivation is domain-separated from [`generate`](Self::generate): the same 32 bytes
+    /// produce different params through each.
+    pub fn derive_from_shared_secret(secret: &[u8; 32]) -> Self {
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_ServerSecretParams_DeriveFromSharedSecret",
+            secret,
+        );
+        Self::generate_from_sho(&mut sho)
+    }
+
+    fn generate_from_sho(sho: &mut Sho) -> Self {
+        let auth_credentials_key_pair = crypto::credentials::KeyPair::generate(sho);
+        let profile_key_credentials_key_pair = crypto::credentials::KeyPair::generate(sho);
+        let sig_key_pair = crypto::signature::KeyPair::generate(sho);
+        let receipt_credentials_key_pair = crypto::credentials::KeyPair::generate(sho);
+        let pni_credentials_key_pair = crypto::credentials::KeyPair::generate(sho);
 
         Self {
             reserved: Default::default(),
@@ -55,7 +319,206 @@ impl ServerSecretParams {
             sig_key_pair,
             receipt_credentials_key_pair,
             pni_credentials_key_pair,
+            membership_token_key_pair: derive_membership_token_key_pair(&auth_credentials_key_pair),
+            personalization: None,
+        }
+    }
+
+    /// Whether `other` holds the same keys, compared in constant time.
+    ///
+    /// Params with the same keys issue and verify each other's credentials. Personalization is not
+    /// compared, since it is not part of the keys and is reapplied separately after loading.
+    pub fn ct_eq(&self, other: &ServerSecretParams) -> bool {
+        let equal = self
+            .auth_credentials_key_pair
+            .ct_eq(&other.auth_credentials_key_pair)
+            & self
+                .profile_key_credentials_key_pair
+                .ct_eq(&other.profile_key_credentials_key_pair)
+            & self.sig_key_pair.ct_eq(&other.sig_key_pair)
+            & self
+                .receipt_credentials_key_pair
+                .ct_eq(&other.receipt_credentials_key_pair)
+            & self
+                .pni_credentials_key_pair
+                .ct_eq(&other.pni_credentials_key_pair);
+        equal.into()
+    }
+
+    /// Like [`generate`](Self::generate), but binds every proof to a per-deployment `salt`.
+    ///
+    /// Proofs made under one salt fail verification under any other. An empty salt is the same as
+    /// no personalization. The personalization derived from the salt is serialized with the
+    /// params, and carried over to their public params.
+    pub fn generate_with_personalization(
+        randomness: impl Into<RandomnessBytes>,
+        salt: &[u8],
+    ) -> Self {
+        Self::generate(randomness).with_personalization(salt)
+    }
+
+    pub fn with_personalization(self, salt: &[u8]) -> Self {
+        Self {
+            personalization: derive_personalization(salt),
+            ..self
+        }
+    }
+
+    /// Returns params whose credential key pairs are derived from these ones and
+    /// `service_context`, so that credentials issued for one service fail to verify for any
+    /// other, even though every service shares the same stored params.
+    ///
+    /// Clients of the service need the matching
+    /// [`get_public_params`](Self::get_public_params) of the returned params. The signing key and
+    /// personalization are kept as they are. An empty context returns these params unchanged.
+    pub fn with_service_context(&self, service_context: &[u8]) -> Self {
+        if service_context.is_empty() {
+            return *self;
+        }
+        let secret_scalars = [
+            self.auth_credentials_key_pair.get_secret_scalars(),
+            self.profile_key_credentials_key_pair.get_secret_scalars(),
+            self.receipt_credentials_key_pair.get_secret_scalars(),
+            self.pni_credentials_key_pair.get_secret_scalars(),
+        ];
+        let mut data = bincode::serialize(&secret_scalars).expect("can serialize");
+        data.extend_from_slice(service_context);
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_ServerSecretParams_ServiceContext",
+            &data,
+        );
+
+        let auth_credentials_key_pair = crypto::credentials::KeyPair::generate(&mut sho);
+        Self {
+            auth_credentials_key_pair,
+            profile_key_credentials_key_pair: crypto::credentials::KeyPair::generate(&mut sho),
+            receipt_credentials_key_pair: crypto::credentials::KeyPair::generate(&mut sho),
+            pni_credentials_key_pair: crypto::credentials::KeyPair::generate(&mut sho),
+            membership_token_key_pair: derive_membership_token_key_pair(&auth_credentials_key_pair),
+            ..*self
+        }
+    }
+
+    /// Returns a copy of these params that issues and verifies receipt credentials with
+    /// `key_pair`, keeping every other key.
+    ///
+    /// Pair this with [`ServerPublicParams::with_receipt_key`] on the client side. Keeping each
+    /// receipt key within the environment it was made for is the caller's responsibility.
+    pub fn with_receipt_key_pair(
+        &self,
+        key_pair: crypto::credentials::KeyPair<crypto::credentials::ReceiptCredential>,
+    ) -> Self {
+        Self {
+            receipt_credentials_key_pair: key_pair,
+            ..*self
+        }
+    }
+
+    /// Serializes these params as a sequence of tagged entries, one per key pair.
+    ///
+    /// Each entry is a one-byte tag, a big-endian `u32` length, and the standard serialization of
+    /// the key pair. Unlike the fixed layout used by `bincode`, key pairs added in later versions
+    /// get new tags, so blobs stay readable in both directions. A personalization is written as one
+    /// more entry.
+    pub fn serialize_extensible(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_extensible_entry(
+            &mut bytes,
+            EXTENSIBLE_TAG_AUTH_CREDENTIALS_KEY_PAIR,
+            &self.auth_credentials_key_pair,
+        );
+        write_extensible_entry(
+            &mut bytes,
+            EXTENSIBLE_TAG_PROFILE_KEY_CREDENTIALS_KEY_PAIR,
+            &self.profile_key_credentials_key_pair,
+        );
+        write_extensible_entry(&mut bytes, EXTENSIBLE_TAG_SIG_KEY_PAIR, &self.sig_key_pair);
+        write_extensible_entry(
+            &mut bytes,
+            EXTENSIBLE_TAG_RECEIPT_CREDENTIALS_KEY_PAIR,
+            &self.receipt_credentials_key_pair,
+        );
+        write_extensible_entry(
+            &mut bytes,
+            EXTENSIBLE_TAG_PNI_CREDENTIALS_KEY_PAIR,
+            &self.pni_credentials_key_pair,
+        );
+        if let Some(personalization) = &self.personalization {
+            write_extensible_entry(&mut bytes, EXTENSIBLE_TAG_PERSONALIZATION, personalization);
+        }
+        bytes
+    }
+
+    /// Reads params written by [`serialize_extensible`](Self::serialize_extensible).
+    ///
+    /// Entries with unknown tags are skipped, so blobs from newer versions can still be read. The
+    /// key pairs present in this version are all required; a repeated tag is an error.
+    pub fn deserialize_extensible(bytes: &[u8]) -> Result<Self, ZkGroupDeserializationFailure> {
+        let mut auth_credentials_key_pair = None;
+        let mut profile_key_credentials_key_pair = None;
+        let mut sig_key_pair = None;
+        let mut receipt_credentials_key_pair = None;
+        let mut pni_credentials_key_pair = None;
+        let mut personalization = None;
+
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            if rest.len() < EXTENSIBLE_ENTRY_HEADER_LEN {
+                return Err(ZkGroupDeserializationFailure);
+            }
+            let (header, after_header) = rest.split_at(EXTENSIBLE_ENTRY_HEADER_LEN);
+            let mut len_bytes = [0u8; 4];
+            len_bytes.copy_from_slice(&header[1..]);
+            let len = u32::from_be_bytes(len_bytes) as usize;
+            if after_header.len() < len {
+                return Err(ZkGroupDeserializationFailure);
+            }
+            let (value_bytes, after_value) = after_header.split_at(len);
+            rest = after_value;
+
+            match header[0] {
+                EXTENSIBLE_TAG_AUTH_CREDENTIALS_KEY_PAIR => {
+                    read_extensible_entry(&mut auth_credentials_key_pair, value_bytes)?
+                }
+                EXTENSIBLE_TAG_PROFILE_KEY_CREDENTIALS_KEY_PAIR => {
+                    read_extensible_entry(&mut profile_key_credentials_key_pair, value_bytes)?
+                }
+                EXTENSIBLE_TAG_SIG_KEY_PAIR => {
+                    read_extensible_entry(&mut sig_key_pair, value_bytes)?
+                }
+                EXTENSIBLE_TAG_RECEIPT_CREDENTIALS_KEY_PAIR => {
+                    read_extensible_entry(&mut receipt_credentials_key_pair, value_bytes)?
+                }
+                EXTENSIBLE_TAG_PNI_CREDENTIALS_KEY_PAIR => {
+                    read_extensible_entry(&mut pni_credentials_key_pair, value_bytes)?
+                }
+                EXTENSIBLE_TAG_PERSONALIZATION => {
+                    read_extensible_entry(&mut personalization, value_bytes)?
+                }
+                // Written by a newer version.
+                _ => {}
+            }
         }
+
+        let auth_credentials_key_pair =
+            auth_credentials_key_pair.ok_or(ZkGroupDeserializationFailure)?;
+        Ok(Self {
+            reserved: Default::default(),
+            auth_credentials_key_pair,
+            profile_key_credentials_key_pair: profile_key_credentials_key_pair
+                .ok_or(ZkGroupDeserializationFailure)?,
+            sig_key_pair: sig_key_pair.ok_or(ZkGroupDeserializationFailure)?,
+            receipt_credentials_key_pair: receipt_credentials_key_pair
+                .ok_or(ZkGroupDeserializationFailure)?,
+            pni_credentials_key_pair: pni_credentials_key_pair
+                .ok_or(ZkGroupDeserializationFailure)?,
+            membership_token_key_pair: derive_membership_token_key_pair(&auth_credentials_key_pair),
+            personalization,
+        })
+    }
+
+    fn personalization(&self) -> &[u8] {
+        personalization_message(&self.personalization)
     }
 
     pub fn get_public_params(&self) -> ServerPublicParams {
@@ -68,43 +531,190 @@ impl ServerSecretParams {
             sig_public_key: self.sig_key_pair.get_public_key(),
             receipt_credentials_public_key: self.receipt_credentials_key_pair.get_public_key(),
             pni_credentials_public_key: self.pni_credentials_key_pair.get_public_key(),
+            personalization: self.personalization,
         }
     }
 
-    pub fn sign(&self, randomness: RandomnessBytes, message: &[u8]) -> NotarySignatureBytes {
+    pub fn sign(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        message: &[u8],
+    ) -> NotarySignatureBytes {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20200424_Random_ServerSecretParams_Sign",
-            &randomness,
+            randomness.expose_bytes(),
         );
         self.sig_key_pair.sign(message, &mut sho)
     }
 
+    /// Signs a summary of a batch issuance, for later reconciliation.
+    ///
+    /// Check the signature with [`ServerPublicParams::verify_issuance_summary`].
+    pub fn sign_issuance_summary(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        summary: &api::IssuanceSummary,
+    ) -> NotarySignatureBytes {
+        let randomness = Randomness::new(randomness.into());
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_Random_ServerSecretParams_SignIssuanceSummary",
+            randomness.expose_bytes(),
+        );
+        self.sig_key_pair
+            .derive_domain_key_pair(ISSUANCE_SUMMARY_SIGNING_DOMAIN)
+            .sign(&summary.signed_message(), &mut sho)
+    }
+
+    /// Sanity-checks the secret scalars of every key pair, to detect params generated from a
+    /// broken randomness source.
+    ///
+    /// Fails if any scalar is zero or one, or if any two scalars (in the same key pair or across
+    /// key pairs) are equal. The error names the first key pair found to be suspect.
+    pub fn self_check(&self) -> Result<(), ZkGroupSelfCheckFailure> {
+        let key_scalars = [
+            (
+                "auth_credentials_key_pair",
+                self.auth_credentials_key_pair.get_secret_scalars(),
+            ),
+            (
+                "profile_key_credentials_key_pair",
+                self.profile_key_credentials_key_pair.get_secret_scalars(),
+            ),
+            ("sig_key_pair", vec![self.sig_key_pair.signing_key]),
+            (
+                "receipt_credentials_key_pair",
+                self.receipt_credentials_key_pair.get_secret_scalars(),
+            ),
+            (
+                "pni_credentials_key_pair",
+                self.pni_credentials_key_pair.get_secret_scalars(),
+            ),
+        ];
+
+        let mut seen = Vec::new();
+        for (name, scalars) in key_scalars.iter() {
+            for scalar in scalars {
+                if *scalar == Scalar::zero() || *scalar == Scalar::one() || seen.contains(scalar) {
+                    return Err(ZkGroupSelfCheckFailure(name));
+                }
+                seen.push(*scalar);
+            }
+        }
+        Ok(())
+    }
+
+    /// The canonical encoding of the notary signing public key, for pinning or publishing
+    /// independently of the rest of the public params.
+    pub fn signing_public_key_bytes(&self) -> [u8; 32] {
+        self.sig_key_pair.get_public_key().to_bytes()
+    }
+
     pub fn issue_auth_credential(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         uid_bytes: UidBytes,
         redemption_time: RedemptionTime,
-    ) -> api::auth::AuthCredentialResponse {
+    ) -> Result<api::auth::AuthCredentialResponse, ZkGroupVerificationFailure> {
+        self.issue_auth_credential_with_epoch(randomness, uid_bytes, redemption_time, 0)
+    }
+
+    /// Issues one auth credential for `uid_bytes` per entry of `redemption_times`, all covered by
+    /// a single issuance proof.
+    ///
+    /// Fails if `redemption_times` is empty or longer than [`MAX_AUTH_CREDENTIAL_BATCH_SIZE`], or
+    /// if the uid is invalid.
+    pub fn issue_auth_credential_batch(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        uid_bytes: UidBytes,
+        redemption_times: &[RedemptionTime],
+    ) -> Result<api::auth::BatchAuthCredentialResponse, ZkGroupVerificationFailure> {
+        if redemption_times.is_empty() || redemption_times.len() > MAX_AUTH_CREDENTIAL_BATCH_SIZE {
+            return Err(ZkGroupVerificationFailure);
+        }
+        let randomness = Randomness::new(randomness.into());
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_Random_ServerSecretParams_IssueAuthCredentialBatch",
+            randomness.expose_bytes(),
+        );
+
+        let uid = crypto::uid_struct::UidStruct::new(uid_bytes)?;
+        let credentials: Vec<_> = redemption_times
+            .iter()
+            .map(|&redemption_time| {
+                self.auth_credentials_key_pair.create_auth_credential(
+                    uid,
+                    redemption_time,
+                    0,
+                    &mut sho,
+                )
+            })
+            .collect();
+        let proof = crypto::proofs::AuthCredentialBatchIssuanceProof::new(
+            self.auth_credentials_key_pair,
+            &credentials,
+            uid,
+            redemption_times,
+            self.personalization(),
+            &mut sho,
+        );
+        Ok(api::auth::BatchAuthCredentialResponse {
+            reserved: Default::default(),
+            credentials,
+            proof,
+        })
+    }
+
+    /// Issues an auth credential bound to `epoch`.
+    ///
+    /// Presentations of the resulting credential can be rejected en masse by raising the
+    /// `min_epoch` passed to [`verify_auth_credential_with_epoch_presentation`]. Epoch 0 produces
+    /// the same response as [`issue_auth_credential`](Self::issue_auth_credential).
+    ///
+    /// [`verify_auth_credential_with_epoch_presentation`]: Self::verify_auth_credential_with_epoch_presentation
+    pub fn issue_auth_credential_with_epoch(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        uid_bytes: UidBytes,
+        redemption_time: RedemptionTime,
+        epoch: AuthCredentialEpoch,
+    ) -> Result<api::auth::AuthCredentialResponse, ZkGroupVerificationFailure> {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20200424_Random_ServerSecretParams_IssueAuthCredential",
-            &randomness,
+            randomness.expose_bytes(),
         );
 
-        let uid = crypto::uid_struct::UidStruct::new(uid_bytes);
-        let credential =
-            self.auth_credentials_key_pair
-                .create_auth_credential(uid, redemption_time, &mut sho);
+        let uid = crypto::uid_struct::UidStruct::new(uid_bytes)?;
+        let credential = self.auth_credentials_key_pair.create_auth_credential(
+            uid,
+            redemption_time,
+            epoch,
+            &mut sho,
+        );
         let proof = crypto::proofs::AuthCredentialIssuanceProof::new(
             self.auth_credentials_key_pair,
             credential,
             uid,
             redemption_time,
+            epoch,
+            self.personalization(),
             &mut sho,
         );
-        api::auth::AuthCredentialResponse {
+        Ok(api::auth::AuthCredentialResponse {
             reserved: Default::default(),
             credential,
             proof,
+        })
+    }
+
+    pub fn auth_only(&self) -> AuthOnlyServerParams {
+        AuthOnlyServerParams {
+            reserved: Default::default(),
+            auth_credentials_key_pair: self.auth_credentials_key_pair,
+            sig_public_key: self.sig_key_pair.get_public_key(),
+            personalization: self.personalization,
         }
     }
 
@@ -113,25 +723,247 @@ impl ServerSecretParams {
         group_public_params: api::groups::GroupPublicParams,
         presentation: &api::auth::AnyAuthCredentialPresentation,
     ) -> Result<(), ZkGroupVerificationFailure> {
-        match presentation {
-            api::auth::AnyAuthCredentialPresentation::V1(presentation_v1) => {
-                presentation_v1.proof.verify(
-                    self.auth_credentials_key_pair,
-                    group_public_params.uid_enc_public_key,
-                    presentation_v1.ciphertext,
-                    presentation_v1.redemption_time,
-                )
-            }
+        self.auth_only()
+            .verify_auth_credential_presentation(group_public_params, presentation)
+    }
 
-            api::auth::AnyAuthCredentialPresentation::V2(presentation_v2) => {
-                presentation_v2.proof.verify(
-                    self.auth_credentials_key_pair,
-                    group_public_params.uid_enc_public_key,
-                    presentation_v2.ciphertext,
-                    presentation_v2.redemption_time,
-                )
+    /// Verifies every auth credential presentation in a file written with
+    /// [`write_framed_presentation`](api::write_framed_presentation), against a single group.
+    ///
+    /// The file is streamed one record at a time. Records that fail to deserialize or verify are
+    /// listed by byte offset in the report; an I/O error or broken framing stops the scan.
+    pub fn verify_file(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        path: &Path,
+    ) -> io::Result<api::VerificationReport> {
+        let mut reader = io::BufReader::new(File::open(path)?);
+        api::presentation_file::verify_framed(&mut reader, |bytes| {
+            match api::auth::AnyAuthCredentialPresentation::new(bytes) {
+                Ok(presentation) => self
+                    .verify_auth_credential_presentation(group_public_params, &presentation)
+                    .is_ok(),
+                Err(_) => false,
             }
+        })
+    }
+
+    /// Verifies `presentation` with the params encoded in `snapshot_bytes`, so an audit can name
+    /// the exact key material used.
+    ///
+    /// The snapshot must be the canonical bincode encoding of a `ServerSecretParams`, with no
+    /// trailing bytes, and must pass [`self_check`](Self::self_check); both are checked before the
+    /// presentation is looked at.
+    pub fn verify_from_snapshot(
+        snapshot_bytes: &[u8],
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+    ) -> Result<(), ZkGroupCheckFailure> {
+        let params: Self = deserialize_strict(snapshot_bytes)
+            .map_err(|_| ZkGroupCheckFailure::SnapshotNotCanonical)?;
+        params
+            .self_check()
+            .map_err(|ZkGroupSelfCheckFailure(name)| {
+                ZkGroupCheckFailure::SnapshotSelfCheck(name)
+            })?;
+        params.verify_auth_credential_presentation(group_public_params, presentation)?;
+        Ok(())
+    }
+
+    pub fn verify_auth_credential_presentation_any_group(
+        &self,
+        groups: &[api::groups::GroupPublicParams],
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+    ) -> Result<usize, ZkGroupVerificationFailure> {
+        self.auth_only()
+            .verify_auth_credential_presentation_any_group(groups, presentation)
+    }
+
+    pub fn verify_auth_credential_presentation_in_range(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+        start_day: RedemptionTime,
+        end_day: RedemptionTime,
+    ) -> Result<(), ZkGroupCheckFailure> {
+        self.auth_only()
+            .verify_auth_credential_presentation_in_range(
+                group_public_params,
+                presentation,
+                start_day,
+                end_day,
+            )
+    }
+
+    pub fn verify_auth_credential_presentation_not_future(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+        now_day: RedemptionTime,
+    ) -> Result<(), ZkGroupCheckFailure> {
+        self.auth_only()
+            .verify_auth_credential_presentation_not_future(
+                group_public_params,
+                presentation,
+                now_day,
+            )
+    }
+
+    pub fn verify_auth_credential_non_membership(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+        members: &[api::groups::UuidCiphertext],
+    ) -> Result<(), ZkGroupCheckFailure> {
+        self.auth_only().verify_auth_credential_non_membership(
+            group_public_params,
+            presentation,
+            members,
+        )
+    }
+
+    pub fn verify_auth_credential_presentation_min_version(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+        min: api::PresentationVersion,
+    ) -> Result<(), ZkGroupCheckFailure> {
+        self.auth_only()
+            .verify_auth_credential_presentation_min_version(group_public_params, presentation, min)
+    }
+
+    pub fn verify_auth_credential_presentation_with_ttl(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+        now_day: RedemptionTime,
+    ) -> Result<u64, ZkGroupCheckFailure> {
+        self.auth_only()
+            .verify_auth_credential_presentation_with_ttl(
+                group_public_params,
+                presentation,
+                now_day,
+            )
+    }
+
+    pub fn verify_auth_credential_presentation_monotonic(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+        last_seen: Option<RedemptionTime>,
+    ) -> Result<RedemptionTime, ZkGroupCheckFailure> {
+        self.auth_only()
+            .verify_auth_credential_presentation_monotonic(
+                group_public_params,
+                presentation,
+                last_seen,
+            )
+    }
+
+    pub fn verify_redacted_presentation(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        redacted: &api::auth::RedactedPresentation,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        self.auth_only()
+            .verify_redacted_presentation(group_public_params, redacted)
+    }
+
+    pub fn verify_auth_credential_presentation_canonical(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+    ) -> Result<Vec<u8>, ZkGroupVerificationFailure> {
+        self.auth_only()
+            .verify_auth_credential_presentation_canonical(group_public_params, presentation)
+    }
+
+    pub fn verify_auth_credential_presentation_with_pseudonym(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+    ) -> Result<[u8; 32], ZkGroupVerificationFailure> {
+        self.auth_only()
+            .verify_auth_credential_presentation_with_pseudonym(group_public_params, presentation)
+    }
+
+    /// Verifies `presentation` and issues a token that lets the same member skip verification
+    /// for this group until `expiration`.
+    ///
+    /// The token is bound to the presentation's pseudonym (see
+    /// [`verify_auth_credential_presentation_with_pseudonym`](Self::verify_auth_credential_presentation_with_pseudonym)),
+    /// the group, and `expiration`, and can only be checked with these params.
+    pub fn issue_membership_token(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+        expiration: u64,
+    ) -> Result<api::auth::MembershipToken, ZkGroupVerificationFailure> {
+        let pseudonym = self.verify_auth_credential_presentation_with_pseudonym(
+            group_public_params,
+            presentation,
+        )?;
+
+        let randomness = Randomness::new(randomness.into());
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_Random_ServerSecretParams_IssueMembershipToken",
+            randomness.expose_bytes(),
+        );
+        let points = crypto::credentials::convert_to_points_membership(
+            &pseudonym,
+            &group_public_params.get_group_identifier(),
+            expiration,
+        );
+        let token = self
+            .membership_token_key_pair
+            .create_membership_token(points, &mut sho);
+        Ok(api::auth::MembershipToken {
+            reserved: Default::default(),
+            token,
+            pseudonym,
+            expiration,
+        })
+    }
+
+    /// Checks a token from [`issue_membership_token`](Self::issue_membership_token) for this
+    /// group, failing once `now` reaches the token's expiration.
+    pub fn verify_membership_token(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        token: &api::auth::MembershipToken,
+        now: u64,
+    ) -> Result<(), ZkGroupCheckFailure> {
+        let points = crypto::credentials::convert_to_points_membership(
+            &token.pseudonym,
+            &group_public_params.get_group_identifier(),
+            token.expiration,
+        );
+        self.membership_token_key_pair
+            .verify_membership_token(token.token, points)?;
+        if now >= token.expiration {
+            return Err(ZkGroupCheckFailure::Expired);
         }
+        Ok(())
+    }
+
+    pub fn verify_auth_credential_presentations<I, P>(
+        &self,
+        presentations: I,
+    ) -> Result<(), ZkGroupVerificationFailure>
+    where
+        I: IntoIterator<Item = (api::groups::GroupPublicParams, P)>,
+        P: Borrow<api::auth::AnyAuthCredentialPresentation>,
+    {
+        self.auth_only()
+            .verify_auth_credential_presentations(presentations)
+    }
+
+    pub fn verify_bare_auth_presentation(
+        &self,
+        presentation: &api::auth::AuthCredentialBarePresentation,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        self.auth_only().verify_bare_auth_presentation(presentation)
     }
 
     pub fn verify_auth_credential_presentation_v1(
@@ -144,6 +976,8 @@ impl ServerSecretParams {
             group_public_params.uid_enc_public_key,
             presentation.ciphertext,
             presentation.redemption_time,
+            0,
+            self.personalization(),
         )
     }
 
@@ -151,12 +985,131 @@ impl ServerSecretParams {
         &self,
         group_public_params: api::groups::GroupPublicParams,
         presentation: &api::auth::AuthCredentialPresentationV2,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        self.verify_auth_credential_presentation_v2_with_context(
+            group_public_params,
+            presentation,
+            None,
+        )
+    }
+
+    /// Verifies a presentation created with
+    /// [`ServerPublicParams::create_auth_credential_presentation_v2_with_context`].
+    ///
+    /// Fails unless `expected_context_label` matches the label the presentation was created with.
+    pub fn verify_auth_credential_presentation_v2_with_context(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AuthCredentialPresentationV2,
+        expected_context_label: Option<&[u8]>,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        presentation.proof.verify(
+            self.auth_credentials_key_pair,
+            group_public_params.uid_enc_public_key,
+            presentation.ciphertext,
+            presentation.redemption_time,
+            0,
+            &context_message(self.personalization(), expected_context_label),
+        )
+    }
+
+    /// Verifies a presentation created with
+    /// [`ServerPublicParams::create_auth_credential_presentation_v2_with_nonce`].
+    ///
+    /// Fails unless `expected_nonce` is the nonce the presentation was created with. Making the
+    /// nonce single-use (and short-lived) is up to the caller.
+    pub fn verify_auth_credential_presentation_v2_with_nonce(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AuthCredentialPresentationV2,
+        expected_nonce: &[u8],
     ) -> Result<(), ZkGroupVerificationFailure> {
         presentation.proof.verify(
             self.auth_credentials_key_pair,
             group_public_params.uid_enc_public_key,
             presentation.ciphertext,
             presentation.redemption_time,
+            0,
+            &bound_message(self.personalization(), NONCE_LABEL, expected_nonce),
+        )
+    }
+
+    /// Verifies a presentation created with
+    /// [`ServerPublicParams::create_auth_credential_presentation_v2_timestamped`], and checks that
+    /// its client time is less than `window` seconds from `server_time` in either direction.
+    ///
+    /// The client time is covered by the proof, so it is only trusted once the proof verifies.
+    pub fn verify_auth_credential_timestamped_presentation(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AuthCredentialTimestampedPresentation,
+        server_time: PresentationTimestamp,
+        window: u64,
+    ) -> Result<(), ZkGroupCheckFailure> {
+        presentation.proof.verify(
+            self.auth_credentials_key_pair,
+            group_public_params.uid_enc_public_key,
+            presentation.ciphertext,
+            presentation.redemption_time,
+            0,
+            &bound_message(
+                self.personalization(),
+                CLIENT_TIME_LABEL,
+                &presentation.client_time.to_be_bytes(),
+            ),
+        )?;
+        let client_time = presentation.client_time;
+        if client_time.saturating_add(window) <= server_time
+            || server_time.saturating_add(window) <= client_time
+        {
+            return Err(ZkGroupCheckFailure::ClientTimeOutOfWindow);
+        }
+        Ok(())
+    }
+
+    /// Verifies a presentation created with
+    /// [`ServerPublicParams::create_auth_credential_presentation_v2_with_device_tag`], returning
+    /// its device tag commitment.
+    ///
+    /// The commitment is the same for every presentation from one device in one group, and
+    /// differs between devices, but says nothing else about the device.
+    pub fn verify_auth_credential_device_tagged_presentation(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AuthCredentialDeviceTaggedPresentation,
+    ) -> Result<[u8; 32], ZkGroupVerificationFailure> {
+        presentation.proof.verify(
+            self.auth_credentials_key_pair,
+            group_public_params.uid_enc_public_key,
+            presentation.ciphertext,
+            presentation.redemption_time,
+            0,
+            &bound_message(
+                self.personalization(),
+                DEVICE_TAG_LABEL,
+                &presentation.device_tag_commitment,
+            ),
+        )?;
+        Ok(presentation.device_tag_commitment)
+    }
+
+    /// Verifies the presentation, rejecting credentials issued for an epoch before `min_epoch`.
+    pub fn verify_auth_credential_with_epoch_presentation(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AuthCredentialWithEpochPresentation,
+        min_epoch: AuthCredentialEpoch,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        if presentation.epoch < min_epoch {
+            return Err(ZkGroupVerificationFailure);
+        }
+        presentation.proof.verify(
+            self.auth_credentials_key_pair,
+            group_public_params.uid_enc_public_key,
+            presentation.ciphertext,
+            presentation.redemption_time,
+            presentation.epoch,
+            self.personalization(),
         )
     }
 
@@ -176,6 +1129,7 @@ impl ServerSecretParams {
                     uid_enc_public_key,
                     presentation_v1.profile_key_enc_ciphertext,
                     profile_key_enc_public_key,
+                    self.personalization(),
                 )
             }
 
@@ -186,11 +1140,99 @@ impl ServerSecretParams {
                     uid_enc_public_key,
                     presentation_v2.profile_key_enc_ciphertext,
                     profile_key_enc_public_key,
+                    self.personalization(),
                 )
             }
         }
     }
 
+    /// Verifies the presentation, then decrypts the profile key it carries.
+    ///
+    /// Decryption should never fail once the proof verifies, but if it does, that is reported as a
+    /// verification failure too.
+    pub fn verify_profile_key_credential_presentation_and_decrypt_key(
+        &self,
+        group_secret_params: api::groups::GroupSecretParams,
+        presentation: &api::profiles::AnyProfileKeyCredentialPresentation,
+    ) -> Result<api::profiles::ProfileKey, ZkGroupVerificationFailure> {
+        self.verify_profile_key_credential_presentation(
+            group_secret_params.get_public_params(),
+            presentation,
+        )?;
+        let uid_bytes = group_secret_params.decrypt_uuid(presentation.get_uuid_ciphertext())?;
+        group_secret_params
+            .decrypt_profile_key(presentation.get_profile_key_ciphertext(), uid_bytes)
+    }
+
+    /// Verifies the presentation, then decrypts its profile key and checks that the profile key's
+    /// version is in `allowed`.
+    ///
+    /// Every entry of `allowed` is compared in constant time.
+    pub fn verify_profile_key_credential_presentation_allowed(
+        &self,
+        group_secret_params: api::groups::GroupSecretParams,
+        presentation: &api::profiles::AnyProfileKeyCredentialPresentation,
+        allowed: &[api::profiles::ProfileKeyVersion],
+    ) -> Result<(), ZkGroupCheckFailure> {
+        let profile_key = self.verify_profile_key_credential_presentation_and_decrypt_key(
+            group_secret_params,
+            presentation,
+        )?;
+        let uid_bytes = group_secret_params.decrypt_uuid(presentation.get_uuid_ciphertext())?;
+        let version = profile_key.get_profile_key_version(uid_bytes);
+
+        let found = allowed
+            .iter()
+            .fold(Choice::from(0), |found, allowed_version| {
+                found | allowed_version.ct_eq(&version)
+            });
+        if bool::from(found) {
+            Ok(())
+        } else {
+            Err(ZkGroupCheckFailure::ProfileKeyVersionNotAllowed)
+        }
+    }
+
+    pub fn verify_profile_key_credential_presentation_hiding_pk(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::profiles::ProfileKeyCredentialHidingPresentation,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        presentation.proof.verify(
+            self.profile_key_credentials_key_pair,
+            presentation.uid_enc_ciphertext,
+            group_public_params.uid_enc_public_key,
+            self.personalization(),
+        )
+    }
+
+    /// Verifies the presentation against these params and every entry in `extra_params`,
+    /// succeeding if any of them accepts it, e.g. to accept credentials from an old key during a
+    /// rotation.
+    ///
+    /// Every set of params is always tried, so the time taken doesn't depend on which one (if any)
+    /// matched.
+    pub fn verify_profile_key_credential_presentation_multi(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::profiles::AnyProfileKeyCredentialPresentation,
+        extra_params: &[&ServerSecretParams],
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        let mut verified = self
+            .verify_profile_key_credential_presentation(group_public_params, presentation)
+            .is_ok();
+        for params in extra_params {
+            verified |= params
+                .verify_profile_key_credential_presentation(group_public_params, presentation)
+                .is_ok();
+        }
+        if verified {
+            Ok(())
+        } else {
+            Err(ZkGroupVerificationFailure)
+        }
+    }
+
     pub fn verify_profile_key_credential_presentation_v1(
         &self,
         group_public_params: api::groups::GroupPublicParams,
@@ -206,6 +1248,7 @@ impl ServerSecretParams {
             uid_enc_public_key,
             presentation.profile_key_enc_ciphertext,
             profile_key_enc_public_key,
+            self.personalization(),
         )
     }
 
@@ -213,6 +1256,20 @@ impl ServerSecretParams {
         &self,
         group_public_params: api::groups::GroupPublicParams,
         presentation: &api::profiles::ProfileKeyCredentialPresentationV2,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        self.verify_profile_key_credential_presentation_v2_with_context(
+            group_public_params,
+            presentation,
+            None,
+        )
+    }
+
+    /// See [`verify_auth_credential_presentation_v2_with_context`](Self::verify_auth_credential_presentation_v2_with_context).
+    pub fn verify_profile_key_credential_presentation_v2_with_context(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::profiles::ProfileKeyCredentialPresentationV2,
+        expected_context_label: Option<&[u8]>,
     ) -> Result<(), ZkGroupVerificationFailure> {
         let credentials_key_pair = self.profile_key_credentials_key_pair;
         let uid_enc_public_key = group_public_params.uid_enc_public_key;
@@ -224,9 +1281,54 @@ impl ServerSecretParams {
             uid_enc_public_key,
             presentation.profile_key_enc_ciphertext,
             profile_key_enc_public_key,
+            &context_message(self.personalization(), expected_context_label),
         )
     }
 
+    /// Verifies the presentation and returns the presenter's profile key version.
+    ///
+    /// Decrypting the ciphertexts requires the group's secret params.
+    pub fn verify_profile_key_credential_presentation_and_version(
+        &self,
+        group_secret_params: api::groups::GroupSecretParams,
+        presentation: &api::profiles::ProfileKeyCredentialPresentationV2,
+    ) -> Result<api::profiles::ProfileKeyVersion, ZkGroupVerificationFailure> {
+        self.verify_profile_key_credential_presentation_v2(
+            group_secret_params.get_public_params(),
+            presentation,
+        )?;
+        let uid_bytes = group_secret_params.decrypt_uuid(presentation.get_uuid_ciphertext())?;
+        let profile_key = group_secret_params
+            .decrypt_profile_key(presentation.get_profile_key_ciphertext(), uid_bytes)?;
+        Ok(profile_key.get_profile_key_version(uid_bytes))
+    }
+
+    /// Reports whether two presentations were made by the same member with the same profile key.
+    ///
+    /// This needs the group's secret params and deliberately links presentations that are
+    /// otherwise unlinkable, so it should only be used where that is acceptable.
+    pub fn presentations_same_member(
+        &self,
+        group_secret_params: api::groups::GroupSecretParams,
+        a: &api::profiles::ProfileKeyCredentialPresentationV2,
+        b: &api::profiles::ProfileKeyCredentialPresentationV2,
+    ) -> Result<bool, ZkGroupVerificationFailure> {
+        let group_public_params = group_secret_params.get_public_params();
+        self.verify_profile_key_credential_presentation_v2(group_public_params, a)?;
+        self.verify_profile_key_credential_presentation_v2(group_public_params, b)?;
+
+        let uid_a = group_secret_params.decrypt_uuid(a.get_uuid_ciphertext())?;
+        let uid_b = group_secret_params.decrypt_uuid(b.get_uuid_ciphertext())?;
+        let profile_key_a =
+            group_secret_params.decrypt_profile_key(a.get_profile_key_ciphertext(), uid_a)?;
+        let profile_key_b =
+            group_secret_params.decrypt_profile_key(b.get_profile_key_ciphertext(), uid_b)?;
+
+        let same =
+            uid_a.ct_eq(&uid_b) & profile_key_a.get_bytes().ct_eq(&profile_key_b.get_bytes());
+        Ok(bool::from(same))
+    }
+
     pub fn verify_pni_credential_presentation(
         &self,
         group_public_params: api::groups::GroupPublicParams,
@@ -244,6 +1346,7 @@ impl ServerSecretParams {
                     presentation_v1.profile_key_enc_ciphertext,
                     profile_key_enc_public_key,
                     presentation_v1.pni_enc_ciphertext,
+                    self.personalization(),
                 )
             }
 
@@ -255,6 +1358,7 @@ impl ServerSecretParams {
                     presentation_v2.profile_key_enc_ciphertext,
                     profile_key_enc_public_key,
                     presentation_v2.pni_enc_ciphertext,
+                    self.personalization(),
                 )
             }
         }
@@ -276,6 +1380,7 @@ impl ServerSecretParams {
             presentation.profile_key_enc_ciphertext,
             profile_key_enc_public_key,
             presentation.pni_enc_ciphertext,
+            self.personalization(),
         )
     }
 
@@ -283,6 +1388,20 @@ impl ServerSecretParams {
         &self,
         group_public_params: api::groups::GroupPublicParams,
         presentation: &api::profiles::PniCredentialPresentationV2,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        self.verify_pni_credential_presentation_v2_with_context(
+            group_public_params,
+            presentation,
+            None,
+        )
+    }
+
+    /// See [`verify_auth_credential_presentation_v2_with_context`](Self::verify_auth_credential_presentation_v2_with_context).
+    pub fn verify_pni_credential_presentation_v2_with_context(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::profiles::PniCredentialPresentationV2,
+        expected_context_label: Option<&[u8]>,
     ) -> Result<(), ZkGroupVerificationFailure> {
         let credentials_key_pair = self.pni_credentials_key_pair;
         let uid_enc_public_key = group_public_params.uid_enc_public_key;
@@ -295,28 +1414,87 @@ impl ServerSecretParams {
             presentation.profile_key_enc_ciphertext,
             profile_key_enc_public_key,
             presentation.pni_enc_ciphertext,
+            &context_message(self.personalization(), expected_context_label),
         )
     }
 
+    /// Verifies both presentations in `presentation`, then checks that their profile key
+    /// ciphertexts match.
+    ///
+    /// This relies on deterministic encryption rather than an equality proof, so it only accepts
+    /// credentials for the same uid. See
+    /// [`ProfileKeyEqualityPresentation`](api::profiles::ProfileKeyEqualityPresentation).
+    pub fn verify_profile_key_equality_presentation(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::profiles::ProfileKeyEqualityPresentation,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        self.verify_profile_key_credential_presentation_v2(
+            group_public_params,
+            &presentation.first,
+        )?;
+        self.verify_profile_key_credential_presentation_v2(
+            group_public_params,
+            &presentation.second,
+        )?;
+        let first = bincode::serialize(&presentation.first.profile_key_enc_ciphertext)
+            .expect("can serialize");
+        let second = bincode::serialize(&presentation.second.profile_key_enc_ciphertext)
+            .expect("can serialize");
+        if bool::from(first[..].ct_eq(&second[..])) {
+            Ok(())
+        } else {
+            Err(ZkGroupVerificationFailure)
+        }
+    }
+
     pub fn issue_profile_key_credential(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         request: &api::profiles::ProfileKeyCredentialRequest,
         uid_bytes: UidBytes,
         commitment: api::profiles::ProfileKeyCommitment,
     ) -> Result<api::profiles::ProfileKeyCredentialResponse, ZkGroupVerificationFailure> {
+        let (proof, blinded_credential) =
+            self.build_profile_key_issuance_proof(randomness, request, uid_bytes, commitment)?;
+        Ok(api::profiles::ProfileKeyCredentialResponse {
+            reserved: Default::default(),
+            blinded_credential,
+            proof,
+        })
+    }
+
+    /// The steps of [`issue_profile_key_credential`](Self::issue_profile_key_credential) before
+    /// the result is wrapped in a response, for callers that want to inspect the proof directly.
+    ///
+    /// Given the same inputs, the results are exactly the contents of the response.
+    pub fn build_profile_key_issuance_proof(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        request: &api::profiles::ProfileKeyCredentialRequest,
+        uid_bytes: UidBytes,
+        commitment: api::profiles::ProfileKeyCommitment,
+    ) -> Result<
+        (
+            crypto::proofs::ProfileKeyCredentialIssuanceProof,
+            crypto::credentials::BlindedProfileKeyCredential,
+        ),
+        ZkGroupVerificationFailure,
+    > {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20200424_Random_ServerSecretParams_IssueProfileKeyCredential",
-            &randomness,
+            randomness.expose_bytes(),
         );
 
         request.proof.verify(
             request.public_key,
             request.ciphertext,
             commitment.commitment,
+            self.personalization(),
         )?;
 
-        let uid = crypto::uid_struct::UidStruct::new(uid_bytes);
+        let uid = crypto::uid_struct::UidStruct::new(uid_bytes)?;
         let blinded_credential_with_secret_nonce = self
             .profile_key_credentials_key_pair
             .create_blinded_profile_key_credential(
@@ -332,38 +1510,56 @@ impl ServerSecretParams {
             request.ciphertext,
             blinded_credential_with_secret_nonce,
             uid,
+            self.personalization(),
             &mut sho,
         );
 
-        Ok(api::profiles::ProfileKeyCredentialResponse {
-            reserved: Default::default(),
-            blinded_credential: blinded_credential_with_secret_nonce
-                .get_blinded_profile_key_credential(),
+        Ok((
             proof,
-        })
+            blinded_credential_with_secret_nonce.get_blinded_profile_key_credential(),
+        ))
+    }
+
+    /// Checks that `credential` carries a valid MAC under this server's current key.
+    ///
+    /// This is a server-side probe on a credential the server has been handed directly; it offers
+    /// none of the privacy of a presentation.
+    pub fn check_profile_key_credential_mac(
+        &self,
+        credential: &api::profiles::ProfileKeyCredential,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        let uid = crypto::uid_struct::UidStruct::new(credential.uid_bytes)?;
+        let profile_key = crypto::profile_key_struct::ProfileKeyStruct::new(
+            credential.profile_key_bytes,
+            credential.uid_bytes,
+        );
+        self.profile_key_credentials_key_pair
+            .verify_profile_key_credential_mac(credential.credential, uid, profile_key)
     }
 
     pub fn issue_pni_credential(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         request: &api::profiles::ProfileKeyCredentialRequest,
         uid_bytes: UidBytes,
         pni_bytes: UidBytes,
         commitment: api::profiles::ProfileKeyCommitment,
     ) -> Result<api::profiles::PniCredentialResponse, ZkGroupVerificationFailure> {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20211111_Random_ServerSecretParams_IssuePniCredential",
-            &randomness,
+            randomness.expose_bytes(),
         );
 
         request.proof.verify(
             request.public_key,
             request.ciphertext,
             commitment.commitment,
+            self.personalization(),
         )?;
 
-        let uid = crypto::uid_struct::UidStruct::new(uid_bytes);
-        let pni = crypto::uid_struct::UidStruct::new(pni_bytes);
+        let uid = crypto::uid_struct::UidStruct::new(uid_bytes)?;
+        let pni = crypto::uid_struct::UidStruct::new(pni_bytes)?;
         let blinded_credential_with_secret_nonce =
             self.pni_credentials_key_pair.create_blinded_pni_credential(
                 uid,
@@ -380,6 +1576,7 @@ impl ServerSecretParams {
             blinded_credential_with_secret_nonce,
             uid,
             pni,
+            self.personalization(),
             &mut sho,
         );
 
@@ -392,14 +1589,15 @@ impl ServerSecretParams {
 
     pub fn issue_receipt_credential(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         request: &api::receipts::ReceiptCredentialRequest,
         receipt_expiration_time: ReceiptExpirationTime,
         receipt_level: ReceiptLevel,
     ) -> api::receipts::ReceiptCredentialResponse {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20210919_Random_ServerSecretParams_IssueReceiptCredential",
-            &randomness,
+            randomness.expose_bytes(),
         );
 
         let blinded_credential_with_secret_nonce = self
@@ -419,6 +1617,7 @@ impl ServerSecretParams {
             blinded_credential_with_secret_nonce,
             receipt_expiration_time,
             receipt_level,
+            self.personalization(),
             &mut sho,
         );
 
@@ -432,18 +1631,383 @@ impl ServerSecretParams {
         }
     }
 
-    pub fn verify_receipt_credential_presentation(
+    pub fn verify_receipt_credential_presentation(
+        &self,
+        presentation: &api::receipts::ReceiptCredentialPresentation,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        presentation.proof.verify(
+            self.receipt_credentials_key_pair,
+            presentation.get_receipt_struct(),
+            self.personalization(),
+        )
+    }
+
+    /// Verifies a receipt presentation and records its serial in `spent`, as a single redemption
+    /// step.
+    ///
+    /// `now` is in seconds since the epoch; the receipt is expired once `now` reaches its
+    /// expiration time. `spent` is only modified if every check passes, so a failed redemption can
+    /// be retried.
+    pub fn verify_and_redeem(
+        &self,
+        presentation: &api::receipts::ReceiptCredentialPresentation,
+        now: u64,
+        spent: &mut api::receipts::SpentSerialSet,
+    ) -> Result<crypto::receipt_struct::ReceiptStruct, ZkGroupCheckFailure> {
+        self.verify_receipt_credential_presentation(presentation)?;
+        if now >= presentation.get_receipt_expiration_time() {
+            return Err(ZkGroupCheckFailure::Expired);
+        }
+        if !spent.insert(presentation.get_receipt_serial_bytes()) {
+            return Err(ZkGroupCheckFailure::AlreadySpent);
+        }
+        Ok(presentation.get_receipt_struct())
+    }
+
+    /// Verifies any presentation type, dispatching to the matching `verify_*` method.
+    pub fn verify_dyn(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &dyn api::VerifiablePresentation,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        presentation.verify_with(self, group_public_params)
+    }
+
+    /// Verifies any presentation type and, only on success, returns its ciphertexts.
+    pub fn verify_and_decode(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &dyn api::VerifiablePresentation,
+    ) -> Result<api::DecodedPresentation, ZkGroupVerificationFailure> {
+        presentation.verify_with(self, group_public_params)?;
+        Ok(presentation.decode())
+    }
+
+    /// Checks offline that a captured profile key credential session is consistent: `response`
+    /// yields a valid credential for `context`, `presentation` verifies, and the presentation
+    /// encrypts the same UUID and profile key that credential was issued for.
+    ///
+    /// Presentations are unlinkable, so this can't tell two credentials for the same UUID and
+    /// profile key apart; it does catch a presentation made from some other user's credential.
+    pub fn verify_full_flow(
+        &self,
+        context: &api::profiles::ProfileKeyCredentialRequestContext,
+        response: &api::profiles::ProfileKeyCredentialResponse,
+        group_secret_params: api::groups::GroupSecretParams,
+        presentation: &api::profiles::AnyProfileKeyCredentialPresentation,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        let credential = self
+            .get_public_params()
+            .receive_profile_key_credential(context, response)?;
+        self.verify_profile_key_credential_presentation(
+            group_secret_params.get_public_params(),
+            presentation,
+        )?;
+
+        let uid_bytes = group_secret_params.decrypt_uuid(presentation.get_uuid_ciphertext())?;
+        let profile_key = group_secret_params
+            .decrypt_profile_key(presentation.get_profile_key_ciphertext(), uid_bytes)?;
+        if uid_bytes != credential.uid_bytes || profile_key.bytes != credential.profile_key_bytes {
+            return Err(ZkGroupVerificationFailure);
+        }
+        Ok(())
+    }
+}
+
+impl AuthOnlyServerParams {
+    fn personalization(&self) -> &[u8] {
+        personalization_message(&self.personalization)
+    }
+
+    pub fn verify_auth_credential_presentation(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        match presentation {
+            api::auth::AnyAuthCredentialPresentation::V1(presentation_v1) => {
+                presentation_v1.proof.verify(
+                    self.auth_credentials_key_pair,
+                    group_public_params.uid_enc_public_key,
+                    presentation_v1.ciphertext,
+                    presentation_v1.redemption_time,
+                    0,
+                    self.personalization(),
+                )
+            }
+
+            api::auth::AnyAuthCredentialPresentation::V2(presentation_v2) => {
+                presentation_v2.proof.verify(
+                    self.auth_credentials_key_pair,
+                    group_public_params.uid_enc_public_key,
+                    presentation_v2.ciphertext,
+                    presentation_v2.redemption_time,
+                    0,
+                    self.personalization(),
+                )
+            }
+        }
+    }
+
+    /// Verifies the presentation and checks that its redemption time is within
+    /// `start_day..=end_day`.
+    ///
+    /// The redemption time is covered by the proof, so it is only trusted once the proof verifies;
+    /// a presentation that fails both checks reports a verification failure.
+    pub fn verify_auth_credential_presentation_in_range(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+        start_day: RedemptionTime,
+        end_day: RedemptionTime,
+    ) -> Result<(), ZkGroupCheckFailure> {
+        self.verify_auth_credential_presentation(group_public_params, presentation)?;
+        if !(start_day..=end_day).contains(&presentation.get_redemption_time()) {
+            return Err(ZkGroupCheckFailure::RedemptionTimeOutOfRange);
+        }
+        Ok(())
+    }
+
+    /// Verifies the presentation and rejects a redemption time more than
+    /// [`REDEMPTION_TIME_FUTURE_SKEW_DAYS`] after `now_day`.
+    ///
+    /// This catches clients whose clocks run ahead, which a window check alone can miss.
+    ///
+    /// `now_day` is usually [`Clock::now_day`](crate::Clock::now_day).
+    pub fn verify_auth_credential_presentation_not_future(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+        now_day: RedemptionTime,
+    ) -> Result<(), ZkGroupCheckFailure> {
+        self.verify_auth_credential_presentation_in_range(
+            group_public_params,
+            presentation,
+            0,
+            now_day.saturating_add(REDEMPTION_TIME_FUTURE_SKEW_DAYS),
+        )
+    }
+
+    /// Verifies the presentation and returns how many seconds remain until its credential stops
+    /// being accepted, measured from the start of `now_day`.
+    ///
+    /// A presentation is accepted from [`REDEMPTION_TIME_FUTURE_SKEW_DAYS`] before its redemption
+    /// time through [`REDEMPTION_TIME_WINDOW_DAYS`] after it. On the last accepted day the result
+    /// is zero rather than an error.
+    pub fn verify_auth_credential_presentation_with_ttl(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+        now_day: RedemptionTime,
+    ) -> Result<u64, ZkGroupCheckFailure> {
+        self.verify_auth_credential_presentation_in_range(
+            group_public_params,
+            presentation,
+            now_day.saturating_sub(REDEMPTION_TIME_WINDOW_DAYS),
+            now_day.saturating_add(REDEMPTION_TIME_FUTURE_SKEW_DAYS),
+        )?;
+        let last_day =
+            u64::from(presentation.get_redemption_time()) + u64::from(REDEMPTION_TIME_WINDOW_DAYS);
+        Ok((last_day - u64::from(now_day)) * SECONDS_PER_DAY)
+    }
+
+    /// Verifies the presentation and checks that its redemption time has not gone backwards for
+    /// this member, returning the day to store as their new `last_seen`.
+    ///
+    /// `last_seen` is whatever the previous call returned for the same member, keyed by the
+    /// pseudonym from
+    /// [`verify_auth_credential_presentation_with_pseudonym`](Self::verify_auth_credential_presentation_with_pseudonym),
+    /// or `None` for a member not seen before. A redemption time more than
+    /// [`REDEMPTION_TIME_MONOTONIC_GRACE_DAYS`] behind `last_seen` is rejected as out of range.
+    /// The returned day never decreases.
+    pub fn verify_auth_credential_presentation_monotonic(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+        last_seen: Option<RedemptionTime>,
+    ) -> Result<RedemptionTime, ZkGroupCheckFailure> {
+        self.verify_auth_credential_presentation(group_public_params, presentation)?;
+        let day = presentation.get_redemption_time();
+        match last_seen {
+            None => Ok(day),
+            Some(last_seen) => {
+                if day.saturating_add(REDEMPTION_TIME_MONOTONIC_GRACE_DAYS) < last_seen {
+                    return Err(ZkGroupCheckFailure::RedemptionTimeOutOfRange);
+                }
+                Ok(day.max(last_seen))
+            }
+        }
+    }
+
+    /// Verifies the presentation and checks that its uid ciphertext is not any of `members`.
+    ///
+    /// No separate inequality proof is needed: uid encryption is deterministic under a group's
+    /// key, so two ciphertexts under the same group public params are equal exactly when their
+    /// uids are. The presentation's proof already binds its ciphertext to the credential's uid,
+    /// so a ciphertext differing from every member's proves the presenter is not a member, without
+    /// revealing anything beyond the ciphertext the presentation already carries.
+    pub fn verify_auth_credential_non_membership(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+        members: &[api::groups::UuidCiphertext],
+    ) -> Result<(), ZkGroupCheckFailure> {
+        self.verify_auth_credential_presentation(group_public_params, presentation)?;
+        let uuid_ciphertext = presentation.get_uuid_ciphertext();
+        if members.contains(&uuid_ciphertext) {
+            return Err(ZkGroupCheckFailure::Member);
+        }
+        Ok(())
+    }
+
+    /// Rejects presentations older than `min` before verifying them, so that a client's V2
+    /// presentation cannot be downgraded to V1 while V1 is still accepted.
+    ///
+    /// Only the version of `min` is considered, not its credential family.
+    pub fn verify_auth_credential_presentation_min_version(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+        min: api::PresentationVersion,
+    ) -> Result<(), ZkGroupCheckFailure> {
+        if presentation.get_version().version_byte() < min.version_byte() {
+            return Err(ZkGroupCheckFailure::VersionTooLow);
+        }
+        self.verify_auth_credential_presentation(group_public_params, presentation)?;
+        Ok(())
+    }
+
+    /// Verifies the presentation and returns the member's rate-limit pseudonym for this group.
+    ///
+    /// See [`AnyAuthCredentialPresentation::derive_rate_limit_token`](api::auth::AnyAuthCredentialPresentation::derive_rate_limit_token).
+    pub fn verify_auth_credential_presentation_with_pseudonym(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+    ) -> Result<[u8; 32], ZkGroupVerificationFailure> {
+        self.verify_auth_credential_presentation(group_public_params, presentation)?;
+        Ok(presentation.derive_rate_limit_token(group_public_params))
+    }
+
+    /// Verifies the presentation and returns its serialization, for an audit log.
+    ///
+    /// The bytes are the standard serialization, which is already minimal: the leading byte is
+    /// the version tag that [`AnyAuthCredentialPresentation::new`] dispatches on, not padding, so
+    /// it is kept. They parse back to the same presentation, and re-serialize to the same bytes.
+    ///
+    /// [`AnyAuthCredentialPresentation::new`]: api::auth::AnyAuthCredentialPresentation::new
+    pub fn verify_auth_credential_presentation_canonical(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+    ) -> Result<Vec<u8>, ZkGroupVerificationFailure> {
+        self.verify_auth_credential_presentation(group_public_params, presentation)?;
+        Ok(bincode::serialize(presentation).expect("can serialize"))
+    }
+
+    /// Verifies a shared presentation and checks that its pseudonym belongs to it.
+    pub fn verify_redacted_presentation(
+        &self,
+        group_public_params: api::groups::GroupPublicParams,
+        redacted: &api::auth::RedactedPresentation,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        let presentation = redacted
+            .get_presentation()
+            .map_err(|_| ZkGroupVerificationFailure)?;
+        let pseudonym = self.verify_auth_credential_presentation_with_pseudonym(
+            group_public_params,
+            &presentation,
+        )?;
+        if !bool::from(pseudonym.ct_eq(&redacted.pseudonym)) {
+            return Err(ZkGroupVerificationFailure);
+        }
+        Ok(())
+    }
+
+    /// Returns the index of the first group in `groups` the presentation verifies against.
+    pub fn verify_auth_credential_presentation_any_group(
+        &self,
+        groups: &[api::groups::GroupPublicParams],
+        presentation: &api::auth::AnyAuthCredentialPresentation,
+    ) -> Result<usize, ZkGroupVerificationFailure> {
+        groups
+            .iter()
+            .position(|group_public_params| {
+                self.verify_auth_credential_presentation(*group_public_params, presentation)
+                    .is_ok()
+            })
+            .ok_or(ZkGroupVerificationFailure)
+    }
+
+    /// Verifies a stream of presentations without buffering them, succeeding only if all of them
+    /// verify.
+    ///
+    /// Each item is checked and dropped before the next is pulled, so memory use doesn't grow with
+    /// the number of presentations. Verification stops at the first bad item, and the error doesn't
+    /// say which one it was; callers that need per-item results should verify items individually.
+    pub fn verify_auth_credential_presentations<I, P>(
+        &self,
+        presentations: I,
+    ) -> Result<(), ZkGroupVerificationFailure>
+    where
+        I: IntoIterator<Item = (api::groups::GroupPublicParams, P)>,
+        P: Borrow<api::auth::AnyAuthCredentialPresentation>,
+    {
+        presentations
+            .into_iter()
+            .try_for_each(|(group_public_params, presentation)| {
+                self.verify_auth_credential_presentation(group_public_params, presentation.borrow())
+            })
+    }
+
+    pub fn verify_bare_auth_presentation(
+        &self,
+        presentation: &api::auth::AuthCredentialBarePresentation,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        presentation.proof.verify(
+            self.auth_credentials_key_pair,
+            presentation.redemption_time,
+            self.personalization(),
+        )
+    }
+
+    pub fn verify_signature(
         &self,
-        presentation: &api::receipts::ReceiptCredentialPresentation,
+        message: &[u8],
+        signature: NotarySignatureBytes,
     ) -> Result<(), ZkGroupVerificationFailure> {
-        presentation.proof.verify(
-            self.receipt_credentials_key_pair,
-            presentation.get_receipt_struct(),
-        )
+        self.sig_public_key.verify(message, signature)
     }
 }
 
 impl ServerPublicParams {
+    /// Applies the deployment salt the server params were generated with.
+    ///
+    /// Public params from [`ServerSecretParams::get_public_params`] already carry it, serialized or
+    /// not. See [`ServerSecretParams::generate_with_personalization`].
+    pub fn with_personalization(self, salt: &[u8]) -> Self {
+        Self {
+            personalization: derive_personalization(salt),
+            ..self
+        }
+    }
+
+    /// Returns a copy of these params that uses `key` for receipt credentials, keeping every other
+    /// key.
+    ///
+    /// See [`ServerSecretParams::with_receipt_key_pair`]. Keeping each receipt key within the
+    /// environment it was made for is the caller's responsibility.
+    pub fn with_receipt_key(&self, key: crypto::credentials::PublicKey) -> Self {
+        Self {
+            receipt_credentials_public_key: key,
+            ..*self
+        }
+    }
+
+    fn personalization(&self) -> &[u8] {
+        personalization_message(&self.personalization)
+    }
+
     pub fn verify_signature(
         &self,
         message: &[u8],
@@ -452,18 +2016,35 @@ impl ServerPublicParams {
         self.sig_public_key.verify(message, signature)
     }
 
+    /// See [`ServerSecretParams::signing_public_key_bytes`].
+    pub fn signing_public_key_bytes(&self) -> [u8; 32] {
+        self.sig_public_key.to_bytes()
+    }
+
+    pub fn verify_issuance_summary(
+        &self,
+        summary: &api::IssuanceSummary,
+        signature: NotarySignatureBytes,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        self.sig_public_key
+            .derive_domain_key(ISSUANCE_SUMMARY_SIGNING_DOMAIN)
+            .verify(&summary.signed_message(), signature)
+    }
+
     pub fn receive_auth_credential(
         &self,
         uid_bytes: UidBytes,
         redemption_time: RedemptionTime,
         response: &api::auth::AuthCredentialResponse,
     ) -> Result<api::auth::AuthCredential, ZkGroupVerificationFailure> {
-        let uid = crypto::uid_struct::UidStruct::new(uid_bytes);
+        let uid = crypto::uid_struct::UidStruct::new(uid_bytes)?;
         response.proof.verify(
             self.auth_credentials_public_key,
             response.credential,
             uid,
             redemption_time,
+            0,
+            self.personalization(),
         )?;
 
         Ok(api::auth::AuthCredential {
@@ -474,12 +2055,77 @@ impl ServerPublicParams {
         })
     }
 
+    /// Checks a response from [`ServerSecretParams::issue_auth_credential_batch`], returning the
+    /// credentials in the same order as `redemption_times`.
+    pub fn receive_auth_credential_batch(
+        &self,
+        uid_bytes: UidBytes,
+        redemption_times: &[RedemptionTime],
+        response: &api::auth::BatchAuthCredentialResponse,
+    ) -> Result<Vec<api::auth::AuthCredential>, ZkGroupVerificationFailure> {
+        if response.credentials.len() != redemption_times.len()
+            || redemption_times.is_empty()
+            || redemption_times.len() > MAX_AUTH_CREDENTIAL_BATCH_SIZE
+        {
+            return Err(ZkGroupVerificationFailure);
+        }
+        let uid = crypto::uid_struct::UidStruct::new(uid_bytes)?;
+        response.proof.verify(
+            self.auth_credentials_public_key,
+            &response.credentials,
+            uid,
+            redemption_times,
+            self.personalization(),
+        )?;
+
+        Ok(response
+            .credentials
+            .iter()
+            .zip(redemption_times)
+            .map(
+                |(&credential, &redemption_time)| api::auth::AuthCredential {
+                    reserved: Default::default(),
+                    credential,
+                    uid,
+                    redemption_time,
+                },
+            )
+            .collect())
+    }
+
+    pub fn receive_auth_credential_with_epoch(
+        &self,
+        uid_bytes: UidBytes,
+        redemption_time: RedemptionTime,
+        epoch: AuthCredentialEpoch,
+        response: &api::auth::AuthCredentialResponse,
+    ) -> Result<api::auth::AuthCredentialWithEpoch, ZkGroupVerificationFailure> {
+        let uid = crypto::uid_struct::UidStruct::new(uid_bytes)?;
+        response.proof.verify(
+            self.auth_credentials_public_key,
+            response.credential,
+            uid,
+            redemption_time,
+            epoch,
+            self.personalization(),
+        )?;
+
+        Ok(api::auth::AuthCredentialWithEpoch {
+            reserved: Default::default(),
+            credential: response.credential,
+            uid,
+            redemption_time,
+            epoch,
+        })
+    }
+
     pub fn create_auth_credential_presentation(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         group_secret_params: api::groups::GroupSecretParams,
         auth_credential: api::auth::AuthCredential,
     ) -> api::auth::AnyAuthCredentialPresentation {
+        let randomness = Randomness::new(randomness.into());
         let presentation_v2 = self.create_auth_credential_presentation_v2(
             randomness,
             group_secret_params,
@@ -490,13 +2136,14 @@ impl ServerPublicParams {
 
     pub fn create_auth_credential_presentation_v1(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         group_secret_params: api::groups::GroupSecretParams,
         auth_credential: api::auth::AuthCredential,
     ) -> api::auth::AuthCredentialPresentationV1 {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20200424_Random_ServerPublicParams_CreateAuthCredentialPresentation",
-            &randomness,
+            randomness.expose_bytes(),
         );
 
         let uuid_ciphertext = group_secret_params.encrypt_uid_struct(auth_credential.uid);
@@ -508,28 +2155,72 @@ impl ServerPublicParams {
             auth_credential.uid,
             uuid_ciphertext.ciphertext,
             auth_credential.redemption_time,
+            0,
+            self.personalization(),
             &mut sho,
         );
 
-        api::auth::AuthCredentialPresentationV1 {
-            reserved: [PRESENTATION_VERSION_1],
-            proof,
-            ciphertext: uuid_ciphertext.ciphertext,
-            redemption_time: auth_credential.redemption_time,
-        }
+        checked_presentation(
+            &randomness,
+            api::auth::AuthCredentialPresentationV1 {
+                reserved: [PRESENTATION_VERSION_1],
+                proof,
+                ciphertext: uuid_ciphertext.ciphertext,
+                redemption_time: auth_credential.redemption_time,
+            },
+        )
     }
 
     pub fn create_auth_credential_presentation_v2(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         group_secret_params: api::groups::GroupSecretParams,
         auth_credential: api::auth::AuthCredential,
     ) -> api::auth::AuthCredentialPresentationV2 {
+        self.create_auth_credential_presentation_v2_with_context(
+            randomness,
+            group_secret_params,
+            auth_credential,
+            None,
+        )
+    }
+
+    /// Like [`create_auth_credential_presentation_v2`](Self::create_auth_credential_presentation_v2),
+    /// but binds the proof to `context_label` (such as the request path), so it only verifies
+    /// under that same label.
+    ///
+    /// With no label, this produces the same presentation as the unlabeled method.
+    pub fn create_auth_credential_presentation_v2_with_context(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        group_secret_params: api::groups::GroupSecretParams,
+        auth_credential: api::auth::AuthCredential,
+        context_label: Option<&[u8]>,
+    ) -> api::auth::AuthCredentialPresentationV2 {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20220120_Random_ServerPublicParams_CreateAuthCredentialPresentationV2",
-            &randomness,
+            randomness.expose_bytes(),
         );
+        checked_presentation(
+            &randomness,
+            self.create_bound_auth_credential_presentation_v2(
+                &mut sho,
+                group_secret_params,
+                auth_credential,
+                &context_message(self.personalization(), context_label),
+            ),
+        )
+    }
 
+    /// Creates a V2 presentation whose proof is over `message`, which the verifier must rebuild.
+    fn create_bound_auth_credential_presentation_v2(
+        &self,
+        sho: &mut Sho,
+        group_secret_params: api::groups::GroupSecretParams,
+        auth_credential: api::auth::AuthCredential,
+        message: &[u8],
+    ) -> api::auth::AuthCredentialPresentationV2 {
         let uuid_ciphertext = group_secret_params.encrypt_uid_struct(auth_credential.uid);
 
         let proof = crypto::proofs::AuthCredentialPresentationProofV2::new(
@@ -539,7 +2230,9 @@ impl ServerPublicParams {
             auth_credential.uid,
             uuid_ciphertext.ciphertext,
             auth_credential.redemption_time,
-            &mut sho,
+            0,
+            message,
+            sho,
         );
 
         api::auth::AuthCredentialPresentationV2 {
@@ -550,15 +2243,191 @@ impl ServerPublicParams {
         }
     }
 
+    /// Creates a presentation of `auth_credential` that is not tied to any group.
+    ///
+    /// See [`api::auth::AuthCredentialBarePresentation`].
+    pub fn create_bare_auth_presentation(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        auth_credential: api::auth::AuthCredential,
+    ) -> api::auth::AuthCredentialBarePresentation {
+        let randomness = Randomness::new(randomness.into());
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_Random_ServerPublicParams_CreateBareAuthPresentation",
+            randomness.expose_bytes(),
+        );
+
+        let proof = crypto::proofs::AuthCredentialBarePresentationProof::new(
+            self.auth_credentials_public_key,
+            auth_credential.credential,
+            auth_credential.uid,
+            auth_credential.redemption_time,
+            self.personalization(),
+            &mut sho,
+        );
+
+        checked_presentation(
+            &randomness,
+            api::auth::AuthCredentialBarePresentation {
+                reserved: Default::default(),
+                proof,
+                redemption_time: auth_credential.redemption_time,
+            },
+        )
+    }
+
+    /// Like [`create_auth_credential_presentation_v2`](Self::create_auth_credential_presentation_v2),
+    /// but binds the proof to `server_nonce` so it only verifies against that nonce.
+    pub fn create_auth_credential_presentation_v2_with_nonce(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        group_secret_params: api::groups::GroupSecretParams,
+        auth_credential: api::auth::AuthCredential,
+        server_nonce: &[u8],
+    ) -> api::auth::AuthCredentialPresentationV2 {
+        let randomness = Randomness::new(randomness.into());
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_Random_ServerPublicParams_CreateAuthCredentialPresentationV2WithNonce",
+            randomness.expose_bytes(),
+        );
+        checked_presentation(
+            &randomness,
+            self.create_bound_auth_credential_presentation_v2(
+                &mut sho,
+                group_secret_params,
+                auth_credential,
+                &bound_message(self.personalization(), NONCE_LABEL, server_nonce),
+            ),
+        )
+    }
+
+    /// Like [`create_auth_credential_presentation_v2`](Self::create_auth_credential_presentation_v2),
+    /// but binds the proof to `client_time`, so a captured presentation is only accepted while
+    /// the server's clock is within its window of that time.
+    pub fn create_auth_credential_presentation_v2_timestamped(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        group_secret_params: api::groups::GroupSecretParams,
+        auth_credential: api::auth::AuthCredential,
+        client_time: PresentationTimestamp,
+    ) -> api::auth::AuthCredentialTimestampedPresentation {
+        let randomness = Randomness::new(randomness.into());
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_Random_ServerPublicParams_CreateAuthCredentialPresentationV2Timestamped",
+            randomness.expose_bytes(),
+        );
+        let presentation = self.create_bound_auth_credential_presentation_v2(
+            &mut sho,
+            group_secret_params,
+            auth_credential,
+            &bound_message(
+                self.personalization(),
+                CLIENT_TIME_LABEL,
+                &client_time.to_be_bytes(),
+            ),
+        );
+
+        checked_presentation(
+            &randomness,
+            api::auth::AuthCredentialTimestampedPresentation {
+                reserved: Default::default(),
+                proof: presentation.proof,
+                ciphertext: presentation.ciphertext,
+                redemption_time: presentation.redemption_time,
+                client_time,
+            },
+        )
+    }
+
+    /// Like [`create_auth_credential_presentation_v2`](Self::create_auth_credential_presentation_v2),
+    /// but also binds the presentation to a commitment to `device_tag`.
+    ///
+    /// The tag should be stable for the device and unique among the member's devices. The server
+    /// only sees a commitment, keyed by the group, so the tag itself may be any identifier.
+    pub fn create_auth_credential_presentation_v2_with_device_tag(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        group_secret_params: api::groups::GroupSecretParams,
+        auth_credential: api::auth::AuthCredential,
+        device_tag: &[u8],
+    ) -> api::auth::AuthCredentialDeviceTaggedPresentation {
+        let randomness = Randomness::new(randomness.into());
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_Random_ServerPublicParams_CreateAuthCredentialPresentationV2WithDeviceTag",
+            randomness.expose_bytes(),
+        );
+        let device_tag_commitment = group_secret_params.device_tag_commitment(device_tag);
+        let presentation = self.create_bound_auth_credential_presentation_v2(
+            &mut sho,
+            group_secret_params,
+            auth_credential,
+            &bound_message(
+                self.personalization(),
+                DEVICE_TAG_LABEL,
+                &device_tag_commitment,
+            ),
+        );
+
+        checked_presentation(
+            &randomness,
+            api::auth::AuthCredentialDeviceTaggedPresentation {
+                reserved: Default::default(),
+                proof: presentation.proof,
+                ciphertext: presentation.ciphertext,
+                redemption_time: presentation.redemption_time,
+                device_tag_commitment,
+            },
+        )
+    }
+
+    pub fn create_auth_credential_with_epoch_presentation(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        group_secret_params: api::groups::GroupSecretParams,
+        auth_credential: api::auth::AuthCredentialWithEpoch,
+    ) -> api::auth::AuthCredentialWithEpochPresentation {
+        let randomness = Randomness::new(randomness.into());
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_Random_ServerPublicParams_CreateAuthCredentialWithEpochPresentation",
+            randomness.expose_bytes(),
+        );
+
+        let uuid_ciphertext = group_secret_params.encrypt_uid_struct(auth_credential.uid);
+
+        let proof = crypto::proofs::AuthCredentialPresentationProofV2::new(
+            self.auth_credentials_public_key,
+            group_secret_params.uid_enc_key_pair,
+            auth_credential.credential,
+            auth_credential.uid,
+            uuid_ciphertext.ciphertext,
+            auth_credential.redemption_time,
+            auth_credential.epoch,
+            self.personalization(),
+            &mut sho,
+        );
+
+        checked_presentation(
+            &randomness,
+            api::auth::AuthCredentialWithEpochPresentation {
+                reserved: Default::default(),
+                proof,
+                ciphertext: uuid_ciphertext.ciphertext,
+                redemption_time: auth_credential.redemption_time,
+                epoch: auth_credential.epoch,
+            },
+        )
+    }
+
     pub fn create_profile_key_credential_request_context(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         uid_bytes: UidBytes,
         profile_key: api::profiles::ProfileKey,
     ) -> api::profiles::ProfileKeyCredentialRequestContext {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20200424_Random_ServerPublicParams_CreateProfileKeyCredentialRequestContext",
-            &randomness,
+            randomness.expose_bytes(),
         );
         let profile_key_struct =
             crypto::profile_key_struct::ProfileKeyStruct::new(profile_key.bytes, uid_bytes);
@@ -576,6 +2445,7 @@ impl ServerPublicParams {
             key_pair,
             ciphertext_with_secret_nonce,
             commitment_with_secret_nonce,
+            self.personalization(),
             &mut sho,
         );
 
@@ -589,13 +2459,22 @@ impl ServerPublicParams {
         }
     }
 
+    /// See [`ProfileKeyCredentialRequestContext::validate`](api::profiles::ProfileKeyCredentialRequestContext::validate).
+    pub fn validate_profile_key_credential_request_context(
+        &self,
+        context: &api::profiles::ProfileKeyCredentialRequestContext,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        context.validate_with_personalization(self.personalization())
+    }
+
     pub fn create_pni_credential_request_context(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         aci_bytes: UidBytes,
         pni_bytes: UidBytes,
         profile_key: api::profiles::ProfileKey,
     ) -> api::profiles::PniCredentialRequestContext {
+        let randomness = Randomness::new(randomness.into());
         // We want to provide an encryption of the profile key and prove that it matches the
         // ProfileKeyCommitment in *exactly* the same way as a non-PNI request, so just invoke that
         // and then add the PNI to the result.
@@ -623,6 +2502,7 @@ impl ServerPublicParams {
             context.uid_bytes,
             context.ciphertext_with_secret_nonce.get_ciphertext(),
             response.blinded_credential,
+            self.personalization(),
         )?;
 
         let credential = context
@@ -637,6 +2517,40 @@ impl ServerPublicParams {
         })
     }
 
+    /// Like [`receive_profile_key_credential`](Self::receive_profile_key_credential), but also
+    /// requires the blinded credential in `response` to equal `expected`, compared in constant
+    /// time.
+    pub fn receive_profile_key_credential_checked(
+        &self,
+        context: &api::profiles::ProfileKeyCredentialRequestContext,
+        response: &api::profiles::ProfileKeyCredentialResponse,
+        expected: &crypto::credentials::BlindedProfileKeyCredential,
+    ) -> Result<api::profiles::ProfileKeyCredential, ZkGroupVerificationFailure> {
+        if !bool::from(response.blinded_credential.ct_eq(expected)) {
+            return Err(ZkGroupVerificationFailure);
+        }
+        self.receive_profile_key_credential(context, response)
+    }
+
+    /// Receives many profile key credentials at once, yielding one result per `(context,
+    /// response)` pair so that a bad response only fails its own entry.
+    ///
+    /// poksho proofs are stored as (challenge, response), so each proof's commitment has to be
+    /// reconstructed and hashed on its own; there's no random-linear-combination shortcut to take.
+    /// The responses are therefore verified individually.
+    pub fn receive_profile_key_credentials_batch(
+        &self,
+        items: &[(
+            &api::profiles::ProfileKeyCredentialRequestContext,
+            &api::profiles::ProfileKeyCredentialResponse,
+        )],
+    ) -> Vec<Result<api::profiles::ProfileKeyCredential, ZkGroupVerificationFailure>> {
+        items
+            .iter()
+            .map(|(context, response)| self.receive_profile_key_credential(context, response))
+            .collect()
+    }
+
     pub fn receive_pni_credential(
         &self,
         context: &api::profiles::PniCredentialRequestContext,
@@ -649,6 +2563,7 @@ impl ServerPublicParams {
             context.pni_bytes,
             context.ciphertext_with_secret_nonce.get_ciphertext(),
             response.blinded_credential,
+            self.personalization(),
         )?;
 
         let credential = context
@@ -666,10 +2581,11 @@ impl ServerPublicParams {
 
     pub fn create_profile_key_credential_presentation(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         group_secret_params: api::groups::GroupSecretParams,
         profile_key_credential: api::profiles::ProfileKeyCredential,
     ) -> api::profiles::AnyProfileKeyCredentialPresentation {
+        let randomness = Randomness::new(randomness.into());
         let presentation_v2 = self.create_profile_key_credential_presentation_v2(
             randomness,
             group_secret_params,
@@ -678,15 +2594,55 @@ impl ServerPublicParams {
         api::profiles::AnyProfileKeyCredentialPresentation::V2(presentation_v2)
     }
 
+    /// Proves that the holder of `profile_key_credential` is a member of the group, without
+    /// including any encryption of their profile key.
+    ///
+    /// See [`api::profiles::ProfileKeyCredentialHidingPresentation`].
+    pub fn create_profile_key_credential_presentation_hiding_pk(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        group_secret_params: api::groups::GroupSecretParams,
+        profile_key_credential: api::profiles::ProfileKeyCredential,
+    ) -> api::profiles::ProfileKeyCredentialHidingPresentation {
+        let randomness = Randomness::new(randomness.into());
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_Random_ServerPublicParams_CreateProfileKeyCredentialHidingPresentation",
+            randomness.expose_bytes(),
+        );
+
+        let uuid_ciphertext = group_secret_params.encrypt_uuid(profile_key_credential.uid_bytes);
+
+        let proof = crypto::proofs::ProfileKeyCredentialHidingPresentationProof::new(
+            group_secret_params.uid_enc_key_pair,
+            self.profile_key_credentials_public_key,
+            profile_key_credential.credential,
+            uuid_ciphertext.ciphertext,
+            profile_key_credential.uid_bytes,
+            profile_key_credential.profile_key_bytes,
+            self.personalization(),
+            &mut sho,
+        );
+
+        checked_presentation(
+            &randomness,
+            api::profiles::ProfileKeyCredentialHidingPresentation {
+                reserved: Default::default(),
+                proof,
+                uid_enc_ciphertext: uuid_ciphertext.ciphertext,
+            },
+        )
+    }
+
     pub fn create_profile_key_credential_presentation_v1(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         group_secret_params: api::groups::GroupSecretParams,
         profile_key_credential: api::profiles::ProfileKeyCredential,
     ) -> api::profiles::ProfileKeyCredentialPresentationV1 {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20200424_Random_ServerPublicParams_CreateProfileKeyCredentialPresentation",
-            &randomness,
+            randomness.expose_bytes(),
         );
 
         let uid_enc_key_pair = group_secret_params.uid_enc_key_pair;
@@ -708,26 +2664,47 @@ impl ServerPublicParams {
             profile_key_ciphertext.ciphertext,
             profile_key_credential.uid_bytes,
             profile_key_credential.profile_key_bytes,
+            self.personalization(),
             &mut sho,
         );
 
-        api::profiles::ProfileKeyCredentialPresentationV1 {
-            reserved: [PRESENTATION_VERSION_1],
-            proof,
-            uid_enc_ciphertext: uuid_ciphertext.ciphertext,
-            profile_key_enc_ciphertext: profile_key_ciphertext.ciphertext,
-        }
+        checked_presentation(
+            &randomness,
+            api::profiles::ProfileKeyCredentialPresentationV1 {
+                reserved: [PRESENTATION_VERSION_1],
+                proof,
+                uid_enc_ciphertext: uuid_ciphertext.ciphertext,
+                profile_key_enc_ciphertext: profile_key_ciphertext.ciphertext,
+            },
+        )
     }
 
     pub fn create_profile_key_credential_presentation_v2(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
+        group_secret_params: api::groups::GroupSecretParams,
+        profile_key_credential: api::profiles::ProfileKeyCredential,
+    ) -> api::profiles::ProfileKeyCredentialPresentationV2 {
+        self.create_profile_key_credential_presentation_v2_with_context(
+            randomness,
+            group_secret_params,
+            profile_key_credential,
+            None,
+        )
+    }
+
+    /// See [`create_auth_credential_presentation_v2_with_context`](Self::create_auth_credential_presentation_v2_with_context).
+    pub fn create_profile_key_credential_presentation_v2_with_context(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
         group_secret_params: api::groups::GroupSecretParams,
         profile_key_credential: api::profiles::ProfileKeyCredential,
+        context_label: Option<&[u8]>,
     ) -> api::profiles::ProfileKeyCredentialPresentationV2 {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20220120_Random_ServerPublicParams_CreateProfileKeyCredentialPresentationV2",
-            &randomness,
+            randomness.expose_bytes(),
         );
 
         let uid_enc_key_pair = group_secret_params.uid_enc_key_pair;
@@ -749,23 +2726,67 @@ impl ServerPublicParams {
             profile_key_ciphertext.ciphertext,
             profile_key_credential.uid_bytes,
             profile_key_credential.profile_key_bytes,
+            &context_message(self.personalization(), context_label),
             &mut sho,
         );
 
-        api::profiles::ProfileKeyCredentialPresentationV2 {
-            version: [PRESENTATION_VERSION_2],
-            proof,
-            uid_enc_ciphertext: uuid_ciphertext.ciphertext,
-            profile_key_enc_ciphertext: profile_key_ciphertext.ciphertext,
-        }
+        checked_presentation(
+            &randomness,
+            api::profiles::ProfileKeyCredentialPresentationV2 {
+                version: [PRESENTATION_VERSION_2],
+                proof,
+                uid_enc_ciphertext: uuid_ciphertext.ciphertext,
+                profile_key_enc_ciphertext: profile_key_ciphertext.ciphertext,
+            },
+        )
+    }
+
+    /// Presents both credentials under `group_secret_params`, each with its own randomness.
+    ///
+    /// This always returns a presentation. It only verifies if the two credentials hold the same
+    /// profile key for the same uid. See
+    /// [`ProfileKeyEqualityPresentation`](api::profiles::ProfileKeyEqualityPresentation).
+    pub fn create_profile_key_equality_presentation(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
+        group_secret_params: api::groups::GroupSecretParams,
+        first_credential: api::profiles::ProfileKeyCredential,
+        second_credential: api::profiles::ProfileKeyCredential,
+    ) -> api::profiles::ProfileKeyEqualityPresentation {
+        let randomness = Randomness::new(randomness.into());
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_Random_ServerPublicParams_CreateProfileKeyEqualityPresentation",
+            randomness.expose_bytes(),
+        );
+        let mut first_randomness: RandomnessBytes = [0u8; RANDOMNESS_LEN];
+        let mut second_randomness: RandomnessBytes = [0u8; RANDOMNESS_LEN];
+        first_randomness.copy_from_slice(&sho.squeeze(RANDOMNESS_LEN)[..]);
+        second_randomness.copy_from_slice(&sho.squeeze(RANDOMNESS_LEN)[..]);
+
+        checked_presentation(
+            &randomness,
+            api::profiles::ProfileKeyEqualityPresentation {
+                first: self.create_profile_key_credential_presentation_v2(
+                    first_randomness,
+                    group_secret_params,
+                    first_credential,
+                ),
+                second: self.create_profile_key_credential_presentation_v2(
+                    second_randomness,
+                    group_secret_params,
+                    second_credential,
+                ),
+            },
+        )
     }
 
     pub fn create_pni_credential_presentation(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         group_secret_params: api::groups::GroupSecretParams,
         pni_credential: api::profiles::PniCredential,
     ) -> api::profiles::AnyPniCredentialPresentation {
+        let randomness = Randomness::new(randomness.into());
         let presentation_v2 = self.create_pni_credential_presentation_v2(
             randomness,
             group_secret_params,
@@ -776,13 +2797,14 @@ impl ServerPublicParams {
 
     pub fn create_pni_credential_presentation_v1(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         group_secret_params: api::groups::GroupSecretParams,
         pni_credential: api::profiles::PniCredential,
     ) -> api::profiles::PniCredentialPresentationV1 {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20211111_Random_ServerPublicParams_CreatePniCredentialPresentation",
-            &randomness,
+            randomness.expose_bytes(),
         );
 
         let uid_enc_key_pair = group_secret_params.uid_enc_key_pair;
@@ -805,27 +2827,48 @@ impl ServerPublicParams {
             pni_credential.aci_bytes,
             pni_credential.pni_bytes,
             pni_credential.profile_key_bytes,
+            self.personalization(),
             &mut sho,
         );
 
-        api::profiles::PniCredentialPresentationV1 {
-            reserved: [PRESENTATION_VERSION_1],
-            proof,
-            aci_enc_ciphertext: aci_ciphertext.ciphertext,
-            pni_enc_ciphertext: pni_ciphertext.ciphertext,
-            profile_key_enc_ciphertext: profile_key_ciphertext.ciphertext,
-        }
+        checked_presentation(
+            &randomness,
+            api::profiles::PniCredentialPresentationV1 {
+                reserved: [PRESENTATION_VERSION_1],
+                proof,
+                aci_enc_ciphertext: aci_ciphertext.ciphertext,
+                pni_enc_ciphertext: pni_ciphertext.ciphertext,
+                profile_key_enc_ciphertext: profile_key_ciphertext.ciphertext,
+            },
+        )
     }
 
     pub fn create_pni_credential_presentation_v2(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
+        group_secret_params: api::groups::GroupSecretParams,
+        pni_credential: api::profiles::PniCredential,
+    ) -> api::profiles::PniCredentialPresentationV2 {
+        self.create_pni_credential_presentation_v2_with_context(
+            randomness,
+            group_secret_params,
+            pni_credential,
+            None,
+        )
+    }
+
+    /// See [`create_auth_credential_presentation_v2_with_context`](Self::create_auth_credential_presentation_v2_with_context).
+    pub fn create_pni_credential_presentation_v2_with_context(
+        &self,
+        randomness: impl Into<RandomnessBytes>,
         group_secret_params: api::groups::GroupSecretParams,
         pni_credential: api::profiles::PniCredential,
+        context_label: Option<&[u8]>,
     ) -> api::profiles::PniCredentialPresentationV2 {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20220120_Random_ServerPublicParams_CreatePniCredentialPresentationV2",
-            &randomness,
+            randomness.expose_bytes(),
         );
 
         let uid_enc_key_pair = group_secret_params.uid_enc_key_pair;
@@ -848,26 +2891,31 @@ impl ServerPublicParams {
             pni_credential.aci_bytes,
             pni_credential.pni_bytes,
             pni_credential.profile_key_bytes,
+            &context_message(self.personalization(), context_label),
             &mut sho,
         );
 
-        api::profiles::PniCredentialPresentationV2 {
-            version: [PRESENTATION_VERSION_2],
-            proof,
-            aci_enc_ciphertext: aci_ciphertext.ciphertext,
-            pni_enc_ciphertext: pni_ciphertext.ciphertext,
-            profile_key_enc_ciphertext: profile_key_ciphertext.ciphertext,
-        }
+        checked_presentation(
+            &randomness,
+            api::profiles::PniCredentialPresentationV2 {
+                version: [PRESENTATION_VERSION_2],
+                proof,
+                aci_enc_ciphertext: aci_ciphertext.ciphertext,
+                pni_enc_ciphertext: pni_ciphertext.ciphertext,
+                profile_key_enc_ciphertext: profile_key_ciphertext.ciphertext,
+            },
+        )
     }
 
     pub fn create_receipt_credential_request_context(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         receipt_serial_bytes: ReceiptSerialBytes,
     ) -> api::receipts::ReceiptCredentialRequestContext {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20210919_Random_ServerPublicParams_CreateReceiptCredentialRequestContext",
-            &randomness,
+            randomness.expose_bytes(),
         );
 
         let key_pair = crypto::receipt_credential_request::KeyPair::generate(&mut sho);
@@ -897,6 +2945,7 @@ impl ServerPublicParams {
             context.ciphertext_with_secret_nonce.get_ciphertext(),
             response.blinded_credential,
             receipt_struct,
+            self.personalization(),
         )?;
         let credential = context
             .key_pair
@@ -912,24 +2961,29 @@ impl ServerPublicParams {
 
     pub fn create_receipt_credential_presentation(
         &self,
-        randomness: RandomnessBytes,
+        randomness: impl Into<RandomnessBytes>,
         receipt_credential: &api::receipts::ReceiptCredential,
     ) -> api::receipts::ReceiptCredentialPresentation {
+        let randomness = Randomness::new(randomness.into());
         let mut sho = Sho::new(
             b"Signal_ZKGroup_20210919_Random_ServerPublicParams_CreateReceiptCredentialPresentation",
-            &randomness,
+            randomness.expose_bytes(),
         );
         let proof = crypto::proofs::ReceiptCredentialPresentationProof::new(
             self.receipt_credentials_public_key,
             receipt_credential.credential,
+            self.personalization(),
             &mut sho,
         );
-        api::receipts::ReceiptCredentialPresentation {
-            reserved: Default::default(),
-            proof,
-            receipt_expiration_time: receipt_credential.receipt_expiration_time,
-            receipt_level: receipt_credential.receipt_level,
-            receipt_serial_bytes: receipt_credential.receipt_serial_bytes,
-        }
+        checked_presentation(
+            &randomness,
+            api::receipts::ReceiptCredentialPresentation {
+                reserved: Default::default(),
+                proof,
+                receipt_expiration_time: receipt_credential.receipt_expiration_time,
+                receipt_level: receipt_credential.receipt_level,
+                receipt_serial_bytes: receipt_credential.receipt_serial_bytes,
+            },
+        )
     }
 }
@@ -0,0 +1,320 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use crate::api;
+use crate::api::auth::*;
+use crate::api::groups::{GroupPublicParams, ProfileKeyCiphertext, UuidCiphertext};
+use crate::api::profiles::*;
+use crate::api::receipts::*;
+use crate::common::constants::*;
+use crate::common::errors::*;
+
+mod private {
+    use super::*;
+
+    pub trait Sealed {
+        fn verify_with(
+            &self,
+            server_secret_params: &api::ServerSecretParams,
+            group_public_params: GroupPublicParams,
+        ) -> Result<(), ZkGroupVerificationFailure>;
+
+        fn decode(&self) -> DecodedPresentation;
+    }
+}
+
+/// The ciphertexts carried by a verified presentation, independent of its concrete type.
+///
+/// Returned by [`ServerSecretParams::verify_and_decode`](api::ServerSecretParams::verify_and_decode).
+#[derive(Copy, Clone, Default)]
+pub struct DecodedPresentation {
+    pub(crate) uuid_ciphertext: Option<UuidCiphertext>,
+    pub(crate) profile_key_ciphertext: Option<ProfileKeyCiphertext>,
+    pub(crate) pni_ciphertext: Option<UuidCiphertext>,
+    pub(crate) version: Option<u8>,
+}
+
+impl DecodedPresentation {
+    /// The member's uid (or, for PNI presentations, ACI) ciphertext; absent for receipts and bare
+    /// auth presentations.
+    pub fn get_uuid_ciphertext(&self) -> Option<UuidCiphertext> {
+        self.uuid_ciphertext
+    }
+
+    pub fn get_profile_key_ciphertext(&self) -> Option<ProfileKeyCiphertext> {
+        self.profile_key_ciphertext
+    }
+
+    pub fn get_pni_ciphertext(&self) -> Option<UuidCiphertext> {
+        self.pni_ciphertext
+    }
+
+    /// The presentation version byte ([`PRESENTATION_VERSION_1`] or [`PRESENTATION_VERSION_2`]),
+    /// or `None` for unversioned presentation types.
+    pub fn get_version(&self) -> Option<u8> {
+        self.version
+    }
+}
+
+/// A presentation that [`ServerSecretParams::verify_dyn`](api::ServerSecretParams::verify_dyn) can
+/// check without knowing its concrete type.
+///
+/// This trait is sealed: only presentation types defined in this crate implement it. It covers
+/// every presentation whose verification needs nothing beyond the server and group params.
+/// [`AuthCredentialWithEpochPresentation`] and [`AuthCredentialTimestampedPresentation`] are left
+/// out, since verifying them also takes a minimum epoch or the server's clock.
+pub trait VerifiablePresentation: private::Sealed {}
+
+impl<T: private::Sealed> VerifiablePresentation for T {}
+
+impl private::Sealed for AnyAuthCredentialPresentation {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params.verify_auth_credential_presentation(group_public_params, self)
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        match self {
+            AnyAuthCredentialPresentation::V1(presentation) => presentation.decode(),
+            AnyAuthCredentialPresentation::V2(presentation) => presentation.decode(),
+        }
+    }
+}
+
+impl private::Sealed for AuthCredentialPresentationV1 {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params.verify_auth_credential_presentation_v1(group_public_params, self)
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        DecodedPresentation {
+            uuid_ciphertext: Some(self.get_uuid_ciphertext()),
+            version: Some(PRESENTATION_VERSION_1),
+            ..Default::default()
+        }
+    }
+}
+
+impl private::Sealed for AuthCredentialPresentationV2 {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params.verify_auth_credential_presentation_v2(group_public_params, self)
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        DecodedPresentation {
+            uuid_ciphertext: Some(self.get_uuid_ciphertext()),
+            version: Some(PRESENTATION_VERSION_2),
+            ..Default::default()
+        }
+    }
+}
+
+/// Bare presentations are not tied to a group, so `group_public_params` is ignored.
+impl private::Sealed for AuthCredentialBarePresentation {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        _group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params.verify_bare_auth_presentation(self)
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        DecodedPresentation::default()
+    }
+}
+
+/// The device tag commitment is not part of [`DecodedPresentation`]; use
+/// [`verify_auth_credential_device_tagged_presentation`](api::ServerSecretParams::verify_auth_credential_device_tagged_presentation)
+/// to get it.
+impl private::Sealed for AuthCredentialDeviceTaggedPresentation {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params
+            .verify_auth_credential_device_tagged_presentation(group_public_params, self)
+            .map(|_| ())
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        DecodedPresentation {
+            uuid_ciphertext: Some(self.get_uuid_ciphertext()),
+            ..Default::default()
+        }
+    }
+}
+
+impl private::Sealed for AnyProfileKeyCredentialPresentation {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params.verify_profile_key_credential_presentation(group_public_params, self)
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        match self {
+            AnyProfileKeyCredentialPresentation::V1(presentation) => presentation.decode(),
+            AnyProfileKeyCredentialPresentation::V2(presentation) => presentation.decode(),
+        }
+    }
+}
+
+impl private::Sealed for ProfileKeyCredentialPresentationV1 {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params
+            .verify_profile_key_credential_presentation_v1(group_public_params, self)
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        DecodedPresentation {
+            uuid_ciphertext: Some(self.get_uuid_ciphertext()),
+            profile_key_ciphertext: Some(self.get_profile_key_ciphertext()),
+            version: Some(PRESENTATION_VERSION_1),
+            ..Default::default()
+        }
+    }
+}
+
+impl private::Sealed for ProfileKeyCredentialPresentationV2 {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params
+            .verify_profile_key_credential_presentation_v2(group_public_params, self)
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        DecodedPresentation {
+            uuid_ciphertext: Some(self.get_uuid_ciphertext()),
+            profile_key_ciphertext: Some(self.get_profile_key_ciphertext()),
+            version: Some(PRESENTATION_VERSION_2),
+            ..Default::default()
+        }
+    }
+}
+
+impl private::Sealed for ProfileKeyCredentialHidingPresentation {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params
+            .verify_profile_key_credential_presentation_hiding_pk(group_public_params, self)
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        DecodedPresentation {
+            uuid_ciphertext: Some(self.get_uuid_ciphertext()),
+            ..Default::default()
+        }
+    }
+}
+
+impl private::Sealed for ProfileKeyEqualityPresentation {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params.verify_profile_key_equality_presentation(group_public_params, self)
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        // Once verified, both halves carry the same ciphertexts.
+        self.first.decode()
+    }
+}
+
+impl private::Sealed for AnyPniCredentialPresentation {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params.verify_pni_credential_presentation(group_public_params, self)
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        match self {
+            AnyPniCredentialPresentation::V1(presentation) => presentation.decode(),
+            AnyPniCredentialPresentation::V2(presentation) => presentation.decode(),
+        }
+    }
+}
+
+impl private::Sealed for PniCredentialPresentationV1 {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params.verify_pni_credential_presentation_v1(group_public_params, self)
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        DecodedPresentation {
+            uuid_ciphertext: Some(self.get_aci_ciphertext()),
+            profile_key_ciphertext: Some(self.get_profile_key_ciphertext()),
+            pni_ciphertext: Some(self.get_pni_ciphertext()),
+            version: Some(PRESENTATION_VERSION_1),
+        }
+    }
+}
+
+impl private::Sealed for PniCredentialPresentationV2 {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params.verify_pni_credential_presentation_v2(group_public_params, self)
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        DecodedPresentation {
+            uuid_ciphertext: Some(self.get_aci_ciphertext()),
+            profile_key_ciphertext: Some(self.get_profile_key_ciphertext()),
+            pni_ciphertext: Some(self.get_pni_ciphertext()),
+            version: Some(PRESENTATION_VERSION_2),
+        }
+    }
+}
+
+/// Receipt presentations are not tied to a group, so `group_public_params` is ignored.
+impl private::Sealed for ReceiptCredentialPresentation {
+    fn verify_with(
+        &self,
+        server_secret_params: &api::ServerSecretParams,
+        _group_public_params: GroupPublicParams,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        server_secret_params.verify_receipt_credential_presentation(self)
+    }
+
+    fn decode(&self) -> DecodedPresentation {
+        DecodedPresentation::default()
+    }
+}
@@ -4,7 +4,11 @@
 //
 
 pub mod array_utils;
+pub mod clock;
 pub mod constants;
 pub mod errors;
+pub mod presentation_randomness;
+pub mod serialization;
 pub mod sho;
 pub mod simple_types;
+pub mod verify_metrics;
@@ -0,0 +1,63 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::convert::TryInto;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::common::constants::*;
+use crate::common::simple_types::*;
+
+/// A source of the current time for freshness checks.
+///
+/// Verify methods never read a clock themselves: the time-aware ones take `now` (in seconds) or
+/// `now_day` as an argument, which callers get from a `Clock`. Tests can pass a [`FixedClock`].
+pub trait Clock {
+    /// Seconds since the Unix epoch.
+    fn now_seconds(&self) -> u64;
+
+    /// Days since the Unix epoch, as used for redemption times.
+    fn now_day(&self) -> RedemptionTime {
+        (self.now_seconds() / SECONDS_PER_DAY)
+            .try_into()
+            .unwrap_or(RedemptionTime::MAX)
+    }
+}
+
+/// The system clock. Times before the Unix epoch read as the epoch.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_seconds(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs())
+    }
+}
+
+/// A clock stopped at the given number of seconds since the Unix epoch.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FixedClock(pub u64);
+
+impl Clock for FixedClock {
+    fn now_seconds(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock() {
+        let clock = FixedClock(123456 * SECONDS_PER_DAY + SECONDS_PER_DAY - 1);
+        assert_eq!(123456, clock.now_day());
+        assert_eq!(RedemptionTime::MAX, FixedClock(u64::MAX).now_day());
+
+        // Well after this was written.
+        assert!(SystemClock.now_day() > 19000);
+    }
+}
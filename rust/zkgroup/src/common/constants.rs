@@ -7,9 +7,28 @@ pub const NUM_AUTH_CRED_ATTRIBUTES: usize = 3;
 pub const NUM_PROFILE_KEY_CRED_ATTRIBUTES: usize = 4;
 pub const NUM_RECEIPT_CRED_ATTRIBUTES: usize = 2;
 
+/// The most auth credentials issued under one batch proof.
+pub const MAX_AUTH_CREDENTIAL_BATCH_SIZE: usize = 16;
+
 pub const PRESENTATION_VERSION_1: u8 = 0;
 pub const PRESENTATION_VERSION_2: u8 = 1;
 
+/// How many days a presented redemption time may run ahead of the verifier's clock.
+pub const REDEMPTION_TIME_FUTURE_SKEW_DAYS: u32 = 1;
+/// How many days after its redemption time an auth credential presentation is still accepted.
+pub const REDEMPTION_TIME_WINDOW_DAYS: u32 = 7;
+/// How many days a member's presented redemption time may lag the latest one already seen for
+/// them. A lag of exactly this many days is still accepted, so a client can present yesterday's
+/// credential after today's.
+pub const REDEMPTION_TIME_MONOTONIC_GRACE_DAYS: u32 = 1;
+pub const SECONDS_PER_DAY: u64 = 86400;
+
+/// The order of the Ristretto group, i.e. the modulus of the scalar field, in little-endian bytes.
+pub const SCALAR_FIELD_ORDER: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
+
 pub const AES_KEY_LEN: usize = 32;
 pub const AESGCM_NONCE_LEN: usize = 12;
 pub const AESGCM_TAG_LEN: usize = 16;
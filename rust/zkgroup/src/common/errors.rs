@@ -10,3 +10,66 @@ pub struct ZkGroupVerificationFailure;
 #[derive(Debug, displaydoc::Display)]
 /// Deserialization failure in zkgroup
 pub struct ZkGroupDeserializationFailure;
+
+#[derive(Debug, displaydoc::Display)]
+/// Server params failed self-check: {0} is suspect
+pub struct ZkGroupSelfCheckFailure(pub &'static str);
+
+#[derive(Debug, displaydoc::Display)]
+/// Failure verifying a presentation or token and then checking what it carries
+#[non_exhaustive]
+pub enum ZkGroupCheckFailure {
+    /// Verification failure in zkgroup
+    Verification,
+    /// Redemption time outside the accepted range
+    RedemptionTimeOutOfRange,
+    /// Client timestamp outside the accepted window
+    ClientTimeOutOfWindow,
+    /// Receipt or membership token has expired
+    Expired,
+    /// Receipt has already been redeemed
+    AlreadySpent,
+    /// No params for the requested group
+    UnknownGroup,
+    /// Profile key version is not allowed
+    ProfileKeyVersionNotAllowed,
+    /// Uid is one of the members
+    Member,
+    /// Presentation version is below the required minimum
+    VersionTooLow,
+    /// Params snapshot is not canonically encoded
+    SnapshotNotCanonical,
+    /// Params snapshot failed self-check: {0} is suspect
+    SnapshotSelfCheck(&'static str),
+    /// Verification failure (auth: {auth}, profile key: {profile_key}, receipt: {receipt})
+    Presentations {
+        auth: bool,
+        profile_key: bool,
+        receipt: bool,
+    },
+}
+
+impl From<ZkGroupVerificationFailure> for ZkGroupCheckFailure {
+    fn from(_: ZkGroupVerificationFailure) -> Self {
+        Self::Verification
+    }
+}
+
+#[derive(Debug, displaydoc::Display)]
+/// Failure recovering a stored credential
+pub enum ZkGroupRecoveryFailure {
+    /// Credential is truncated in its {0} field
+    Truncated(&'static str),
+    /// Credential's {0} field is corrupt
+    Corrupt(&'static str),
+    /// Credential is followed by unexpected bytes
+    TrailingBytes,
+}
+
+#[derive(Debug, displaydoc::Display)]
+/// Presentation randomness counter was already used
+pub struct ZkGroupCounterReuseFailure;
+
+#[derive(Debug, displaydoc::Display)]
+/// Presentation randomness was already used for a different presentation
+pub struct ZkGroupRandomnessReuseFailure;
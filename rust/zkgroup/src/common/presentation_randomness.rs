@@ -0,0 +1,173 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use zeroize::Zeroize;
+
+use crate::common::constants::*;
+use crate::common::errors::*;
+use crate::common::sho::*;
+use crate::common::simple_types::*;
+
+/// Derives presentation randomness deterministically from a long-term client secret and a
+/// counter, for reproducible presentations.
+///
+/// The same secret and counter always give the same randomness, and therefore the same
+/// presentation, which would let a verifier link the two. The deriver only hands out increasing
+/// counters; to resume after a restart, construct it with the
+/// [`next_counter`](Self::next_counter) saved from last time.
+pub struct PresentationRandomnessDeriver {
+    secret: [u8; 32],
+    next_counter: Option<u64>,
+}
+
+impl PresentationRandomnessDeriver {
+    pub fn new(secret: [u8; 32]) -> Self {
+        Self::resume(secret, 0)
+    }
+
+    /// Continues from a previous deriver for the same secret, refusing every counter below
+    /// `next_counter`.
+    pub fn resume(secret: [u8; 32], next_counter: u64) -> Self {
+        Self {
+            secret,
+            next_counter: Some(next_counter),
+        }
+    }
+
+    /// The lowest counter that has not been used yet, or `None` if every counter has been used.
+    pub fn next_counter(&self) -> Option<u64> {
+        self.next_counter
+    }
+
+    /// Derives the randomness for `counter`, which must not be lower than
+    /// [`next_counter`](Self::next_counter). Counters may be skipped.
+    pub fn derive(&mut self, counter: u64) -> Result<Randomness, ZkGroupCounterReuseFailure> {
+        match self.next_counter {
+            Some(next_counter) if counter >= next_counter => {}
+            _ => return Err(ZkGroupCounterReuseFailure),
+        }
+        self.next_counter = counter.checked_add(1);
+
+        let mut data = self.secret.to_vec();
+        data.extend_from_slice(&counter.to_be_bytes());
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_PresentationRandomness_Derive",
+            &data,
+        );
+        data.zeroize();
+
+        let mut randomness: RandomnessBytes = [0u8; RANDOMNESS_LEN];
+        randomness.copy_from_slice(&sho.squeeze(RANDOMNESS_LEN));
+        Ok(Randomness::new(randomness))
+    }
+
+    /// Derives the randomness for [`next_counter`](Self::next_counter).
+    pub fn derive_next(&mut self) -> Result<Randomness, ZkGroupCounterReuseFailure> {
+        let counter = self.next_counter.ok_or(ZkGroupCounterReuseFailure)?;
+        self.derive(counter)
+    }
+}
+
+impl Drop for PresentationRandomnessDeriver {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// Produces presentation randomness from a one-way ratchet, for forward secrecy.
+///
+/// Each call hashes the current state into the randomness and the next state, and wipes the old
+/// state. Unlike [`PresentationRandomnessDeriver`], randomness that has been handed out can't be
+/// recomputed from what the source holds afterwards, so the state can't be rewound or resumed
+/// from an earlier point.
+pub struct RatchetingPresentationSource {
+    state: [u8; 32],
+}
+
+impl RatchetingPresentationSource {
+    pub fn new(seed: [u8; 32]) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns fresh randomness and advances the ratchet.
+    pub fn next_randomness(&mut self) -> Randomness {
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_PresentationRandomness_Ratchet",
+            &self.state,
+        );
+        let mut output = sho.squeeze(32 + RANDOMNESS_LEN);
+        self.state.zeroize();
+        self.state.copy_from_slice(&output[..32]);
+
+        let mut randomness: RandomnessBytes = [0u8; RANDOMNESS_LEN];
+        randomness.copy_from_slice(&output[32..]);
+        output.zeroize();
+        Randomness::new(randomness)
+    }
+}
+
+impl Drop for RatchetingPresentationSource {
+    fn drop(&mut self) {
+        self.state.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive() {
+        let mut deriver = PresentationRandomnessDeriver::new(TEST_ARRAY_32);
+        let first = deriver.derive_next().unwrap();
+        let fifth = deriver.derive(5).unwrap();
+        assert_ne!(first.expose_bytes(), fifth.expose_bytes());
+        assert_eq!(Some(6), deriver.next_counter());
+
+        assert!(deriver.derive(0).is_err());
+        assert!(deriver.derive(5).is_err());
+
+        // The same secret and counter give the same randomness.
+        let mut resumed = PresentationRandomnessDeriver::resume(TEST_ARRAY_32, 5);
+        assert_eq!(
+            fifth.expose_bytes(),
+            resumed.derive_next().unwrap().expose_bytes()
+        );
+        let mut other = PresentationRandomnessDeriver::resume(TEST_ARRAY_32_1, 5);
+        assert_ne!(
+            fifth.expose_bytes(),
+            other.derive_next().unwrap().expose_bytes()
+        );
+
+        let mut exhausted = PresentationRandomnessDeriver::resume(TEST_ARRAY_32, u64::MAX);
+        exhausted.derive_next().unwrap();
+        assert_eq!(None, exhausted.next_counter());
+        assert!(exhausted.derive_next().is_err());
+    }
+
+    #[test]
+    fn test_ratchet() {
+        let mut source = RatchetingPresentationSource::new(TEST_ARRAY_32);
+        let first = source.next_randomness();
+        let state = source.state;
+        let second = source.next_randomness();
+        assert_ne!(first.expose_bytes(), second.expose_bytes());
+        assert_ne!(state, source.state);
+        assert_ne!(TEST_ARRAY_32, state);
+
+        // The state after a step reproduces only what comes after it.
+        let mut resumed = RatchetingPresentationSource::new(state);
+        assert_eq!(
+            second.expose_bytes(),
+            resumed.next_randomness().expose_bytes()
+        );
+        assert_ne!(
+            first.expose_bytes(),
+            RatchetingPresentationSource::new(TEST_ARRAY_32_1)
+                .next_randomness()
+                .expose_bytes()
+        );
+    }
+}
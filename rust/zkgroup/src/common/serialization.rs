@@ -0,0 +1,80 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Deserialization entry points with explicit framing rules.
+//!
+//! Every binding (Java, Swift, Node) serializes by calling into this crate, so there is exactly
+//! one wire format: bincode with little-endian, fixed-width integers and u64 length prefixes.
+//! The only leniency in the plain `bincode::deserialize` used throughout the crate is that it
+//! ignores trailing bytes after the value.
+
+use bincode::Options;
+use serde::de::DeserializeOwned;
+
+use crate::common::errors::*;
+
+/// Deserializes `bytes` in the canonical format, rejecting any trailing bytes.
+///
+/// A successful result means re-serializing the value reproduces `bytes` exactly.
+pub fn deserialize_strict<T: DeserializeOwned>(
+    bytes: &[u8],
+) -> Result<T, ZkGroupDeserializationFailure> {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize(bytes)
+        .map_err(|_| ZkGroupDeserializationFailure)
+}
+
+/// Deserializes `bytes` the way the rest of this crate always has.
+///
+/// The single tolerated difference from [`deserialize_strict`] is trailing bytes after the value,
+/// which are ignored. Integer and length encodings are *not* relaxed: varint-encoded input is
+/// rejected (or misparsed and then rejected), since no binding has ever produced it.
+pub fn deserialize_compatible<T: DeserializeOwned>(
+    bytes: &[u8],
+) -> Result<T, ZkGroupDeserializationFailure> {
+    bincode::deserialize(bytes).map_err(|_| ZkGroupDeserializationFailure)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use crate::common::constants::*;
+
+    #[test]
+    fn test_trailing_bytes() {
+        let server_secret_params = api::ServerSecretParams::generate(TEST_ARRAY_32);
+        let server_public_params = server_secret_params.get_public_params();
+        let group_secret_params = api::groups::GroupSecretParams::generate(TEST_ARRAY_32_1);
+        let uid = TEST_ARRAY_16;
+        let redemption_time = 123456u32;
+
+        let response = server_secret_params
+            .issue_auth_credential(TEST_ARRAY_32_2, uid, redemption_time)
+            .unwrap();
+        let credential = server_public_params
+            .receive_auth_credential(uid, redemption_time, &response)
+            .unwrap();
+        let presentation = server_public_params.create_auth_credential_presentation_v2(
+            TEST_ARRAY_32_3,
+            group_secret_params,
+            credential,
+        );
+
+        let mut bytes = bincode::serialize(&presentation).unwrap();
+        deserialize_strict::<api::auth::AuthCredentialPresentationV2>(&bytes).unwrap();
+        deserialize_compatible::<api::auth::AuthCredentialPresentationV2>(&bytes).unwrap();
+
+        bytes.push(0);
+        assert!(deserialize_strict::<api::auth::AuthCredentialPresentationV2>(&bytes).is_err());
+        deserialize_compatible::<api::auth::AuthCredentialPresentationV2>(&bytes).unwrap();
+
+        bytes.truncate(bytes.len() - 2);
+        assert!(deserialize_strict::<api::auth::AuthCredentialPresentationV2>(&bytes).is_err());
+        assert!(deserialize_compatible::<api::auth::AuthCredentialPresentationV2>(&bytes).is_err());
+    }
+}
@@ -7,6 +7,13 @@ use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
 use poksho::ShoApi;
 
+/// Domain-separated hashing used for key derivation and randomness expansion.
+///
+/// Verifiers never recompute values derived under the `Signal_ZKGroup_*_Random_*` labels; proof
+/// transcripts are domain-separated by poksho's own fixed label instead. Presentations from older
+/// clients are distinguished by their version byte (see `AnyAuthCredentialPresentation` and
+/// friends). Key derivation labels, on the other hand, are part of the format: changing one changes
+/// every key derived from the same seed.
 pub struct Sho {
     internal_sho: poksho::ShoHmacSha256,
 }
@@ -4,7 +4,10 @@
 //
 
 use crate::common::constants::*;
+use crate::common::errors::*;
 use curve25519_dalek::scalar::Scalar;
+use std::convert::TryInto;
+use zeroize::Zeroize;
 
 pub type AesKeyBytes = [u8; AES_KEY_LEN];
 pub type GroupMasterKeyBytes = [u8; GROUP_MASTER_KEY_LEN];
@@ -18,6 +21,67 @@ pub type GroupIdentifierBytes = [u8; GROUP_IDENTIFIER_LEN];
 pub type ProfileKeyVersionBytes = [u8; PROFILE_KEY_VERSION_LEN];
 pub type ProfileKeyVersionEncodedBytes = [u8; PROFILE_KEY_VERSION_ENCODED_LEN];
 pub type RedemptionTime = u32;
+pub type AuthCredentialEpoch = u32;
+
+/// Copies `bytes` into a fixed-size byte array such as [`UidBytes`] or [`RandomnessBytes`],
+/// failing instead of panicking if the length is wrong.
+pub fn bytes_from_slice<const N: usize>(
+    bytes: &[u8],
+) -> Result<[u8; N], ZkGroupDeserializationFailure> {
+    bytes.try_into().map_err(|_| ZkGroupDeserializationFailure)
+}
+
+/// Checked conversions into [`NotarySignatureBytes`].
+pub struct NotarySignature;
+
+impl NotarySignature {
+    /// Checks that `bytes` has the length of a notary signature and passes
+    /// [`validate_signature_encoding`](crate::api::validate_signature_encoding).
+    ///
+    /// This does not check the signature against any message.
+    pub fn try_from_slice(
+        bytes: &[u8],
+    ) -> Result<NotarySignatureBytes, ZkGroupDeserializationFailure> {
+        let signature: NotarySignatureBytes = bytes_from_slice(bytes)?;
+        crate::crypto::signature::validate_encoding(&signature)?;
+        Ok(signature)
+    }
+}
+
+/// Randomness for a single operation, wiped from memory when dropped.
+///
+/// Every API that takes `RandomnessBytes` also accepts a `Randomness`, by value. Those APIs keep
+/// their own copy in a `Randomness` too, so it is wiped when they return. The bytes are only
+/// readable through [`expose_bytes`](Self::expose_bytes).
+pub struct Randomness(RandomnessBytes);
+
+impl Randomness {
+    pub fn new(bytes: RandomnessBytes) -> Self {
+        Self(bytes)
+    }
+
+    pub fn expose_bytes(&self) -> &RandomnessBytes {
+        &self.0
+    }
+}
+
+impl From<RandomnessBytes> for Randomness {
+    fn from(bytes: RandomnessBytes) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<Randomness> for RandomnessBytes {
+    fn from(randomness: Randomness) -> Self {
+        randomness.0
+    }
+}
+
+impl Drop for Randomness {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
 
 // A random UUID that the receipt issuing server will blind authorize to redeem a given receipt
 // level within a certain time frame.
@@ -27,16 +91,36 @@ pub type ReceiptSerialBytes = [u8; RECEIPT_SERIAL_LEN];
 // days into the future.
 pub type ReceiptExpirationTime = u64;
 
+// Seconds past the epoch, as read from the client's clock when creating a presentation.
+pub type PresentationTimestamp = u64;
+
 // Used to tell the server handling receipt redemptions what to redeem the receipt for. Clients
 // should validate this matches their expectations.
 pub type ReceiptLevel = u64;
 
 pub fn encode_redemption_time(redemption_time: u32) -> Scalar {
+    encode_redemption_time_and_epoch(redemption_time, 0)
+}
+
+// The epoch shares the redemption time's attribute; epoch 0 encodes identically to the time alone.
+pub fn encode_redemption_time_and_epoch(
+    redemption_time: RedemptionTime,
+    epoch: AuthCredentialEpoch,
+) -> Scalar {
     let mut scalar_bytes: [u8; 32] = Default::default();
     scalar_bytes[0..4].copy_from_slice(&redemption_time.to_be_bytes());
+    scalar_bytes[4..8].copy_from_slice(&epoch.to_be_bytes());
     Scalar::from_bytes_mod_order(scalar_bytes)
 }
 
+/// Whether the little-endian `scalar_bytes` would change when reduced into the scalar field, i.e.
+/// whether they encode a value of at least [`SCALAR_FIELD_ORDER`].
+///
+/// Values that fit in 252 bits, such as any integer attribute up to `u128`, are never reducible.
+pub fn is_reducible(scalar_bytes: &[u8; 32]) -> bool {
+    Scalar::from_bytes_mod_order(*scalar_bytes).to_bytes() != *scalar_bytes
+}
+
 pub fn encode_receipt_serial_bytes(receipt_serial_bytes: ReceiptSerialBytes) -> Scalar {
     let mut scalar_bytes: [u8; 32] = Default::default();
     scalar_bytes[0..16].copy_from_slice(&receipt_serial_bytes[..]);
@@ -55,4 +139,61 @@ mod tests {
             Ok(_) => unreachable!(),
         }
     }
+
+    #[test]
+    fn test_bytes_from_slice() {
+        let uid: UidBytes = bytes_from_slice(&TEST_ARRAY_16).unwrap();
+        assert_eq!(uid, TEST_ARRAY_16);
+        assert!(bytes_from_slice::<UUID_LEN>(&TEST_ARRAY_16[1..]).is_err());
+        assert!(bytes_from_slice::<UUID_LEN>(&TEST_ARRAY_32).is_err());
+    }
+
+    #[test]
+    fn test_notary_signature_try_from_slice() {
+        let server_secret_params = crate::api::ServerSecretParams::generate(TEST_ARRAY_32);
+        let signature = server_secret_params.sign(TEST_ARRAY_32_1, b"message");
+        assert_eq!(
+            NotarySignature::try_from_slice(&signature).unwrap(),
+            signature
+        );
+        server_secret_params
+            .get_public_params()
+            .verify_signature(
+                b"message",
+                NotarySignature::try_from_slice(&signature).unwrap(),
+            )
+            .unwrap();
+
+        assert!(NotarySignature::try_from_slice(&signature[1..]).is_err());
+        assert!(NotarySignature::try_from_slice(&[signature.as_ref(), &[0]].concat()).is_err());
+
+        for offset in &[0, 32] {
+            let mut noncanonical = signature;
+            noncanonical[offset + 31] = 0xFF;
+            assert!(NotarySignature::try_from_slice(&noncanonical).is_err());
+        }
+    }
+
+    #[test]
+    fn test_scalar_field_order() {
+        assert_eq!(
+            SCALAR_FIELD_ORDER,
+            curve25519_dalek::constants::BASEPOINT_ORDER.to_bytes()
+        );
+        // Reducing the order itself gives zero.
+        assert_eq!(
+            Scalar::zero(),
+            Scalar::from_bytes_mod_order(SCALAR_FIELD_ORDER)
+        );
+        assert!(is_reducible(&SCALAR_FIELD_ORDER));
+        assert!(is_reducible(&[0xFF; 32]));
+
+        let mut just_below = SCALAR_FIELD_ORDER;
+        just_below[0] -= 1;
+        assert!(!is_reducible(&just_below));
+        assert!(!is_reducible(&[0; 32]));
+        let mut max_u64 = [0u8; 32];
+        max_u64[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(!is_reducible(&max_u64));
+    }
 }
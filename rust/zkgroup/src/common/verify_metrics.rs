@@ -0,0 +1,169 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Presentation verification latency, recorded through the [`metrics`] facade when the
+//! `metrics` feature is enabled.
+//!
+//! Every presentation proof verification records one value, in microseconds, into the
+//! [`VERIFY_LATENCY_HISTOGRAM`] histogram, whether or not the proof verifies. Values are labeled
+//! with:
+//!
+//! - `credential`: `auth`, `profile_key`, `pni`, or `receipt`
+//! - `version`: `v1` or `v2` for versioned presentations (receipt presentations are always
+//!   `v1`), or the kind of an unversioned one: `bare` for auth presentations not bound to a group,
+//!   and `hiding` for profile key presentations without a profile key ciphertext
+//!
+//! Without the feature nothing is timed or recorded.
+
+/// The name of the verification latency histogram. Values are in microseconds.
+#[cfg(feature = "metrics")]
+pub const VERIFY_LATENCY_HISTOGRAM: &str = "zkgroup_verify_presentation_latency_us";
+
+/// Registers the unit and description of [`VERIFY_LATENCY_HISTOGRAM`] with the installed
+/// recorder.
+#[cfg(feature = "metrics")]
+pub fn describe_metrics() {
+    metrics::describe_histogram!(
+        VERIFY_LATENCY_HISTOGRAM,
+        metrics::Unit::Microseconds,
+        "Time to verify a zkgroup presentation proof"
+    );
+}
+
+/// Records the time until it is dropped into [`VERIFY_LATENCY_HISTOGRAM`].
+///
+/// Without the `metrics` feature this is an empty type, and starting one does nothing.
+pub(crate) struct VerifyTimer {
+    #[cfg(feature = "metrics")]
+    credential: &'static str,
+    #[cfg(feature = "metrics")]
+    version: &'static str,
+    #[cfg(feature = "metrics")]
+    start: std::time::Instant,
+}
+
+impl VerifyTimer {
+    #[cfg(feature = "metrics")]
+    pub(crate) fn start(credential: &'static str, version: &'static str) -> Self {
+        Self {
+            credential,
+            version,
+            start: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    #[inline(always)]
+    pub(crate) fn start(_credential: &'static str, _version: &'static str) -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl Drop for VerifyTimer {
+    fn drop(&mut self) {
+        metrics::histogram!(
+            VERIFY_LATENCY_HISTOGRAM,
+            self.start.elapsed().as_secs_f64() * 1e6,
+            "credential" => self.credential,
+            "version" => self.version,
+        );
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use crate::api;
+    use crate::common::constants::*;
+    use metrics::{Counter, Gauge, Histogram, HistogramFn, Key, KeyName, SharedString, Unit};
+    use std::sync::{Arc, Mutex};
+
+    /// A histogram name, its labels, and the recorded value.
+    type Sample = (String, Vec<(String, String)>, f64);
+
+    #[derive(Default)]
+    struct Recorded(Mutex<Vec<Sample>>);
+
+    struct RecordedHistogram(Key, Arc<Recorded>);
+
+    impl HistogramFn for RecordedHistogram {
+        fn record(&self, value: f64) {
+            let labels = self
+                .0
+                .labels()
+                .map(|label| (label.key().to_string(), label.value().to_string()))
+                .collect();
+            let mut recorded = self.1 .0.lock().unwrap();
+            recorded.push((self.0.name().to_string(), labels, value));
+        }
+    }
+
+    struct TestRecorder(Arc<Recorded>);
+
+    impl metrics::Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn register_counter(&self, _: &Key) -> Counter {
+            Counter::noop()
+        }
+        fn register_gauge(&self, _: &Key) -> Gauge {
+            Gauge::noop()
+        }
+        fn register_histogram(&self, key: &Key) -> Histogram {
+            Histogram::from_arc(Arc::new(RecordedHistogram(
+                key.clone(),
+                Arc::clone(&self.0),
+            )))
+        }
+    }
+
+    #[test]
+    fn test_verify_records_latency() {
+        let recorded = Arc::new(Recorded::default());
+        metrics::set_boxed_recorder(Box::new(TestRecorder(Arc::clone(&recorded)))).unwrap();
+        describe_metrics();
+
+        let server_secret_params = api::ServerSecretParams::generate(TEST_ARRAY_32);
+        let server_public_params = server_secret_params.get_public_params();
+        let group_secret_params = api::groups::GroupSecretParams::generate(TEST_ARRAY_32_1);
+        let response = server_secret_params
+            .issue_auth_credential(TEST_ARRAY_32_2, TEST_ARRAY_16, 123456)
+            .unwrap();
+        let credential = server_public_params
+            .receive_auth_credential(TEST_ARRAY_16, 123456, &response)
+            .unwrap();
+        let presentation = server_public_params.create_auth_credential_presentation_v2(
+            TEST_ARRAY_32_3,
+            group_secret_params,
+            credential,
+        );
+        server_secret_params
+            .verify_auth_credential_presentation_v2(
+                group_secret_params.get_public_params(),
+                &presentation,
+            )
+            .unwrap();
+        let bare_presentation =
+            server_public_params.create_bare_auth_presentation(TEST_ARRAY_32_4, credential);
+        server_secret_params
+            .verify_bare_auth_presentation(&bare_presentation)
+            .unwrap();
+
+        // Other tests may be verifying concurrently, so look for a matching sample rather than
+        // expecting exactly one.
+        let recorded = recorded.0.lock().unwrap();
+        for version in ["v2", "bare"] {
+            let expected_labels = vec![
+                ("credential".to_string(), "auth".to_string()),
+                ("version".to_string(), version.to_string()),
+            ];
+            assert!(recorded.iter().any(|(name, labels, value)| {
+                name == VERIFY_LATENCY_HISTOGRAM && *labels == expected_labels && *value > 0.0
+            }));
+        }
+    }
+}
@@ -8,14 +8,16 @@
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
 use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::subtle::{Choice, ConstantTimeEq};
 use serde::{Deserialize, Serialize};
 
 use crate::common::array_utils::{ArrayLike, OneBased};
+use crate::common::errors::*;
 use crate::common::sho::*;
 use crate::common::simple_types::*;
 use crate::crypto::receipt_struct::ReceiptStruct;
-use crate::crypto::uid_struct;
 use crate::crypto::{profile_key_credential_request, receipt_credential_request, receipt_struct};
+use crate::crypto::{profile_key_struct, uid_struct};
 use crate::{
     NUM_AUTH_CRED_ATTRIBUTES, NUM_PROFILE_KEY_CRED_ATTRIBUTES, NUM_RECEIPT_CRED_ATTRIBUTES,
 };
@@ -77,6 +79,9 @@ impl AttrScalars for ReceiptCredential {
 impl AttrScalars for PniCredential {
     type Storage = [Scalar; 6];
 }
+impl AttrScalars for MembershipToken {
+    type Storage = [Scalar; 2];
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct KeyPair<S: AttrScalars> {
@@ -199,12 +204,34 @@ pub struct BlindedReceiptCredential {
     pub(crate) S2: RistrettoPoint,
 }
 
+/// A MAC over a member pseudonym, group, and expiration time, issued and checked by the same
+/// server. Unlike the credentials above it is never presented with a proof.
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MembershipToken {
+    pub(crate) t: Scalar,
+    pub(crate) U: RistrettoPoint,
+    pub(crate) V: RistrettoPoint,
+}
+
+/// Hashes a credential's version byte and its `U` point, and nothing else.
+///
+/// See [`crate::api::auth::AuthCredential::credential_log_id`].
+pub(crate) fn credential_log_id(label: &[u8], version: u8, U: RistrettoPoint) -> [u8; 16] {
+    let mut data = vec![version];
+    data.extend_from_slice(U.compress().as_bytes());
+    let mut sho = Sho::new(label, &data);
+    let mut log_id = [0u8; 16];
+    log_id.copy_from_slice(&sho.squeeze(16));
+    log_id
+}
+
 pub(crate) fn convert_to_points_uid_struct(
     uid: uid_struct::UidStruct,
     redemption_time: RedemptionTime,
+    epoch: AuthCredentialEpoch,
 ) -> Vec<RistrettoPoint> {
     let system = SystemParams::get_hardcoded();
-    let redemption_time_scalar = encode_redemption_time(redemption_time);
+    let redemption_time_scalar = encode_redemption_time_and_epoch(redemption_time, epoch);
     vec![uid.M1, uid.M2, redemption_time_scalar * system.G_m3]
 }
 
@@ -217,6 +244,18 @@ pub(crate) fn convert_to_points_receipt_struct(
     vec![m1 * system.G_m1, receipt_serial_scalar * system.G_m2]
 }
 
+pub(crate) fn convert_to_points_membership(
+    pseudonym: &[u8; 32],
+    group_id: &GroupIdentifierBytes,
+    expiration: u64,
+) -> [RistrettoPoint; 2] {
+    let system = SystemParams::get_hardcoded();
+    let mut data = pseudonym.to_vec();
+    data.extend_from_slice(group_id);
+    let mut sho = Sho::new(b"Signal_ZKGroup_20220301_MembershipToken_CalcM1", &data);
+    [sho.get_point(), Scalar::from(expiration) * system.G_m2]
+}
+
 pub(crate) fn convert_to_point_M2_receipt_serial_bytes(
     receipt_serial_bytes: ReceiptSerialBytes,
 ) -> RistrettoPoint {
@@ -354,6 +393,13 @@ impl<S: AttrScalars> KeyPair<S> {
         }
     }
 
+    /// Returns every secret scalar in the key pair, for sanity checks.
+    pub(crate) fn get_secret_scalars(&self) -> Vec<Scalar> {
+        let mut scalars = vec![self.w, self.wprime, self.x0, self.x1];
+        scalars.extend(self.y.iter());
+        scalars
+    }
+
     pub fn get_public_key(&self) -> PublicKey {
         PublicKey {
             C_W: self.C_W,
@@ -387,9 +433,10 @@ impl KeyPair<AuthCredential> {
         &self,
         uid: uid_struct::UidStruct,
         redemption_time: RedemptionTime,
+        epoch: AuthCredentialEpoch,
         sho: &mut Sho,
     ) -> AuthCredential {
-        let M = convert_to_points_uid_struct(uid, redemption_time);
+        let M = convert_to_points_uid_struct(uid, redemption_time, epoch);
         let (t, U, V) = self.credential_core(&M, sho);
         AuthCredential { t, U, V }
     }
@@ -419,6 +466,53 @@ impl KeyPair<ProfileKeyCredential> {
             S2,
         }
     }
+
+    /// Checks the MAC of an unblinded credential directly, without any proof.
+    pub fn verify_profile_key_credential_mac(
+        &self,
+        credential: ProfileKeyCredential,
+        uid: uid_struct::UidStruct,
+        profile_key: profile_key_struct::ProfileKeyStruct,
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        let M = [uid.M1, uid.M2, profile_key.M3, profile_key.M4];
+
+        let mut V = self.W + (self.x0 + self.x1 * credential.t) * credential.U;
+        for (yn, Mn) in self.y.iter().zip(&M) {
+            V += yn * Mn;
+        }
+        if bool::from(V.ct_eq(&credential.V)) {
+            Ok(())
+        } else {
+            Err(ZkGroupVerificationFailure)
+        }
+    }
+}
+
+impl KeyPair<MembershipToken> {
+    pub fn create_membership_token(
+        &self,
+        M: [RistrettoPoint; 2],
+        sho: &mut Sho,
+    ) -> MembershipToken {
+        let (t, U, V) = self.credential_core(&M, sho);
+        MembershipToken { t, U, V }
+    }
+
+    pub fn verify_membership_token(
+        &self,
+        token: MembershipToken,
+        M: [RistrettoPoint; 2],
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        let mut V = self.W + (self.x0 + self.x1 * token.t) * token.U;
+        for (yn, Mn) in self.y.iter().zip(&M) {
+            V += yn * Mn;
+        }
+        if bool::from(V.ct_eq(&token.V)) {
+            Ok(())
+        } else {
+            Err(ZkGroupVerificationFailure)
+        }
+    }
 }
 
 impl KeyPair<PniCredential> {
@@ -489,6 +583,28 @@ impl BlindedProfileKeyCredentialWithSecretNonce {
     }
 }
 
+impl<S: AttrScalars> ConstantTimeEq for KeyPair<S> {
+    /// Compares the private scalars, from which everything else in the key pair is derived.
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.y.iter().zip(other.y.iter()).fold(
+            self.w.ct_eq(&other.w)
+                & self.wprime.ct_eq(&other.wprime)
+                & self.x0.ct_eq(&other.x0)
+                & self.x1.ct_eq(&other.x1),
+            |equal, (y, other_y)| equal & y.ct_eq(other_y),
+        )
+    }
+}
+
+impl ConstantTimeEq for BlindedProfileKeyCredential {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.t.ct_eq(&other.t)
+            & self.U.ct_eq(&other.U)
+            & self.S1.ct_eq(&other.S1)
+            & self.S2.ct_eq(&other.S2)
+    }
+}
+
 impl BlindedPniCredentialWithSecretNonce {
     pub fn get_blinded_pni_credential(&self) -> BlindedPniCredential {
         BlindedPniCredential {
@@ -532,19 +648,21 @@ mod tests {
 
         let uid_bytes = TEST_ARRAY_16;
         let redemption_time = 37;
-        let uid = uid_struct::UidStruct::new(uid_bytes);
-        let credential = keypair.create_auth_credential(uid, redemption_time, &mut sho);
+        let uid = uid_struct::UidStruct::new(uid_bytes).unwrap();
+        let credential = keypair.create_auth_credential(uid, redemption_time, 0, &mut sho);
         let proof = proofs::AuthCredentialIssuanceProof::new(
             keypair,
             credential,
             uid,
             redemption_time,
+            0,
+            &[],
             &mut sho,
         );
 
         let public_key = keypair.get_public_key();
         proof
-            .verify(public_key, credential, uid, redemption_time)
+            .verify(public_key, credential, uid, redemption_time, 0, &[])
             .unwrap();
 
         let keypair_bytes = bincode::serialize(&keypair).unwrap();
@@ -11,6 +11,7 @@ use crate::common::simple_types::*;
 use crate::crypto::profile_key_struct;
 use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::subtle::{Choice, ConstantTimeEq};
 use serde::{Deserialize, Serialize};
 
 use lazy_static::lazy_static;
@@ -42,6 +43,12 @@ pub struct Commitment {
     pub(crate) J3: RistrettoPoint,
 }
 
+impl ConstantTimeEq for Commitment {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.J1.ct_eq(&other.J1) & self.J2.ct_eq(&other.J2) & self.J3.ct_eq(&other.J3)
+    }
+}
+
 impl SystemParams {
     pub fn generate() -> Self {
         let mut sho = Sho::new(
@@ -92,6 +99,9 @@ impl CommitmentWithSecretNonce {
         }
     }
 
+    // The nonce is deliberately derived from (profile_key, uid) rather than sampled: the server
+    // stores the commitment from the client's profile upload and later checks credential
+    // requests against it, so every client computation of the commitment must agree.
     pub fn calc_j3(profile_key_bytes: ProfileKeyBytes, uid_bytes: UidBytes) -> Scalar {
         let mut combined_array = [0u8; PROFILE_KEY_LEN + UUID_LEN];
         combined_array[..PROFILE_KEY_LEN].copy_from_slice(&profile_key_bytes);
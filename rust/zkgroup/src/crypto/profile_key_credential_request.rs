@@ -185,7 +185,7 @@ mod tests {
             TEST_ARRAY_32_5,
         );
 
-        let uid = uid_encryption::UidStruct::new(uid_bytes);
+        let uid = uid_encryption::UidStruct::new(uid_bytes).unwrap();
         let uid_ciphertext = uid_enc_key_pair.encrypt(uid);
 
         ppp.verify(
@@ -43,6 +43,9 @@ pub struct PublicKey {
     pub(crate) B: RistrettoPoint,
 }
 
+/// Both points are Ristretto points, so they are always in the prime-order group: decoding rejects
+/// every encoding that doesn't represent a group element, including small-order Edwards points,
+/// and there is no cofactor to clear.
 #[derive(Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Ciphertext {
     pub(crate) E_B1: RistrettoPoint,
@@ -15,6 +15,7 @@ use crate::common::constants::*;
 use crate::common::errors::*;
 use crate::common::sho::*;
 use crate::common::simple_types::*;
+use crate::common::verify_metrics::VerifyTimer;
 use crate::crypto::credentials;
 use crate::crypto::profile_key_commitment;
 use crate::crypto::profile_key_credential_request;
@@ -30,6 +31,15 @@ pub struct AuthCredentialIssuanceProof {
     poksho_proof: Vec<u8>,
 }
 
+/// One issuance proof covering several auth credentials for the same uid.
+///
+/// A poksho proof has one response per secret scalar, however many equations it proves, so this
+/// is the same size as a single [`AuthCredentialIssuanceProof`].
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AuthCredentialBatchIssuanceProof {
+    poksho_proof: Vec<u8>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ProfileKeyCredentialRequestProof {
     poksho_proof: Vec<u8>,
@@ -72,6 +82,17 @@ pub struct AuthCredentialPresentationProofV2 {
     poksho_proof: Vec<u8>,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AuthCredentialBarePresentationProof {
+    C_x0: RistrettoPoint,
+    C_x1: RistrettoPoint,
+    C_y1: RistrettoPoint,
+    C_y2: RistrettoPoint,
+    C_y3: RistrettoPoint,
+    C_V: RistrettoPoint,
+    poksho_proof: Vec<u8>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ProfileKeyCredentialPresentationProofV1 {
     C_x0: RistrettoPoint,
@@ -98,6 +119,21 @@ pub struct ProfileKeyCredentialPresentationProofV2 {
     poksho_proof: Vec<u8>,
 }
 
+/// Like [`ProfileKeyCredentialPresentationProofV2`], but without the relations on the profile key
+/// ciphertext, which is not sent.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProfileKeyCredentialHidingPresentationProof {
+    C_x0: RistrettoPoint,
+    C_x1: RistrettoPoint,
+    C_y1: RistrettoPoint,
+    C_y2: RistrettoPoint,
+    C_y3: RistrettoPoint,
+    C_y4: RistrettoPoint,
+    C_V: RistrettoPoint,
+    C_z: RistrettoPoint,
+    poksho_proof: Vec<u8>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct PniCredentialPresentationProofV1 {
     C_x0: RistrettoPoint,
@@ -171,11 +207,13 @@ impl AuthCredentialIssuanceProof {
         credential: credentials::AuthCredential,
         uid: uid_struct::UidStruct,
         redemption_time: RedemptionTime,
+        epoch: AuthCredentialEpoch,
+        personalization: &[u8],
         sho: &mut Sho,
     ) -> Self {
         let system = credentials::SystemParams::get_hardcoded();
 
-        let M = credentials::convert_to_points_uid_struct(uid, redemption_time);
+        let M = credentials::convert_to_points_uid_struct(uid, redemption_time, epoch);
 
         let mut scalar_args = poksho::ScalarArgs::new();
         scalar_args.add("w", key_pair.w);
@@ -207,7 +245,7 @@ impl AuthCredentialIssuanceProof {
             .prove(
                 &scalar_args,
                 &point_args,
-                &[],
+                personalization,
                 &sho.squeeze(RANDOMNESS_LEN)[..],
             )
             .unwrap();
@@ -220,10 +258,12 @@ impl AuthCredentialIssuanceProof {
         credential: credentials::AuthCredential,
         uid_struct: uid_struct::UidStruct,
         redemption_time: RedemptionTime,
+        epoch: AuthCredentialEpoch,
+        personalization: &[u8],
     ) -> Result<(), ZkGroupVerificationFailure> {
         let system = credentials::SystemParams::get_hardcoded();
 
-        let M = credentials::convert_to_points_uid_struct(uid_struct, redemption_time);
+        let M = credentials::convert_to_points_uid_struct(uid_struct, redemption_time, epoch);
 
         let mut point_args = poksho::PointArgs::new();
         point_args.add("C_W", public_key.C_W);
@@ -242,7 +282,138 @@ impl AuthCredentialIssuanceProof {
         point_args.add("M2", M[1]);
         point_args.add("M3", M[2]);
 
-        match Self::get_poksho_statement().verify_proof(&self.poksho_proof, &point_args, &[]) {
+        match Self::get_poksho_statement().verify_proof(
+            &self.poksho_proof,
+            &point_args,
+            personalization,
+        ) {
+            Err(_) => Err(ZkGroupVerificationFailure),
+            Ok(_) => Ok(()),
+        }
+    }
+}
+
+impl AuthCredentialBatchIssuanceProof {
+    /// The [`AuthCredentialIssuanceProof`] statement, with one `V` equation per credential.
+    ///
+    /// Every equation shares the server's secret scalars, so they are all proven with the same
+    /// key.
+    pub fn get_poksho_statement(batch_size: usize) -> poksho::Statement {
+        let mut st = poksho::Statement::new();
+        st.add("C_W", &[("w", "G_w"), ("wprime", "G_wprime")]);
+        st.add(
+            "G_V-I",
+            &[
+                ("x0", "G_x0"),
+                ("x1", "G_x1"),
+                ("y1", "G_y1"),
+                ("y2", "G_y2"),
+                ("y3", "G_y3"),
+            ],
+        );
+        for i in 0..batch_size {
+            st.add(
+                &format!("V{}", i),
+                &[
+                    ("w", "G_w"),
+                    ("x0", &format!("U{}", i)),
+                    ("x1", &format!("tU{}", i)),
+                    ("y1", "M1"),
+                    ("y2", "M2"),
+                    ("y3", &format!("M3_{}", i)),
+                ],
+            );
+        }
+        st
+    }
+
+    fn point_args(
+        public_key: credentials::PublicKey,
+        credentials: &[credentials::AuthCredential],
+        uid: uid_struct::UidStruct,
+        redemption_times: &[RedemptionTime],
+    ) -> poksho::PointArgs {
+        let system = credentials::SystemParams::get_hardcoded();
+
+        let mut point_args = poksho::PointArgs::new();
+        point_args.add("C_W", public_key.C_W);
+        point_args.add("G_w", system.G_w);
+        point_args.add("G_wprime", system.G_wprime);
+        point_args.add("G_V-I", system.G_V - public_key.I);
+        point_args.add("G_x0", system.G_x0);
+        point_args.add("G_x1", system.G_x1);
+        point_args.add("G_y1", system.G_y[1]);
+        point_args.add("G_y2", system.G_y[2]);
+        point_args.add("G_y3", system.G_y[3]);
+        point_args.add("M1", uid.M1);
+        point_args.add("M2", uid.M2);
+        for (i, (credential, &redemption_time)) in
+            credentials.iter().zip(redemption_times).enumerate()
+        {
+            let M = credentials::convert_to_points_uid_struct(uid, redemption_time, 0);
+            point_args.add(&format!("V{}", i), credential.V);
+            point_args.add(&format!("U{}", i), credential.U);
+            point_args.add(&format!("tU{}", i), credential.t * credential.U);
+            point_args.add(&format!("M3_{}", i), M[2]);
+        }
+        point_args
+    }
+
+    /// `credentials` and `redemption_times` must be the same length.
+    pub fn new(
+        key_pair: credentials::KeyPair<credentials::AuthCredential>,
+        credentials: &[credentials::AuthCredential],
+        uid: uid_struct::UidStruct,
+        redemption_times: &[RedemptionTime],
+        personalization: &[u8],
+        sho: &mut Sho,
+    ) -> Self {
+        assert_eq!(credentials.len(), redemption_times.len());
+
+        let mut scalar_args = poksho::ScalarArgs::new();
+        scalar_args.add("w", key_pair.w);
+        scalar_args.add("wprime", key_pair.wprime);
+        scalar_args.add("x0", key_pair.x0);
+        scalar_args.add("x1", key_pair.x1);
+        scalar_args.add("y1", key_pair.y[1]);
+        scalar_args.add("y2", key_pair.y[2]);
+        scalar_args.add("y3", key_pair.y[3]);
+
+        let point_args = Self::point_args(
+            key_pair.get_public_key(),
+            credentials,
+            uid,
+            redemption_times,
+        );
+
+        let poksho_proof = Self::get_poksho_statement(credentials.len())
+            .prove(
+                &scalar_args,
+                &point_args,
+                personalization,
+                &sho.squeeze(RANDOMNESS_LEN)[..],
+            )
+            .unwrap();
+        Self { poksho_proof }
+    }
+
+    pub fn verify(
+        &self,
+        public_key: credentials::PublicKey,
+        credentials: &[credentials::AuthCredential],
+        uid: uid_struct::UidStruct,
+        redemption_times: &[RedemptionTime],
+        personalization: &[u8],
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        if credentials.len() != redemption_times.len() {
+            return Err(ZkGroupVerificationFailure);
+        }
+        let point_args = Self::point_args(public_key, credentials, uid, redemption_times);
+        match Self::get_poksho_statement(credentials.len()).verify_proof(
+            &self.poksho_proof,
+            &point_args,
+            personalization,
+        ) {
             Err(_) => Err(ZkGroupVerificationFailure),
             Ok(_) => Ok(()),
         }
@@ -265,6 +436,7 @@ impl ProfileKeyCredentialRequestProof {
         key_pair: profile_key_credential_request::KeyPair,
         ciphertext: profile_key_credential_request::CiphertextWithSecretNonce,
         commitment: profile_key_commitment::CommitmentWithSecretNonce,
+        personalization: &[u8],
         sho: &mut Sho,
     ) -> ProfileKeyCredentialRequestProof {
         let commitment_system = profile_key_commitment::SystemParams::get_hardcoded();
@@ -290,7 +462,7 @@ impl ProfileKeyCredentialRequestProof {
             .prove(
                 &scalar_args,
                 &point_args,
-                &[],
+                personalization,
                 &sho.squeeze(RANDOMNESS_LEN)[..],
             )
             .unwrap();
@@ -302,6 +474,7 @@ impl ProfileKeyCredentialRequestProof {
         public_key: profile_key_credential_request::PublicKey,
         ciphertext: profile_key_credential_request::Ciphertext,
         commitment: profile_key_commitment::Commitment,
+        personalization: &[u8],
     ) -> Result<(), ZkGroupVerificationFailure> {
         let commitment_system = profile_key_commitment::SystemParams::get_hardcoded();
 
@@ -316,7 +489,11 @@ impl ProfileKeyCredentialRequestProof {
         point_args.add("E2-J2", ciphertext.E2 - commitment.J2);
         point_args.add("-G_j2", -commitment_system.G_j2);
 
-        match Self::get_poksho_statement().verify_proof(&self.poksho_proof, &point_args, &[]) {
+        match Self::get_poksho_statement().verify_proof(
+            &self.poksho_proof,
+            &point_args,
+            personalization,
+        ) {
             Err(_) => Err(ZkGroupVerificationFailure),
             Ok(_) => Ok(()),
         }
@@ -361,6 +538,7 @@ impl ProfileKeyCredentialIssuanceProof {
         request: profile_key_credential_request::Ciphertext,
         blinded_credential: credentials::BlindedProfileKeyCredentialWithSecretNonce,
         uid: uid_struct::UidStruct,
+        personalization: &[u8],
         sho: &mut Sho,
     ) -> Self {
         let credentials_system = credentials::SystemParams::get_hardcoded();
@@ -403,7 +581,7 @@ impl ProfileKeyCredentialIssuanceProof {
             .prove(
                 &scalar_args,
                 &point_args,
-                &[],
+                personalization,
                 &sho.squeeze(RANDOMNESS_LEN)[..],
             )
             .unwrap();
@@ -417,9 +595,10 @@ impl ProfileKeyCredentialIssuanceProof {
         uid_bytes: UidBytes,
         request: profile_key_credential_request::Ciphertext,
         blinded_credential: credentials::BlindedProfileKeyCredential,
+        personalization: &[u8],
     ) -> Result<(), ZkGroupVerificationFailure> {
         let credentials_system = credentials::SystemParams::get_hardcoded();
-        let uid = uid_struct::UidStruct::new(uid_bytes);
+        let uid = uid_struct::UidStruct::new(uid_bytes)?;
 
         let mut point_args = poksho::PointArgs::new();
         point_args.add("C_W", credentials_public_key.C_W);
@@ -444,7 +623,11 @@ impl ProfileKeyCredentialIssuanceProof {
         point_args.add("M1", uid.M1);
         point_args.add("M2", uid.M2);
 
-        match Self::get_poksho_statement().verify_proof(&self.poksho_proof, &point_args, &[]) {
+        match Self::get_poksho_statement().verify_proof(
+            &self.poksho_proof,
+            &point_args,
+            personalization,
+        ) {
             Err(_) => Err(ZkGroupVerificationFailure),
             Ok(_) => Ok(()),
         }
@@ -487,6 +670,7 @@ impl PniCredentialIssuanceProof {
         st
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         key_pair: credentials::KeyPair<credentials::PniCredential>,
         request_public_key: profile_key_credential_request::PublicKey,
@@ -494,6 +678,7 @@ impl PniCredentialIssuanceProof {
         blinded_credential: credentials::BlindedPniCredentialWithSecretNonce,
         uid: uid_struct::UidStruct,
         pni: uid_struct::UidStruct,
+        personalization: &[u8],
         sho: &mut Sho,
     ) -> Self {
         let credentials_system = credentials::SystemParams::get_hardcoded();
@@ -542,13 +727,14 @@ impl PniCredentialIssuanceProof {
             .prove(
                 &scalar_args,
                 &point_args,
-                &[],
+                personalization,
                 &sho.squeeze(RANDOMNESS_LEN)[..],
             )
             .unwrap();
         Self { poksho_proof }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn verify(
         &self,
         credentials_public_key: credentials::PublicKey,
@@ -557,10 +743,11 @@ impl PniCredentialIssuanceProof {
         pni_bytes: UidBytes,
         request: profile_key_credential_request::Ciphertext,
         blinded_credential: credentials::BlindedPniCredential,
+        personalization: &[u8],
     ) -> Result<(), ZkGroupVerificationFailure> {
         let credentials_system = credentials::SystemParams::get_hardcoded();
-        let uid = uid_struct::UidStruct::new(uid_bytes);
-        let pni = uid_struct::UidStruct::new(pni_bytes);
+        let uid = uid_struct::UidStruct::new(uid_bytes)?;
+        let pni = uid_struct::UidStruct::new(pni_bytes)?;
 
         let mut point_args = poksho::PointArgs::new();
         point_args.add("C_W", credentials_public_key.C_W);
@@ -589,7 +776,11 @@ impl PniCredentialIssuanceProof {
         point_args.add("M5", pni.M1);
         point_args.add("M6", pni.M2);
 
-        match Self::get_poksho_statement().verify_proof(&self.poksho_proof, &point_args, &[]) {
+        match Self::get_poksho_statement().verify_proof(
+            &self.poksho_proof,
+            &point_args,
+            personalization,
+        ) {
             Err(_) => Err(ZkGroupVerificationFailure),
             Ok(_) => Ok(()),
         }
@@ -625,6 +816,7 @@ impl ReceiptCredentialIssuanceProof {
         st
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         key_pair: credentials::KeyPair<credentials::ReceiptCredential>,
         request_public_key: receipt_credential_request::PublicKey,
@@ -632,6 +824,7 @@ impl ReceiptCredentialIssuanceProof {
         blinded_credential: credentials::BlindedReceiptCredentialWithSecretNonce,
         receipt_expiration_time: ReceiptExpirationTime,
         receipt_level: ReceiptLevel,
+        personalization: &[u8],
         sho: &mut Sho,
     ) -> Self {
         let credentials_system = credentials::SystemParams::get_hardcoded();
@@ -669,7 +862,7 @@ impl ReceiptCredentialIssuanceProof {
             .prove(
                 &scalar_args,
                 &point_args,
-                &[],
+                personalization,
                 &sho.squeeze(RANDOMNESS_LEN)[..],
             )
             .unwrap();
@@ -683,6 +876,7 @@ impl ReceiptCredentialIssuanceProof {
         request: receipt_credential_request::Ciphertext,
         blinded_credential: credentials::BlindedReceiptCredential,
         receipt_struct: ReceiptStruct,
+        personalization: &[u8],
     ) -> Result<(), ZkGroupVerificationFailure> {
         let credentials_system = credentials::SystemParams::get_hardcoded();
 
@@ -706,7 +900,11 @@ impl ReceiptCredentialIssuanceProof {
         point_args.add("tU", blinded_credential.t * blinded_credential.U);
         point_args.add("M1", M[0]);
 
-        match Self::get_poksho_statement().verify_proof(&self.poksho_proof, &point_args, &[]) {
+        match Self::get_poksho_statement().verify_proof(
+            &self.poksho_proof,
+            &point_args,
+            personalization,
+        ) {
             Err(_) => Err(ZkGroupVerificationFailure),
             Ok(_) => Ok(()),
         }
@@ -726,6 +924,7 @@ impl AuthCredentialPresentationProofV1 {
         st
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         credentials_public_key: credentials::PublicKey,
         uid_enc_key_pair: uid_encryption::KeyPair,
@@ -733,11 +932,13 @@ impl AuthCredentialPresentationProofV1 {
         uid: uid_struct::UidStruct,
         uid_ciphertext: uid_encryption::Ciphertext,
         redemption_time: RedemptionTime,
+        epoch: AuthCredentialEpoch,
+        personalization: &[u8],
         sho: &mut Sho,
     ) -> Self {
         let credentials_system = credentials::SystemParams::get_hardcoded();
         let uid_system = uid_encryption::SystemParams::get_hardcoded();
-        let M = credentials::convert_to_points_uid_struct(uid, redemption_time);
+        let M = credentials::convert_to_points_uid_struct(uid, redemption_time, epoch);
 
         let z = sho.get_scalar();
 
@@ -788,7 +989,7 @@ impl AuthCredentialPresentationProofV1 {
             .prove(
                 &scalar_args,
                 &point_args,
-                &[],
+                personalization,
                 &sho.squeeze(RANDOMNESS_LEN)[..],
             )
             .unwrap();
@@ -810,7 +1011,10 @@ impl AuthCredentialPresentationProofV1 {
         uid_enc_public_key: uid_encryption::PublicKey,
         uid_ciphertext: uid_encryption::Ciphertext,
         redemption_time: RedemptionTime,
+        epoch: AuthCredentialEpoch,
+        personalization: &[u8],
     ) -> Result<(), ZkGroupVerificationFailure> {
+        let _timer = VerifyTimer::start("auth", "v1");
         let enc_system = uid_encryption::SystemParams::get_hardcoded();
         let credentials_system = credentials::SystemParams::get_hardcoded();
 
@@ -835,7 +1039,7 @@ impl AuthCredentialPresentationProofV1 {
             ..
         } = credentials_key_pair;
 
-        let m3 = encode_redemption_time(redemption_time);
+        let m3 = encode_redemption_time_and_epoch(redemption_time, epoch);
         let M3 = m3 * credentials_system.G_m3;
         let Z = C_V - W - x0 * C_x0 - x1 * C_x1 - y1 * C_y1 - y2 * C_y2 - y3 * (C_y3 + M3);
 
@@ -859,7 +1063,8 @@ impl AuthCredentialPresentationProofV1 {
         point_args.add("C_y3", C_y3);
         point_args.add("G_y3", credentials_system.G_y[3]);
 
-        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, &[]) {
+        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, personalization)
+        {
             Err(_) => Err(ZkGroupVerificationFailure),
             Ok(_) => Ok(()),
         }
@@ -878,6 +1083,7 @@ impl AuthCredentialPresentationProofV2 {
         st
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         credentials_public_key: credentials::PublicKey,
         uid_enc_key_pair: uid_encryption::KeyPair,
@@ -885,11 +1091,13 @@ impl AuthCredentialPresentationProofV2 {
         uid: uid_struct::UidStruct,
         uid_ciphertext: uid_encryption::Ciphertext,
         redemption_time: RedemptionTime,
+        epoch: AuthCredentialEpoch,
+        personalization: &[u8],
         sho: &mut Sho,
     ) -> Self {
         let credentials_system = credentials::SystemParams::get_hardcoded();
         let uid_system = uid_encryption::SystemParams::get_hardcoded();
-        let M = credentials::convert_to_points_uid_struct(uid, redemption_time);
+        let M = credentials::convert_to_points_uid_struct(uid, redemption_time, epoch);
 
         let z = sho.get_scalar();
 
@@ -937,7 +1145,7 @@ impl AuthCredentialPresentationProofV2 {
             .prove(
                 &scalar_args,
                 &point_args,
-                &[],
+                personalization,
                 &sho.squeeze(RANDOMNESS_LEN)[..],
             )
             .unwrap();
@@ -959,7 +1167,10 @@ impl AuthCredentialPresentationProofV2 {
         uid_enc_public_key: uid_encryption::PublicKey,
         uid_ciphertext: uid_encryption::Ciphertext,
         redemption_time: RedemptionTime,
+        epoch: AuthCredentialEpoch,
+        personalization: &[u8],
     ) -> Result<(), ZkGroupVerificationFailure> {
+        let _timer = VerifyTimer::start("auth", "v2");
         let enc_system = uid_encryption::SystemParams::get_hardcoded();
         let credentials_system = credentials::SystemParams::get_hardcoded();
 
@@ -984,7 +1195,7 @@ impl AuthCredentialPresentationProofV2 {
             ..
         } = credentials_key_pair;
 
-        let m3 = encode_redemption_time(redemption_time);
+        let m3 = encode_redemption_time_and_epoch(redemption_time, epoch);
         let M3 = m3 * credentials_system.G_m3;
         let Z = C_V - W - x0 * C_x0 - x1 * C_x1 - y1 * C_y1 - y2 * C_y2 - y3 * (C_y3 + M3);
 
@@ -1009,7 +1220,136 @@ impl AuthCredentialPresentationProofV2 {
         point_args.add("G_y3", credentials_system.G_y[3]);
         //point_args.add("0", RistrettoPoint::identity());
 
-        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, &[]) {
+        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, personalization)
+        {
+            Err(_) => Err(ZkGroupVerificationFailure),
+            Ok(_) => Ok(()),
+        }
+    }
+}
+
+// Like AuthCredentialPresentationProofV2, but without the statements tying C_y1/C_y2 to a uid
+// ciphertext: it only shows the holder has a valid MAC on some uid at the given redemption time.
+impl AuthCredentialBarePresentationProof {
+    pub fn get_poksho_statement() -> poksho::Statement {
+        let mut st = poksho::Statement::new();
+
+        st.add("Z", &[("z", "I")]);
+        st.add("C_x1", &[("t", "C_x0"), ("z0", "G_x0"), ("z", "G_x1")]);
+        st.add("C_y3", &[("z", "G_y3")]);
+        st
+    }
+
+    pub fn new(
+        credentials_public_key: credentials::PublicKey,
+        credential: credentials::AuthCredential,
+        uid: uid_struct::UidStruct,
+        redemption_time: RedemptionTime,
+        personalization: &[u8],
+        sho: &mut Sho,
+    ) -> Self {
+        let credentials_system = credentials::SystemParams::get_hardcoded();
+        let M = credentials::convert_to_points_uid_struct(uid, redemption_time, 0);
+
+        let z = sho.get_scalar();
+
+        let C_y1 = z * credentials_system.G_y[1] + M[0];
+        let C_y2 = z * credentials_system.G_y[2] + M[1];
+        let C_y3 = z * credentials_system.G_y[3];
+
+        let C_x0 = z * credentials_system.G_x0 + credential.U;
+        let C_V = z * credentials_system.G_V + credential.V;
+        let C_x1 = z * credentials_system.G_x1 + credential.t * credential.U;
+
+        let z0 = -z * credential.t;
+
+        let I = credentials_public_key.I;
+        let Z = z * I;
+
+        // Scalars listed in order of stmts for debugging
+        let mut scalar_args = poksho::ScalarArgs::new();
+        scalar_args.add("z", z);
+        scalar_args.add("t", credential.t);
+        scalar_args.add("z0", z0);
+
+        // Points listed in order of stmts for debugging
+        let mut point_args = poksho::PointArgs::new();
+        point_args.add("Z", Z);
+        point_args.add("I", I);
+        point_args.add("C_x1", C_x1);
+        point_args.add("C_x0", C_x0);
+        point_args.add("G_x0", credentials_system.G_x0);
+        point_args.add("G_x1", credentials_system.G_x1);
+        point_args.add("C_y3", C_y3);
+        point_args.add("G_y3", credentials_system.G_y[3]);
+
+        let poksho_proof = Self::get_poksho_statement()
+            .prove(
+                &scalar_args,
+                &point_args,
+                personalization,
+                &sho.squeeze(RANDOMNESS_LEN)[..],
+            )
+            .unwrap();
+
+        Self {
+            C_x0,
+            C_x1,
+            C_y1,
+            C_y2,
+            C_y3,
+            C_V,
+            poksho_proof,
+        }
+    }
+
+    pub fn verify(
+        &self,
+        credentials_key_pair: credentials::KeyPair<credentials::AuthCredential>,
+        redemption_time: RedemptionTime,
+        personalization: &[u8],
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        let _timer = VerifyTimer::start("auth", "bare");
+        let credentials_system = credentials::SystemParams::get_hardcoded();
+
+        let Self {
+            C_x0,
+            C_x1,
+            C_y1,
+            C_y2,
+            C_y3,
+            C_V,
+            poksho_proof,
+        } = self;
+
+        let (C_x0, C_x1, C_y1, C_y2, C_y3, C_V) = (*C_x0, *C_x1, *C_y1, *C_y2, *C_y3, *C_V);
+
+        let credentials::KeyPair {
+            W,
+            x0,
+            x1,
+            y: OneBased([y1, y2, y3, ..]),
+            I,
+            ..
+        } = credentials_key_pair;
+
+        let m3 = encode_redemption_time(redemption_time);
+        let M3 = m3 * credentials_system.G_m3;
+        let Z = C_V - W - x0 * C_x0 - x1 * C_x1 - y1 * C_y1 - y2 * C_y2 - y3 * (C_y3 + M3);
+
+        // Points listed in order of stmts for debugging
+        let mut point_args = poksho::PointArgs::new();
+        point_args.add("Z", Z);
+        point_args.add("I", I);
+        point_args.add("C_x1", C_x1);
+        point_args.add("C_x0", C_x0);
+        point_args.add("G_x0", credentials_system.G_x0);
+        point_args.add("G_x1", credentials_system.G_x1);
+        point_args.add("C_y3", C_y3);
+        point_args.add("G_y3", credentials_system.G_y[3]);
+
+        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, personalization)
+        {
             Err(_) => Err(ZkGroupVerificationFailure),
             Ok(_) => Ok(()),
         }
@@ -1041,12 +1381,13 @@ impl ProfileKeyCredentialPresentationProofV1 {
         profile_key_ciphertext: profile_key_encryption::Ciphertext,
         uid_bytes: UidBytes,
         profile_key_bytes: ProfileKeyBytes,
+        personalization: &[u8],
         sho: &mut Sho,
     ) -> Self {
         let credentials_system = credentials::SystemParams::get_hardcoded();
         let uid_system = uid_encryption::SystemParams::get_hardcoded();
         let profile_key_system = profile_key_encryption::SystemParams::get_hardcoded();
-        let uid = uid_struct::UidStruct::new(uid_bytes);
+        let uid = uid_struct::UidStruct::calculate(uid_bytes);
         let profile_key = profile_key_struct::ProfileKeyStruct::new(profile_key_bytes, uid_bytes);
 
         let z = sho.get_scalar();
@@ -1118,7 +1459,7 @@ impl ProfileKeyCredentialPresentationProofV1 {
             .prove(
                 &scalar_args,
                 &point_args,
-                &[],
+                personalization,
                 &sho.squeeze(RANDOMNESS_LEN)[..],
             )
             .unwrap();
@@ -1143,7 +1484,9 @@ impl ProfileKeyCredentialPresentationProofV1 {
         uid_enc_public_key: uid_encryption::PublicKey,
         profile_key_ciphertext: profile_key_encryption::Ciphertext,
         profile_key_enc_public_key: profile_key_encryption::PublicKey,
+        personalization: &[u8],
     ) -> Result<(), ZkGroupVerificationFailure> {
+        let _timer = VerifyTimer::start("profile_key", "v1");
         let uid_enc_system = uid_encryption::SystemParams::get_hardcoded();
         let profile_key_enc_system = profile_key_encryption::SystemParams::get_hardcoded();
         let credentials_system = credentials::SystemParams::get_hardcoded();
@@ -1208,7 +1551,8 @@ impl ProfileKeyCredentialPresentationProofV1 {
         point_args.add("C_y3", C_y3);
         point_args.add("G_y3", credentials_system.G_y[3]);
 
-        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, &[]) {
+        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, personalization)
+        {
             Err(_) => Err(ZkGroupVerificationFailure),
             Ok(_) => Ok(()),
         }
@@ -1249,12 +1593,13 @@ impl ProfileKeyCredentialPresentationProofV2 {
         profile_key_ciphertext: profile_key_encryption::Ciphertext,
         uid_bytes: UidBytes,
         profile_key_bytes: ProfileKeyBytes,
+        personalization: &[u8],
         sho: &mut Sho,
     ) -> Self {
         let credentials_system = credentials::SystemParams::get_hardcoded();
         let uid_system = uid_encryption::SystemParams::get_hardcoded();
         let profile_key_system = profile_key_encryption::SystemParams::get_hardcoded();
-        let uid = uid_struct::UidStruct::new(uid_bytes);
+        let uid = uid_struct::UidStruct::calculate(uid_bytes);
         let profile_key = profile_key_struct::ProfileKeyStruct::new(profile_key_bytes, uid_bytes);
 
         let z = sho.get_scalar();
@@ -1325,7 +1670,7 @@ impl ProfileKeyCredentialPresentationProofV2 {
             .prove(
                 &scalar_args,
                 &point_args,
-                &[],
+                personalization,
                 &sho.squeeze(RANDOMNESS_LEN)[..],
             )
             .unwrap();
@@ -1350,7 +1695,9 @@ impl ProfileKeyCredentialPresentationProofV2 {
         uid_enc_public_key: uid_encryption::PublicKey,
         profile_key_ciphertext: profile_key_encryption::Ciphertext,
         profile_key_enc_public_key: profile_key_encryption::PublicKey,
+        personalization: &[u8],
     ) -> Result<(), ZkGroupVerificationFailure> {
+        let _timer = VerifyTimer::start("profile_key", "v2");
         let uid_enc_system = uid_encryption::SystemParams::get_hardcoded();
         let profile_key_enc_system = profile_key_encryption::SystemParams::get_hardcoded();
         let credentials_system = credentials::SystemParams::get_hardcoded();
@@ -1414,7 +1761,181 @@ impl ProfileKeyCredentialPresentationProofV2 {
         point_args.add("G_y3", credentials_system.G_y[3]);
         point_args.add("0", RistrettoPoint::identity());
 
-        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, &[]) {
+        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, personalization)
+        {
+            Err(_) => Err(ZkGroupVerificationFailure),
+            Ok(_) => Ok(()),
+        }
+    }
+}
+
+impl ProfileKeyCredentialHidingPresentationProof {
+    pub fn get_poksho_statement() -> poksho::Statement {
+        let mut st = poksho::Statement::new();
+        st.add("C_z", &[("z", "G_z")]);
+        st.add("Z", &[("z", "I")]);
+        st.add("C_x1", &[("t", "C_x0"), ("z0", "G_x0"), ("z", "G_x1")]);
+        st.add("A", &[("a1", "G_a1"), ("a2", "G_a2")]);
+        st.add("C_y2-E_A2", &[("z", "G_y2"), ("a2", "-E_A1")]);
+        st.add("E_A1", &[("a1", "C_y1"), ("z1", "G_y1")]);
+        st.add("0", &[("z1", "I"), ("a1", "Z")]);
+        st
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        uid_enc_key_pair: uid_encryption::KeyPair,
+        credentials_public_key: credentials::PublicKey,
+        credential: credentials::ProfileKeyCredential,
+        uid_ciphertext: uid_encryption::Ciphertext,
+        uid_bytes: UidBytes,
+        profile_key_bytes: ProfileKeyBytes,
+        personalization: &[u8],
+        sho: &mut Sho,
+    ) -> Self {
+        let credentials_system = credentials::SystemParams::get_hardcoded();
+        let uid_system = uid_encryption::SystemParams::get_hardcoded();
+        let uid = uid_struct::UidStruct::calculate(uid_bytes);
+        let profile_key = profile_key_struct::ProfileKeyStruct::new(profile_key_bytes, uid_bytes);
+
+        let z = sho.get_scalar();
+
+        // The profile key attributes are still committed to, since the MAC covers them, but
+        // nothing further is proven about them.
+        let C_y1 = z * credentials_system.G_y[1] + uid.M1;
+        let C_y2 = z * credentials_system.G_y[2] + uid.M2;
+        let C_y3 = z * credentials_system.G_y[3] + profile_key.M3;
+        let C_y4 = z * credentials_system.G_y[4] + profile_key.M4;
+
+        let C_x0 = z * credentials_system.G_x0 + credential.U;
+        let C_V = z * credentials_system.G_V + credential.V;
+        let C_x1 = z * credentials_system.G_x1 + credential.t * credential.U;
+        let C_z = z * credentials_system.G_z;
+
+        let z0 = -z * credential.t;
+        let z1 = -z * uid_enc_key_pair.a1;
+
+        let I = credentials_public_key.I;
+        let Z = z * I;
+
+        // Scalars listed in order of stmts for debugging
+        let mut scalar_args = poksho::ScalarArgs::new();
+        scalar_args.add("z", z);
+        scalar_args.add("t", credential.t);
+        scalar_args.add("z0", z0);
+        scalar_args.add("a1", uid_enc_key_pair.a1);
+        scalar_args.add("a2", uid_enc_key_pair.a2);
+        scalar_args.add("z1", z1);
+
+        // Points listed in order of stmts for debugging
+        let mut point_args = poksho::PointArgs::new();
+        point_args.add("C_z", C_z);
+        point_args.add("G_z", credentials_system.G_z);
+        point_args.add("Z", Z);
+        point_args.add("I", I);
+
+        point_args.add("C_x1", C_x1);
+        point_args.add("C_x0", C_x0);
+        point_args.add("G_x0", credentials_system.G_x0);
+        point_args.add("G_x1", credentials_system.G_x1);
+
+        point_args.add("A", uid_enc_key_pair.A);
+        point_args.add("G_a1", uid_system.G_a1);
+        point_args.add("G_a2", uid_system.G_a2);
+
+        point_args.add("C_y2-E_A2", C_y2 - uid_ciphertext.E_A2);
+        point_args.add("G_y2", credentials_system.G_y[2]);
+        point_args.add("-E_A1", -uid_ciphertext.E_A1);
+        point_args.add("E_A1", uid_ciphertext.E_A1);
+        point_args.add("C_y1", C_y1);
+        point_args.add("G_y1", credentials_system.G_y[1]);
+        point_args.add("0", RistrettoPoint::identity());
+
+        let poksho_proof = Self::get_poksho_statement()
+            .prove(
+                &scalar_args,
+                &point_args,
+                personalization,
+                &sho.squeeze(RANDOMNESS_LEN)[..],
+            )
+            .unwrap();
+
+        ProfileKeyCredentialHidingPresentationProof {
+            C_y1,
+            C_y2,
+            C_y3,
+            C_y4,
+            C_x0,
+            C_x1,
+            C_V,
+            C_z,
+            poksho_proof,
+        }
+    }
+
+    pub fn verify(
+        &self,
+        credentials_key_pair: credentials::KeyPair<credentials::ProfileKeyCredential>,
+        uid_ciphertext: uid_encryption::Ciphertext,
+        uid_enc_public_key: uid_encryption::PublicKey,
+        personalization: &[u8],
+    ) -> Result<(), ZkGroupVerificationFailure> {
+        let _timer = VerifyTimer::start("profile_key", "hiding");
+        let uid_enc_system = uid_encryption::SystemParams::get_hardcoded();
+        let credentials_system = credentials::SystemParams::get_hardcoded();
+
+        let Self {
+            C_x0,
+            C_x1,
+            C_y1,
+            C_y2,
+            C_y3,
+            C_y4,
+            C_V,
+            C_z,
+            poksho_proof,
+        } = self;
+
+        let (C_x0, C_x1, C_y1, C_y2, C_y3, C_y4, C_V, C_z) =
+            (*C_x0, *C_x1, *C_y1, *C_y2, *C_y3, *C_y4, *C_V, *C_z);
+
+        let credentials::KeyPair {
+            W,
+            x0,
+            x1,
+            y: OneBased([y1, y2, y3, y4]),
+            I,
+            ..
+        } = credentials_key_pair;
+
+        let Z =
+            C_V - W - x0 * C_x0 - x1 * C_x1 - (y1 * C_y1) - (y2 * C_y2) - (y3 * C_y3) - (y4 * C_y4);
+
+        // Points listed in order of stmts for debugging
+        let mut point_args = poksho::PointArgs::new();
+        point_args.add("C_z", C_z);
+        point_args.add("G_z", credentials_system.G_z);
+        point_args.add("Z", Z);
+        point_args.add("I", I);
+        point_args.add("C_x1", C_x1);
+        point_args.add("C_x0", C_x0);
+        point_args.add("G_x0", credentials_system.G_x0);
+        point_args.add("G_x1", credentials_system.G_x1);
+
+        point_args.add("A", uid_enc_public_key.A);
+        point_args.add("G_a1", uid_enc_system.G_a1);
+        point_args.add("G_a2", uid_enc_system.G_a2);
+
+        point_args.add("C_y2-E_A2", C_y2 - uid_ciphertext.E_A2);
+        point_args.add("G_y2", credentials_system.G_y[2]);
+        point_args.add("-E_A1", -uid_ciphertext.E_A1);
+        point_args.add("E_A1", uid_ciphertext.E_A1);
+        point_args.add("C_y1", C_y1);
+        point_args.add("G_y1", credentials_system.G_y[1]);
+        point_args.add("0", RistrettoPoint::identity());
+
+        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, personalization)
+        {
             Err(_) => Err(ZkGroupVerificationFailure),
             Ok(_) => Ok(()),
         }
@@ -1450,14 +1971,15 @@ impl PniCredentialPresentationProofV1 {
         uid_bytes: UidBytes,
         pni_bytes: UidBytes,
         profile_key_bytes: ProfileKeyBytes,
+        personalization: &[u8],
         sho: &mut Sho,
     ) -> Self {
         let credentials_system = credentials::SystemParams::get_hardcoded();
         let uid_system = uid_encryption::SystemParams::get_hardcoded();
         let profile_key_system = profile_key_encryption::SystemParams::get_hardcoded();
-        let uid = uid_struct::UidStruct::new(uid_bytes);
+        let uid = uid_struct::UidStruct::calculate(uid_bytes);
         let profile_key = profile_key_struct::ProfileKeyStruct::new(profile_key_bytes, uid_bytes);
-        let pni = uid_struct::UidStruct::new(pni_bytes);
+        let pni = uid_struct::UidStruct::calculate(pni_bytes);
 
         let z = sho.get_scalar();
 
@@ -1537,7 +2059,7 @@ impl PniCredentialPresentationProofV1 {
             .prove(
                 &scalar_args,
                 &point_args,
-                &[],
+                personalization,
                 &sho.squeeze(RANDOMNESS_LEN)[..],
             )
             .unwrap();
@@ -1557,6 +2079,7 @@ impl PniCredentialPresentationProofV1 {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn verify(
         &self,
         credentials_key_pair: credentials::KeyPair<credentials::PniCredential>,
@@ -1565,7 +2088,9 @@ impl PniCredentialPresentationProofV1 {
         profile_key_ciphertext: profile_key_encryption::Ciphertext,
         profile_key_enc_public_key: profile_key_encryption::PublicKey,
         pni_ciphertext: uid_encryption::Ciphertext,
+        personalization: &[u8],
     ) -> Result<(), ZkGroupVerificationFailure> {
+        let _timer = VerifyTimer::start("pni", "v1");
         let uid_enc_system = uid_encryption::SystemParams::get_hardcoded();
         let profile_key_enc_system = profile_key_encryption::SystemParams::get_hardcoded();
         let credentials_system = credentials::SystemParams::get_hardcoded();
@@ -1644,7 +2169,8 @@ impl PniCredentialPresentationProofV1 {
         point_args.add("C_y5", *C_y5);
         point_args.add("G_y5", credentials_system.G_y[5]);
 
-        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, &[]) {
+        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, personalization)
+        {
             Err(_) => Err(ZkGroupVerificationFailure),
             Ok(_) => Ok(()),
         }
@@ -1689,14 +2215,15 @@ impl PniCredentialPresentationProofV2 {
         uid_bytes: UidBytes,
         pni_bytes: UidBytes,
         profile_key_bytes: ProfileKeyBytes,
+        personalization: &[u8],
         sho: &mut Sho,
     ) -> Self {
         let credentials_system = credentials::SystemParams::get_hardcoded();
         let uid_system = uid_encryption::SystemParams::get_hardcoded();
         let profile_key_system = profile_key_encryption::SystemParams::get_hardcoded();
-        let uid = uid_struct::UidStruct::new(uid_bytes);
+        let uid = uid_struct::UidStruct::calculate(uid_bytes);
         let profile_key = profile_key_struct::ProfileKeyStruct::new(profile_key_bytes, uid_bytes);
-        let pni = uid_struct::UidStruct::new(pni_bytes);
+        let pni = uid_struct::UidStruct::calculate(pni_bytes);
 
         let z = sho.get_scalar();
 
@@ -1775,7 +2302,7 @@ impl PniCredentialPresentationProofV2 {
             .prove(
                 &scalar_args,
                 &point_args,
-                &[],
+                personalization,
                 &sho.squeeze(RANDOMNESS_LEN)[..],
             )
             .unwrap();
@@ -1795,6 +2322,7 @@ impl PniCredentialPresentationProofV2 {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn verify(
         &self,
         credentials_key_pair: credentials::KeyPair<credentials::PniCredential>,
@@ -1803,7 +2331,9 @@ impl PniCredentialPresentationProofV2 {
         profile_key_ciphertext: profile_key_encryption::Ciphertext,
         profile_key_enc_public_key: profile_key_encryption::PublicKey,
         pni_ciphertext: uid_encryption::Ciphertext,
+        personalization: &[u8],
     ) -> Result<(), ZkGroupVerificationFailure> {
+        let _timer = VerifyTimer::start("pni", "v2");
         let uid_enc_system = uid_encryption::SystemParams::get_hardcoded();
         let profile_key_enc_system = profile_key_encryption::SystemParams::get_hardcoded();
         let credentials_system = credentials::SystemParams::get_hardcoded();
@@ -1881,7 +2411,8 @@ impl PniCredentialPresentationProofV2 {
         point_args.add("G_y5", credentials_system.G_y[5]);
         point_args.add("0", RistrettoPoint::identity());
 
-        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, &[]) {
+        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, personalization)
+        {
             Err(_) => Err(ZkGroupVerificationFailure),
             Ok(_) => Ok(()),
         }
@@ -1902,6 +2433,7 @@ impl ReceiptCredentialPresentationProof {
     pub fn new(
         credentials_public_key: credentials::PublicKey,
         credential: credentials::ReceiptCredential,
+        personalization: &[u8],
         sho: &mut Sho,
     ) -> Self {
         let credentials_system = credentials::SystemParams::get_hardcoded();
@@ -1940,7 +2472,7 @@ impl ReceiptCredentialPresentationProof {
             .prove(
                 &scalar_args,
                 &point_args,
-                &[],
+                personalization,
                 &sho.squeeze(RANDOMNESS_LEN)[..],
             )
             .unwrap();
@@ -1959,7 +2491,9 @@ impl ReceiptCredentialPresentationProof {
         &self,
         credentials_key_pair: credentials::KeyPair<credentials::ReceiptCredential>,
         receipt_struct: ReceiptStruct,
+        personalization: &[u8],
     ) -> Result<(), ZkGroupVerificationFailure> {
+        let _timer = VerifyTimer::start("receipt", "v1");
         let credentials_system = credentials::SystemParams::get_hardcoded();
         let M = credentials::convert_to_points_receipt_struct(receipt_struct);
 
@@ -1997,7 +2531,8 @@ impl ReceiptCredentialPresentationProof {
         point_args.add("G_y1", credentials_system.G_y[1]);
         point_args.add("G_y2", credentials_system.G_y[2]);
 
-        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, &[]) {
+        match Self::get_poksho_statement().verify_proof(poksho_proof, &point_args, personalization)
+        {
             Err(_) => Err(ZkGroupVerificationFailure),
             Ok(_) => Ok(()),
         }
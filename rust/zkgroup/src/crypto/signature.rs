@@ -10,6 +10,7 @@ use crate::common::simple_types::*;
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
 use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::subtle::{Choice, ConstantTimeEq};
 use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
@@ -18,6 +19,12 @@ pub struct KeyPair {
     pub(crate) public_key: RistrettoPoint,
 }
 
+impl ConstantTimeEq for KeyPair {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.signing_key.ct_eq(&other.signing_key)
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PublicKey {
     pub(crate) public_key: RistrettoPoint,
@@ -51,9 +58,49 @@ impl KeyPair {
             public_key: self.public_key,
         }
     }
+
+    /// A key pair for signing in a separate domain, named by `label`.
+    ///
+    /// Signatures made with one key pair do not verify under the other. The derived public key can
+    /// be computed from this key pair's public key alone; see [`PublicKey::derive_domain_key`].
+    pub fn derive_domain_key_pair(&self, label: &[u8]) -> Self {
+        let signing_key = self.signing_key + domain_tweak(label, self.public_key);
+        KeyPair {
+            signing_key,
+            public_key: signing_key * RISTRETTO_BASEPOINT_POINT,
+        }
+    }
+}
+
+/// Binds the public key as well as the label, so that domain keys of different key pairs are
+/// unrelated.
+fn domain_tweak(label: &[u8], public_key: RistrettoPoint) -> Scalar {
+    Sho::new(label, &public_key.compress().to_bytes()).get_scalar()
+}
+
+/// Checks that `signature` is a structurally valid poksho signature: a canonically-encoded
+/// challenge scalar followed by a canonically-encoded response scalar.
+pub fn validate_encoding(signature: &SignatureBytes) -> Result<(), ZkGroupDeserializationFailure> {
+    match poksho::Proof::from_slice(signature) {
+        Some(_) => Ok(()),
+        None => Err(ZkGroupDeserializationFailure),
+    }
 }
 
 impl PublicKey {
+    /// The compressed Ristretto encoding of the key.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.public_key.compress().to_bytes()
+    }
+
+    /// The public half of [`KeyPair::derive_domain_key_pair`].
+    pub fn derive_domain_key(&self, label: &[u8]) -> Self {
+        PublicKey {
+            public_key: self.public_key
+                + domain_tweak(label, self.public_key) * RISTRETTO_BASEPOINT_POINT,
+        }
+    }
+
     // Might return VerificationFailure
     pub fn verify(
         &self,
@@ -100,6 +147,12 @@ mod tests {
             .verify(&message, signature)
             .expect_err("signature verify should have failed");
 
+        // a wrong signature can still be well-formed, but non-canonical scalars are not
+        validate_encoding(&signature).unwrap();
+        let mut malformed = signature;
+        malformed[SIGNATURE_LEN - 1] = 0xff;
+        validate_encoding(&malformed).expect_err("non-canonical scalar should be rejected");
+
         println!("signature = {:#x?}", &signature[..]);
         let signature_result = [
             0xdb, 0x9b, 0xfb, 0xd6, 0x15, 0x26, 0xc3, 0x50, 0xf9, 0xbe, 0x95, 0x17, 0x11, 0x6,
@@ -111,4 +164,27 @@ mod tests {
 
         assert!(signature[..] == signature_result[..]);
     }
+
+    #[test]
+    fn test_domain_key_pair() {
+        let mut sho = Sho::new(b"Test_Domain_Key_Pair", &TEST_ARRAY_32);
+        let key_pair = KeyPair::generate(&mut sho);
+        let domain_key_pair = key_pair.derive_domain_key_pair(b"Test_Domain");
+        let domain_public_key = key_pair.get_public_key().derive_domain_key(b"Test_Domain");
+        assert!(domain_key_pair.get_public_key() == domain_public_key);
+        assert!(domain_public_key != key_pair.get_public_key());
+        assert!(domain_public_key != key_pair.get_public_key().derive_domain_key(b"Test_Other"));
+
+        let message = TEST_ARRAY_32_1;
+        let signature = key_pair.sign(&message, &mut sho);
+        let domain_signature = domain_key_pair.sign(&message, &mut sho);
+        domain_public_key
+            .verify(&message, domain_signature)
+            .unwrap();
+        assert!(domain_public_key.verify(&message, signature).is_err());
+        assert!(key_pair
+            .get_public_key()
+            .verify(&message, domain_signature)
+            .is_err());
+    }
 }
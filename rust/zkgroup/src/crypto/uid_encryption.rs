@@ -38,6 +38,9 @@ pub struct PublicKey {
     pub(crate) A: RistrettoPoint,
 }
 
+/// Both points are Ristretto points, so they are always in the prime-order group: decoding rejects
+/// every encoding that doesn't represent a group element, including small-order Edwards points,
+/// and there is no cofactor to clear.
 #[derive(Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Ciphertext {
     pub(crate) E_A1: RistrettoPoint,
@@ -139,7 +142,7 @@ mod tests {
         let key_pair2: KeyPair = bincode::deserialize(&key_pair_bytes).unwrap();
         assert!(key_pair == key_pair2);
 
-        let uid = uid_struct::UidStruct::new(TEST_ARRAY_16);
+        let uid = uid_struct::UidStruct::new(TEST_ARRAY_16).unwrap();
         let ciphertext = key_pair.encrypt(uid);
 
         // Test serialize / deserialize of Ciphertext
@@ -5,9 +5,11 @@
 
 #![allow(non_snake_case)]
 
+use crate::common::errors::*;
 use crate::common::sho::*;
 use crate::common::simple_types::*;
 use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::traits::Identity;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
 
@@ -21,7 +23,22 @@ pub struct UidStruct {
 pub struct PointDecodeFailure;
 
 impl UidStruct {
-    pub fn new(uid_bytes: UidBytes) -> Self {
+    /// Rejects reserved uids: the all-zero uid (used as a "no member" sentinel) and any uid whose
+    /// points land on the identity.
+    pub fn new(uid_bytes: UidBytes) -> Result<Self, ZkGroupVerificationFailure> {
+        let uid = Self::calculate(uid_bytes);
+        if uid_bytes == UidBytes::default()
+            || uid.M1 == RistrettoPoint::identity()
+            || uid.M2 == RistrettoPoint::identity()
+        {
+            return Err(ZkGroupVerificationFailure);
+        }
+        Ok(uid)
+    }
+
+    /// Computes the struct without rejecting reserved uids, for values that are only being
+    /// encrypted or decrypted rather than credentialed.
+    pub(crate) fn calculate(uid_bytes: UidBytes) -> Self {
         let mut sho = Sho::new(b"Signal_ZKGroup_20200424_UID_CalcM1", &uid_bytes);
         let M1 = sho.get_point();
         let M2 = RistrettoPoint::lizard_encode::<Sha256>(&uid_bytes);
@@ -35,7 +52,7 @@ impl UidStruct {
     pub fn from_M2(M2: RistrettoPoint) -> Result<Self, PointDecodeFailure> {
         match M2.lizard_decode::<Sha256>() {
             None => Err(PointDecodeFailure),
-            Some(bytes) => Ok(Self::new(bytes)),
+            Some(bytes) => Ok(Self::calculate(bytes)),
         }
     }
 
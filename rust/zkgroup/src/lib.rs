@@ -8,7 +8,13 @@
 pub mod api;
 pub mod common;
 pub mod crypto;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub use api::*;
+pub use common::clock::{Clock, FixedClock, SystemClock};
 pub use common::constants::*;
 pub use common::errors::*;
+pub use common::presentation_randomness::{
+    PresentationRandomnessDeriver, RatchetingPresentationSource,
+};
 pub use common::simple_types::*;
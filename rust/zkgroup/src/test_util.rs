@@ -0,0 +1,421 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Helpers for pinning golden bytes in client test suites.
+//!
+//! These run the real server code, so fixtures built from them stay consistent with what a server
+//! holding the same secret params would produce.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api;
+use crate::common::constants::*;
+use crate::common::errors::*;
+use crate::common::sho::Sho;
+use crate::common::simple_types::*;
+use crate::crypto;
+
+/// Returns the serialized `AuthCredentialResponse` that
+/// [`ServerSecretParams::issue_auth_credential`](api::ServerSecretParams::issue_auth_credential)
+/// produces for these inputs.
+///
+/// Panics if `uid` is rejected by issuance.
+pub fn expected_auth_credential_response(
+    server_secret_params: &api::ServerSecretParams,
+    randomness: impl Into<RandomnessBytes>,
+    uid: UidBytes,
+    redemption_time: RedemptionTime,
+) -> Vec<u8> {
+    let randomness = Randomness::new(randomness.into());
+    let response = server_secret_params
+        .issue_auth_credential(randomness, uid, redemption_time)
+        .expect("uid must be valid for issuance");
+    bincode::serialize(&response).expect("can serialize")
+}
+
+/// Returns the serialized `BlindedProfileKeyCredential` that the server's blinding step produces
+/// for these inputs, drawing its randomness from `sho`.
+///
+/// This is only the blinding step of
+/// [`ServerSecretParams::issue_profile_key_credential`](api::ServerSecretParams::issue_profile_key_credential):
+/// the request's proof is not checked and no issuance proof is built, so golden values for the
+/// blinded credential don't change when the proofs do.
+///
+/// Panics if `uid` is rejected by issuance.
+pub fn expected_blinded_profile_key_credential(
+    server_secret_params: &api::ServerSecretParams,
+    sho: &mut Sho,
+    request: &api::profiles::ProfileKeyCredentialRequest,
+    uid: UidBytes,
+) -> Vec<u8> {
+    let uid = crypto::uid_struct::UidStruct::new(uid).expect("uid must be valid for issuance");
+    let blinded_credential = server_secret_params
+        .profile_key_credentials_key_pair
+        .create_blinded_profile_key_credential(uid, request.public_key, request.ciphertext, sho)
+        .get_blinded_profile_key_credential();
+    bincode::serialize(&blinded_credential).expect("can serialize")
+}
+
+/// Every value produced while issuing and presenting a profile key credential, from server setup
+/// through the client's presentation, so a fixture can be captured once and checked again later.
+///
+/// Serialize with bincode to store a fixture; [`replay_and_verify`](Self::replay_and_verify)
+/// re-runs each verification step against the stored values.
+#[derive(Serialize, Deserialize)]
+pub struct LifecycleSnapshot {
+    pub server_secret_params: api::ServerSecretParams,
+    pub group_secret_params: api::groups::GroupSecretParams,
+    pub uid: UidBytes,
+    pub profile_key: api::profiles::ProfileKey,
+    pub request_context: api::profiles::ProfileKeyCredentialRequestContext,
+    pub response: api::profiles::ProfileKeyCredentialResponse,
+    pub credential: api::profiles::ProfileKeyCredential,
+    pub presentation: api::profiles::ProfileKeyCredentialPresentationV2,
+}
+
+impl LifecycleSnapshot {
+    /// Runs the full lifecycle, drawing the randomness for each step from `randomness`.
+    ///
+    /// Panics if any step fails.
+    pub fn capture(
+        server_secret_params: api::ServerSecretParams,
+        group_secret_params: api::groups::GroupSecretParams,
+        randomness: impl Into<RandomnessBytes>,
+        uid: UidBytes,
+        profile_key: api::profiles::ProfileKey,
+    ) -> Self {
+        let randomness = Randomness::new(randomness.into());
+        let mut sho = Sho::new(
+            b"Signal_ZKGroup_20220301_Random_LifecycleSnapshot_Capture",
+            randomness.expose_bytes(),
+        );
+        let mut next_randomness = || -> RandomnessBytes {
+            let mut bytes = [0u8; RANDOMNESS_LEN];
+            bytes.copy_from_slice(&sho.squeeze(RANDOMNESS_LEN));
+            bytes
+        };
+
+        let server_public_params = server_secret_params.get_public_params();
+        let request_context = server_public_params.create_profile_key_credential_request_context(
+            next_randomness(),
+            uid,
+            profile_key,
+        );
+        let response = server_secret_params
+            .issue_profile_key_credential(
+                next_randomness(),
+                &request_context.get_request(),
+                uid,
+                profile_key.get_commitment(uid),
+            )
+            .expect("request must be accepted");
+        let credential = server_public_params
+            .receive_profile_key_credential(&request_context, &response)
+            .expect("response must be accepted");
+        let presentation = server_public_params.create_profile_key_credential_presentation_v2(
+            next_randomness(),
+            group_secret_params,
+            credential,
+        );
+
+        Self {
+            server_secret_params,
+            group_secret_params,
+            uid,
+            profile_key,
+            request_context,
+            response,
+            credential,
+            presentation,
+        }
+    }
+
+    /// Re-runs every verification step of the lifecycle against the captured values.
+    ///
+    /// Panics, naming the step, if any of them fails or disagrees with the captured values.
+    pub fn replay_and_verify(&self) {
+        let server_public_params = self.server_secret_params.get_public_params();
+        let group_public_params = self.group_secret_params.get_public_params();
+
+        self.server_secret_params
+            .issue_profile_key_credential(
+                [0u8; RANDOMNESS_LEN],
+                &self.request_context.get_request(),
+                self.uid,
+                self.profile_key.get_commitment(self.uid),
+            )
+            .expect("request no longer accepted");
+        let credential = server_public_params
+            .receive_profile_key_credential(&self.request_context, &self.response)
+            .expect("response no longer accepted");
+        assert_eq!(
+            bincode::serialize(&credential).expect("can serialize"),
+            bincode::serialize(&self.credential).expect("can serialize"),
+            "received credential differs"
+        );
+        self.server_secret_params
+            .check_profile_key_credential_mac(&self.credential)
+            .expect("credential MAC no longer valid");
+
+        self.server_secret_params
+            .verify_profile_key_credential_presentation_v2(group_public_params, &self.presentation)
+            .expect("presentation no longer verifies");
+        let uid = self
+            .group_secret_params
+            .decrypt_uuid(self.presentation.get_uuid_ciphertext())
+            .expect("uid ciphertext no longer decrypts");
+        assert_eq!(uid, self.uid, "presented uid differs");
+        let profile_key = self
+            .group_secret_params
+            .decrypt_profile_key(self.presentation.get_profile_key_ciphertext(), uid)
+            .expect("profile key ciphertext no longer decrypts");
+        assert_eq!(
+            profile_key.get_bytes(),
+            self.profile_key.get_bytes(),
+            "presented profile key differs"
+        );
+    }
+}
+
+/// Catches the same presentation randomness being used for two different presentations.
+///
+/// Each call to [`check`](Self::check) names the randomness and a `context` describing the
+/// presentation it is for, such as the serialized credential and group params. Using randomness
+/// again with the same context is allowed, since it reproduces the same presentation; using it
+/// with a different context is reported. Only fingerprints are kept, for the most recent
+/// `capacity` distinct randomness values.
+///
+/// In test builds, and with this feature, every `create_*_presentation` method checks its
+/// randomness against a guard kept for the current thread, with the serialized presentation as the
+/// context, and panics on reuse.
+pub struct RandomnessGuard {
+    capacity: usize,
+    contexts: HashMap<[u8; 32], [u8; 32]>,
+    order: VecDeque<[u8; 32]>,
+}
+
+impl RandomnessGuard {
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            capacity,
+            contexts: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn check(
+        &mut self,
+        randomness: impl Into<RandomnessBytes>,
+        context: &[u8],
+    ) -> Result<(), ZkGroupRandomnessReuseFailure> {
+        let randomness = Randomness::new(randomness.into());
+        let key = fingerprint(
+            b"Signal_ZKGroup_20220301_RandomnessGuard_Randomness",
+            randomness.expose_bytes(),
+        );
+        let context = fingerprint(b"Signal_ZKGroup_20220301_RandomnessGuard_Context", context);
+
+        match self.contexts.get(&key) {
+            Some(previous) if *previous == context => Ok(()),
+            Some(_) => Err(ZkGroupRandomnessReuseFailure),
+            None => {
+                if self.order.len() == self.capacity {
+                    if let Some(oldest) = self.order.pop_front() {
+                        self.contexts.remove(&oldest);
+                    }
+                }
+                self.contexts.insert(key, context);
+                self.order.push_back(key);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// How many recent randomness values each thread's presentation guard remembers.
+const PRESENTATION_GUARD_CAPACITY: usize = 1024;
+
+thread_local! {
+    /// `None` while [`allowing_presentation_randomness_reuse`] is running.
+    static PRESENTATION_GUARD: RefCell<Option<RandomnessGuard>> =
+        RefCell::new(Some(RandomnessGuard::new(PRESENTATION_GUARD_CAPACITY)));
+}
+
+/// Runs `f` with the presentation randomness check turned off on this thread.
+///
+/// Only for tests that reuse randomness on purpose, such as those reproducing pinned test vectors.
+pub fn allowing_presentation_randomness_reuse<R>(f: impl FnOnce() -> R) -> R {
+    let guard = PRESENTATION_GUARD.with(|guard| guard.borrow_mut().take());
+    let result = f();
+    PRESENTATION_GUARD.with(|slot| *slot.borrow_mut() = guard);
+    result
+}
+
+/// Called by every presentation constructor with the presentation it made from `randomness`.
+///
+/// Panics if `randomness` already made a different presentation on this thread.
+pub(crate) fn check_presentation_randomness<T: Serialize>(
+    randomness: &Randomness,
+    presentation: &T,
+) {
+    let context = bincode::serialize(presentation).expect("can serialize");
+    PRESENTATION_GUARD
+        .with(|guard| match guard.borrow_mut().as_mut() {
+            Some(guard) => guard.check(*randomness.expose_bytes(), &context),
+            None => Ok(()),
+        })
+        .expect("presentation randomness was reused for a different presentation");
+}
+
+fn fingerprint(label: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut fingerprint = [0u8; 32];
+    fingerprint.copy_from_slice(&Sho::new(label, data).squeeze(32));
+    fingerprint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expected_auth_credential_response() {
+        let server_secret_params = api::ServerSecretParams::generate(TEST_ARRAY_32);
+        let server_public_params = server_secret_params.get_public_params();
+        let bytes = expected_auth_credential_response(
+            &server_secret_params,
+            TEST_ARRAY_32_2,
+            TEST_ARRAY_16,
+            123456,
+        );
+        assert_eq!(AUTH_CREDENTIAL_RESPONSE_LEN, bytes.len());
+
+        let response: api::auth::AuthCredentialResponse = bincode::deserialize(&bytes).unwrap();
+        server_public_params
+            .receive_auth_credential(TEST_ARRAY_16, 123456, &response)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_expected_blinded_profile_key_credential() {
+        let server_secret_params = api::ServerSecretParams::generate(TEST_ARRAY_32);
+        let server_public_params = server_secret_params.get_public_params();
+        let profile_key = api::profiles::ProfileKey::create(TEST_ARRAY_32_1);
+        let context = server_public_params.create_profile_key_credential_request_context(
+            TEST_ARRAY_32_3,
+            TEST_ARRAY_16,
+            profile_key,
+        );
+
+        let blind = |seed: &[u8]| {
+            let mut sho = Sho::new(b"Test_Blinded_Profile_Key_Credential", seed);
+            expected_blinded_profile_key_credential(
+                &server_secret_params,
+                &mut sho,
+                &context.get_request(),
+                TEST_ARRAY_16,
+            )
+        };
+        let bytes = blind(b"");
+        assert_eq!(bytes, blind(b""));
+        assert_ne!(bytes, blind(b"other"));
+
+        let blinded_credential: crypto::credentials::BlindedProfileKeyCredential =
+            bincode::deserialize(&bytes).unwrap();
+        let credential = api::profiles::ProfileKeyCredential {
+            reserved: Default::default(),
+            credential: context
+                .key_pair
+                .decrypt_blinded_profile_key_credential(blinded_credential),
+            uid_bytes: TEST_ARRAY_16,
+            profile_key_bytes: profile_key.get_bytes(),
+        };
+        server_secret_params
+            .check_profile_key_credential_mac(&credential)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_lifecycle_snapshot_round_trip() {
+        let snapshot = LifecycleSnapshot::capture(
+            api::ServerSecretParams::generate(TEST_ARRAY_32),
+            api::groups::GroupSecretParams::generate(TEST_ARRAY_32_1),
+            TEST_ARRAY_32_2,
+            TEST_ARRAY_16,
+            api::profiles::ProfileKey::create(TEST_ARRAY_32_3),
+        );
+        snapshot.replay_and_verify();
+
+        let bytes = bincode::serialize(&snapshot).unwrap();
+        let replayed: LifecycleSnapshot = bincode::deserialize(&bytes).unwrap();
+        replayed.replay_and_verify();
+        assert_eq!(bytes, bincode::serialize(&replayed).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "presentation no longer verifies")]
+    fn test_lifecycle_snapshot_detects_other_group() {
+        let mut snapshot = LifecycleSnapshot::capture(
+            api::ServerSecretParams::generate(TEST_ARRAY_32),
+            api::groups::GroupSecretParams::generate(TEST_ARRAY_32_1),
+            TEST_ARRAY_32_2,
+            TEST_ARRAY_16,
+            api::profiles::ProfileKey::create(TEST_ARRAY_32_3),
+        );
+        snapshot.group_secret_params = api::groups::GroupSecretParams::generate(TEST_ARRAY_32_4);
+        snapshot.replay_and_verify();
+    }
+
+    #[test]
+    fn test_randomness_guard() {
+        let mut guard = RandomnessGuard::new(2);
+        guard.check(TEST_ARRAY_32, b"credential A").unwrap();
+        guard.check(TEST_ARRAY_32, b"credential A").unwrap();
+        assert!(guard.check(TEST_ARRAY_32, b"credential B").is_err());
+        guard.check(TEST_ARRAY_32_1, b"credential B").unwrap();
+
+        // Only the most recent values are remembered.
+        guard.check(TEST_ARRAY_32_2, b"credential C").unwrap();
+        guard.check(TEST_ARRAY_32, b"credential B").unwrap();
+        assert!(guard.check(TEST_ARRAY_32_2, b"credential A").is_err());
+    }
+
+    fn create_two_presentations(randomness: RandomnessBytes) {
+        let server_secret_params = api::ServerSecretParams::generate(TEST_ARRAY_32);
+        let server_public_params = server_secret_params.get_public_params();
+        let group_secret_params = api::groups::GroupSecretParams::generate(TEST_ARRAY_32_1);
+        let response = server_secret_params
+            .issue_auth_credential(TEST_ARRAY_32_2, TEST_ARRAY_16, 123456)
+            .unwrap();
+        let credential = server_public_params
+            .receive_auth_credential(TEST_ARRAY_16, 123456, &response)
+            .unwrap();
+        server_public_params.create_auth_credential_presentation_v1(
+            randomness,
+            group_secret_params,
+            credential,
+        );
+        server_public_params.create_auth_credential_presentation_v2(
+            randomness,
+            group_secret_params,
+            credential,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "presentation randomness was reused")]
+    fn test_presentation_randomness_reuse_detected() {
+        create_two_presentations(TEST_ARRAY_32_5);
+    }
+
+    #[test]
+    fn test_presentation_randomness_reuse_allowed() {
+        allowing_presentation_randomness_reuse(|| create_two_presentations(TEST_ARRAY_32_5));
+    }
+}
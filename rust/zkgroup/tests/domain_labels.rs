@@ -0,0 +1,88 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Audits the Sho domain-separation labels used by the crate.
+//!
+//! Labels are written inline at each `Sho::new` call site, so rather than keeping a separate list
+//! in sync by hand, this scans the crate's sources for every `b"Signal_ZKGroup_..."` literal.
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LABEL_PREFIX: &str = "b\"Signal_ZKGroup_";
+
+fn collect_sources(dir: &Path, out: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_sources(&path, out);
+        } else if path.extension() == Some(OsStr::new("rs")) {
+            out.push(path);
+        }
+    }
+}
+
+fn all_domain_labels() -> Vec<(String, String)> {
+    let src_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let mut sources = Vec::new();
+    collect_sources(&src_dir, &mut sources);
+    sources.sort();
+
+    let mut labels = Vec::new();
+    for path in sources {
+        let contents = fs::read_to_string(&path).unwrap();
+        for (line_index, line) in contents.lines().enumerate() {
+            let mut rest = line;
+            while let Some(start) = rest.find(LABEL_PREFIX) {
+                let literal = &rest[start + 2..];
+                let end = literal.find('"').expect("unterminated label literal");
+                let location = format!("{}:{}", path.display(), line_index + 1);
+                labels.push((literal[..end].to_string(), location));
+                rest = &literal[end + 1..];
+            }
+        }
+    }
+    labels
+}
+
+#[test]
+fn test_domain_labels_are_distinct() {
+    let labels = all_domain_labels();
+    assert!(!labels.is_empty());
+
+    let mut seen: HashMap<&str, &str> = HashMap::new();
+    for (label, location) in &labels {
+        if let Some(previous) = seen.insert(label, location) {
+            panic!(
+                "domain label {:?} is used at both {} and {}",
+                label, previous, location
+            );
+        }
+    }
+}
+
+#[test]
+fn test_domain_labels_are_well_formed() {
+    for (label, location) in all_domain_labels() {
+        let mut parts = label.splitn(4, '_');
+        assert_eq!(parts.next(), Some("Signal"), "{}", location);
+        assert_eq!(parts.next(), Some("ZKGroup"), "{}", location);
+        let date = parts.next().unwrap_or_default();
+        assert!(
+            date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit()),
+            "{} has no YYYYMMDD date: {:?}",
+            location,
+            label
+        );
+        assert!(
+            matches!(parts.next(), Some(name) if !name.is_empty()),
+            "{} has no name: {:?}",
+            location,
+            label
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//
+// Copyright 2022 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Audits the type ids registered for [`zkgroup::Envelope`].
+//!
+//! A public API type that can be serialized but has no type id can't be stored in an envelope, and
+//! nothing else would notice. Rather than keeping a list in sync by hand, this scans the `api`
+//! sources for every public type that implements both `Serialize` and `Deserialize`.
+
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ENVELOPE_SOURCE: &str = "src/api/envelope.rs";
+
+fn collect_sources(dir: &Path, out: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_sources(&path, out);
+        } else if path.extension() == Some(OsStr::new("rs")) {
+            out.push(path);
+        }
+    }
+}
+
+/// Returns the identifier at the start of `text`.
+fn leading_ident(text: &str) -> &str {
+    let end = text
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+/// Every public type under `src/api` that derives or implements both `Serialize` and
+/// `Deserialize`.
+fn serializable_api_types() -> BTreeSet<String> {
+    let api_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/api");
+    let mut sources = Vec::new();
+    collect_sources(&api_dir, &mut sources);
+
+    let mut types = BTreeSet::new();
+    for path in sources {
+        let contents = fs::read_to_string(&path).unwrap();
+        let mut derives_serde = false;
+        for line in contents.lines().map(str::trim) {
+            if line.starts_with("#[derive(") {
+                derives_serde = line.contains("Serialize") && line.contains("Deserialize");
+            } else if let Some(rest) = line
+                .strip_prefix("pub struct ")
+                .or_else(|| line.strip_prefix("pub enum "))
+            {
+                if derives_serde {
+                    types.insert(leading_ident(rest).to_string());
+                }
+                derives_serde = false;
+            } else if let Some(rest) = line.strip_prefix("impl_personalized_serde!(") {
+                types.insert(leading_ident(rest).to_string());
+            } else if let Some(rest) = line.strip_prefix("impl<'de> Deserialize<'de> for ") {
+                // Skips the generic impl inside `impl_personalized_serde!` itself.
+                if !rest.starts_with('$') {
+                    types.insert(leading_ident(rest).to_string());
+                }
+            } else if !line.starts_with("#[") && !line.starts_with("///") {
+                derives_serde = false;
+            }
+        }
+    }
+    types
+}
+
+/// Every `(type, type id)` pair registered in the envelope source, and its `ENVELOPE_TYPE_ID_MAX`.
+fn registered_types() -> (Vec<(String, u16)>, u16) {
+    let contents =
+        fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join(ENVELOPE_SOURCE)).unwrap();
+    let mut registered = Vec::new();
+    let mut current = None;
+    let mut max = None;
+    for line in contents.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("impl EnvelopeContents for ") {
+            current = Some(leading_ident(rest).to_string());
+        } else if let Some(rest) = line.strip_prefix("const TYPE_ID: u16 = ") {
+            if let Some(name) = current.take() {
+                registered.push((name, rest.trim_end_matches(';').parse().unwrap()));
+            }
+        } else if let Some(rest) = line.strip_prefix("const ENVELOPE_TYPE_ID_MAX: u16 = ") {
+            max = Some(rest.trim_end_matches(';').parse().unwrap());
+        }
+    }
+    (registered, max.expect("ENVELOPE_TYPE_ID_MAX not found"))
+}
+
+#[test]
+fn test_serializable_api_types_are_registered() {
+    let (registered, _) = registered_types();
+    let registered: BTreeSet<_> = registered.into_iter().map(|(name, _)| name).collect();
+    let types = serializable_api_types();
+    assert!(types.contains("AuthCredential"));
+    assert!(types.contains("ServerSecretParams"));
+
+    let missing: Vec<_> = types.difference(&registered).collect();
+    assert!(
+        missing.is_empty(),
+        "no envelope type id in {} for {:?}",
+        ENVELOPE_SOURCE,
+        missing
+    );
+}
+
+#[test]
+fn test_envelope_type_ids_are_distinct() {
+    let (registered, max) = registered_types();
+    assert!(!registered.is_empty());
+
+    let mut seen: HashMap<u16, &str> = HashMap::new();
+    for (name, type_id) in &registered {
+        assert!(
+            (1..=max).contains(type_id),
+            "type id {} for {} is outside 1..={}",
+            type_id,
+            name,
+            max
+        );
+        if let Some(previous) = seen.insert(*type_id, name) {
+            panic!(
+                "type id {} is used by both {} and {}",
+                type_id, previous, name
+            );
+        }
+    }
+    assert!(
+        seen.contains_key(&max),
+        "ENVELOPE_TYPE_ID_MAX is {} but no type uses it",
+        max
+    );
+}
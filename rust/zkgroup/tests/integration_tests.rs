@@ -8,6 +8,14 @@ extern crate zkgroup;
 use curve25519_dalek::ristretto::RistrettoPoint;
 use sha2::Sha256;
 
+#[cfg(feature = "test-util")]
+use zkgroup::test_util::allowing_presentation_randomness_reuse;
+
+#[cfg(not(feature = "test-util"))]
+fn allowing_presentation_randomness_reuse<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
+
 #[test]
 fn test_lizard() {
     let p = RistrettoPoint::lizard_encode::<Sha256>(&zkgroup::common::constants::TEST_ARRAY_16);
@@ -309,13 +317,16 @@ fn test_integration_auth() {
     // SERVER
     // Issue credential
     let randomness = zkgroup::TEST_ARRAY_32_2;
-    let auth_credential_response =
-        server_secret_params.issue_auth_credential(randomness, uid, redemption_time);
+    let auth_credential_response = server_secret_params
+        .issue_auth_credential(randomness, uid, redemption_time)
+        .unwrap();
 
     // CLIENT
     let auth_credential = server_public_params
         .receive_auth_credential(uid, redemption_time, &auth_credential_response)
         .unwrap();
+    assert!(auth_credential.uid_matches(uid));
+    assert!(!auth_credential.uid_matches(zkgroup::TEST_ARRAY_16_1));
 
     // Create and decrypt user entry
     let uuid_ciphertext = group_secret_params.encrypt_uuid(uid);
@@ -325,11 +336,14 @@ fn test_integration_auth() {
     // Create and receive presentation
     let randomness = zkgroup::TEST_ARRAY_32_5;
 
-    let presentation_v1 = server_public_params.create_auth_credential_presentation_v1(
-        randomness,
-        group_secret_params,
-        auth_credential,
-    );
+    // The pinned v1 and v2 vectors were made with the same randomness.
+    let presentation_v1 = allowing_presentation_randomness_reuse(|| {
+        server_public_params.create_auth_credential_presentation_v1(
+            randomness,
+            group_secret_params,
+            auth_credential,
+        )
+    });
 
     let presentation_v2 = server_public_params.create_auth_credential_presentation_v2(
         randomness,
@@ -469,11 +483,14 @@ fn test_integration_profile() {
     // Create presentation
     let randomness = zkgroup::TEST_ARRAY_32_5;
 
-    let presentation_v1 = server_public_params.create_profile_key_credential_presentation_v1(
-        randomness,
-        group_secret_params,
-        profile_key_credential,
-    );
+    // The pinned v1 and v2 vectors were made with the same randomness.
+    let presentation_v1 = allowing_presentation_randomness_reuse(|| {
+        server_public_params.create_profile_key_credential_presentation_v1(
+            randomness,
+            group_secret_params,
+            profile_key_credential,
+        )
+    });
 
     let presentation_v2 = server_public_params.create_profile_key_credential_presentation_v2(
         randomness,
@@ -598,11 +615,14 @@ fn test_integration_pni() {
     // Create presentation
     let randomness = zkgroup::TEST_ARRAY_32_5;
 
-    let presentation_v1 = server_public_params.create_pni_credential_presentation_v1(
-        randomness,
-        group_secret_params,
-        pni_credential,
-    );
+    // The pinned v1 and v2 vectors were made with the same randomness.
+    let presentation_v1 = allowing_presentation_randomness_reuse(|| {
+        server_public_params.create_pni_credential_presentation_v1(
+            randomness,
+            group_secret_params,
+            pni_credential,
+        )
+    });
 
     let presentation_v2 = server_public_params.create_pni_credential_presentation_v2(
         randomness,
@@ -672,6 +692,619 @@ fn test_integration_pni() {
     pni_credential_response_bytes.copy_from_slice(&bincode::serialize(&response).unwrap());
 }
 
+#[test]
+fn test_pni_credential_validate() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let aci = zkgroup::TEST_ARRAY_16;
+    let pni = zkgroup::TEST_ARRAY_16_1;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+    let profile_key_commitment = profile_key.get_commitment(aci);
+
+    let context = server_public_params.create_pni_credential_request_context(
+        zkgroup::TEST_ARRAY_32_3,
+        aci,
+        pni,
+        profile_key,
+    );
+    let response = server_secret_params
+        .issue_pni_credential(
+            zkgroup::TEST_ARRAY_32_4,
+            &context.get_request(),
+            aci,
+            pni,
+            profile_key_commitment,
+        )
+        .unwrap();
+    let pni_credential = server_public_params
+        .receive_pni_credential(&context, &response)
+        .unwrap();
+    assert!(pni_credential.validate().is_ok());
+
+    // reserved (1) + t, U, V (3 * 32) + aci (16) + pni (16) + profile key (32)
+    let mut pni_credential_bytes = bincode::serialize(&pni_credential).unwrap();
+    let pni_offset = 1 + 3 * 32 + zkgroup::UUID_LEN;
+    pni_credential_bytes[pni_offset..pni_offset + zkgroup::UUID_LEN].fill(0);
+    let corrupted: zkgroup::profiles::PniCredential =
+        bincode::deserialize(&pni_credential_bytes).unwrap();
+    assert!(corrupted.validate().is_err());
+}
+
+fn receive_auth_credential(
+    server_secret_params: &zkgroup::ServerSecretParams,
+    uid: zkgroup::UidBytes,
+    redemption_time: zkgroup::RedemptionTime,
+    randomness: zkgroup::RandomnessBytes,
+) -> zkgroup::auth::AuthCredential {
+    let response = server_secret_params
+        .issue_auth_credential(randomness, uid, redemption_time)
+        .unwrap();
+    server_secret_params
+        .get_public_params()
+        .receive_auth_credential(uid, redemption_time, &response)
+        .unwrap()
+}
+
+fn receive_profile_key_credential(
+    server_secret_params: &zkgroup::ServerSecretParams,
+    uid: zkgroup::UidBytes,
+    profile_key: zkgroup::profiles::ProfileKey,
+    randomness: zkgroup::RandomnessBytes,
+) -> zkgroup::profiles::ProfileKeyCredential {
+    let server_public_params = server_secret_params.get_public_params();
+    let context = server_public_params.create_profile_key_credential_request_context(
+        randomness,
+        uid,
+        profile_key,
+    );
+    let response = server_secret_params
+        .issue_profile_key_credential(
+            randomness,
+            &context.get_request(),
+            uid,
+            profile_key.get_commitment(uid),
+        )
+        .unwrap();
+    server_public_params
+        .receive_profile_key_credential(&context, &response)
+        .unwrap()
+}
+
+#[test]
+fn test_personalization() {
+    let default_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let empty_salt_params =
+        zkgroup::ServerSecretParams::generate_with_personalization(zkgroup::TEST_ARRAY_32, b"");
+    let salted_params = zkgroup::ServerSecretParams::generate_with_personalization(
+        zkgroup::TEST_ARRAY_32,
+        b"deployment A",
+    );
+    let other_salted_params = default_params.with_personalization(b"deployment B");
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+
+    let present = |server_secret_params: &zkgroup::ServerSecretParams, randomness| {
+        let server_public_params = server_secret_params.get_public_params();
+        let response = server_secret_params
+            .issue_auth_credential(zkgroup::TEST_ARRAY_32_2, uid, redemption_time)
+            .unwrap();
+        let credential = server_public_params
+            .receive_auth_credential(uid, redemption_time, &response)
+            .unwrap();
+        (
+            bincode::serialize(&response).unwrap(),
+            server_public_params.create_auth_credential_presentation(
+                randomness,
+                group_secret_params,
+                credential,
+            ),
+        )
+    };
+
+    // An empty salt produces exactly the same proofs as no salt.
+    let (default_response, _) = present(&default_params, zkgroup::TEST_ARRAY_32_3);
+    let (empty_salt_response, _) = present(&empty_salt_params, zkgroup::TEST_ARRAY_32_3);
+    assert_eq!(default_response, empty_salt_response);
+
+    // Salted issuance proofs don't verify for clients using unsalted public params.
+    let salted_response: zkgroup::auth::AuthCredentialResponse =
+        bincode::deserialize(&present(&salted_params, zkgroup::TEST_ARRAY_32_4).0).unwrap();
+    assert!(default_params
+        .get_public_params()
+        .receive_auth_credential(uid, redemption_time, &salted_response)
+        .is_err());
+    assert!(salted_params
+        .get_public_params()
+        .receive_auth_credential(uid, redemption_time, &salted_response)
+        .is_ok());
+
+    // Salted presentations only verify under the same salt.
+    let (_, presentation) = present(&salted_params, zkgroup::TEST_ARRAY_32_4);
+    salted_params
+        .verify_auth_credential_presentation(group_public_params, &presentation)
+        .unwrap();
+    assert!(other_salted_params
+        .verify_auth_credential_presentation(group_public_params, &presentation)
+        .is_err());
+    assert!(default_params
+        .verify_auth_credential_presentation(group_public_params, &presentation)
+        .is_err());
+}
+
+#[test]
+fn test_personalization_survives_serialization() {
+    use zkgroup::CompactSerializable;
+
+    let default_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let salted_params = default_params.with_personalization(b"deployment A");
+    let other_salted_params = default_params.with_personalization(b"deployment B");
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+
+    // Without a salt the bytes are unchanged.
+    let default_bytes = bincode::serialize(&default_params).unwrap();
+    assert_eq!(zkgroup::SERVER_SECRET_PARAMS_LEN, default_bytes.len());
+    assert_eq!(
+        default_bytes,
+        bincode::serialize(&default_params.with_personalization(b"")).unwrap()
+    );
+    assert_eq!(
+        zkgroup::SERVER_PUBLIC_PARAMS_LEN,
+        bincode::serialize(&default_params.get_public_params())
+            .unwrap()
+            .len()
+    );
+
+    // Every way of storing the salted params keeps the salt.
+    let salted_bytes = bincode::serialize(&salted_params).unwrap();
+    let reloaded_params = [
+        bincode::deserialize::<zkgroup::ServerSecretParams>(&salted_bytes).unwrap(),
+        zkgroup::common::serialization::deserialize_strict(&salted_bytes).unwrap(),
+        zkgroup::ServerSecretParams::deserialize_compact(&salted_params.serialize_compact())
+            .unwrap(),
+        zkgroup::ServerSecretParams::deserialize_extensible(&salted_params.serialize_extensible())
+            .unwrap(),
+        zkgroup::Envelope::wrap(&salted_params)
+            .unwrap::<zkgroup::ServerSecretParams>()
+            .unwrap(),
+    ];
+    // An unsalted reader can't silently drop it.
+    assert!(
+        zkgroup::common::serialization::deserialize_strict::<zkgroup::ServerSecretParams>(
+            &salted_bytes[..zkgroup::SERVER_SECRET_PARAMS_LEN]
+        )
+        .is_err()
+    );
+
+    let salted_public_params: zkgroup::ServerPublicParams =
+        bincode::deserialize(&bincode::serialize(&salted_params.get_public_params()).unwrap())
+            .unwrap();
+    let response = salted_params
+        .issue_auth_credential(zkgroup::TEST_ARRAY_32_2, uid, redemption_time)
+        .unwrap();
+    let credential = salted_public_params
+        .receive_auth_credential(uid, redemption_time, &response)
+        .unwrap();
+    let presentation = salted_public_params.create_auth_credential_presentation(
+        zkgroup::TEST_ARRAY_32_3,
+        group_secret_params,
+        credential,
+    );
+
+    let other_response = other_salted_params
+        .issue_auth_credential(zkgroup::TEST_ARRAY_32_2, uid, redemption_time)
+        .unwrap();
+    let other_credential = other_salted_params
+        .get_public_params()
+        .receive_auth_credential(uid, redemption_time, &other_response)
+        .unwrap();
+    let other_presentation = other_salted_params
+        .get_public_params()
+        .create_auth_credential_presentation(
+            zkgroup::TEST_ARRAY_32_4,
+            group_secret_params,
+            other_credential,
+        );
+
+    for reloaded in &reloaded_params {
+        reloaded
+            .verify_auth_credential_presentation(group_public_params, &presentation)
+            .unwrap();
+        assert!(reloaded
+            .verify_auth_credential_presentation(group_public_params, &other_presentation)
+            .is_err());
+        let auth_only: zkgroup::AuthOnlyServerParams =
+            bincode::deserialize(&bincode::serialize(&reloaded.auth_only()).unwrap()).unwrap();
+        assert!(auth_only
+            .verify_auth_credential_presentation(group_public_params, &other_presentation)
+            .is_err());
+    }
+}
+
+#[test]
+fn test_randomness_wrapper() {
+    let randomness = zkgroup::Randomness::new(zkgroup::TEST_ARRAY_32);
+    assert_eq!(&zkgroup::TEST_ARRAY_32, randomness.expose_bytes());
+
+    let expected = zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let by_value = zkgroup::groups::GroupSecretParams::generate(randomness);
+    assert_eq!(
+        expected.get_group_identifier(),
+        by_value.get_group_identifier()
+    );
+}
+
+#[test]
+fn test_rate_limit_token() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let other_group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_5);
+    let redemption_time = 123456u32;
+
+    let present = |uid, randomness, group_secret_params: zkgroup::groups::GroupSecretParams| {
+        let credential = receive_auth_credential(
+            &server_secret_params,
+            uid,
+            redemption_time,
+            zkgroup::TEST_ARRAY_32_2,
+        );
+        server_public_params
+            .create_auth_credential_presentation_v2(randomness, group_secret_params, credential)
+            .derive_rate_limit_token(group_secret_params.get_public_params())
+    };
+
+    let token = present(
+        zkgroup::TEST_ARRAY_16,
+        zkgroup::TEST_ARRAY_32_3,
+        group_secret_params,
+    );
+    assert_eq!(
+        token,
+        present(
+            zkgroup::TEST_ARRAY_16,
+            zkgroup::TEST_ARRAY_32_4,
+            group_secret_params
+        )
+    );
+    assert_ne!(
+        token,
+        present(
+            zkgroup::TEST_ARRAY_16_1,
+            zkgroup::TEST_ARRAY_32_5,
+            group_secret_params
+        )
+    );
+    assert_ne!(
+        token,
+        present(
+            zkgroup::TEST_ARRAY_16,
+            zkgroup::TEST_ARRAY_32_2,
+            other_group_secret_params
+        )
+    );
+}
+
+#[test]
+fn test_presentation_version_splicing() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+    let uid = zkgroup::TEST_ARRAY_16;
+
+    // Auth V1 and V2 proofs differ in size, so a retagged V1 proof fails to parse.
+    let redemption_time = 123456u32;
+    let auth_credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let presentation_v1 = server_public_params.create_auth_credential_presentation_v1(
+        zkgroup::TEST_ARRAY_32_3,
+        group_secret_params,
+        auth_credential,
+    );
+    let mut spliced = bincode::serialize(&presentation_v1).unwrap();
+    assert!(zkgroup::auth::AnyAuthCredentialPresentation::new(&spliced).is_ok());
+    spliced[0] = zkgroup::PRESENTATION_VERSION_2;
+    assert!(zkgroup::auth::AnyAuthCredentialPresentation::new(&spliced).is_err());
+    assert!(zkgroup::auth::AnyAuthCredentialPresentation::new(&[]).is_err());
+
+    // Profile key V1 and V2 proofs have the same layout, so a retagged V1 proof parses but is
+    // checked against the V2 statement and rejected.
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+    let profile_key_credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_4,
+    );
+    let presentation_v1 = server_public_params.create_profile_key_credential_presentation_v1(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        profile_key_credential,
+    );
+    let mut spliced = bincode::serialize(&presentation_v1).unwrap();
+    let presentation =
+        zkgroup::profiles::AnyProfileKeyCredentialPresentation::new(&spliced).unwrap();
+    server_secret_params
+        .verify_profile_key_credential_presentation(group_public_params, &presentation)
+        .unwrap();
+    spliced[0] = zkgroup::PRESENTATION_VERSION_2;
+    let presentation =
+        zkgroup::profiles::AnyProfileKeyCredentialPresentation::new(&spliced).unwrap();
+    assert!(server_secret_params
+        .verify_profile_key_credential_presentation(group_public_params, &presentation)
+        .is_err());
+}
+
+#[test]
+fn test_auth_only_params() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+    let other_group_public_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_5).get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let auth_credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let presentation = server_public_params.create_auth_credential_presentation(
+        zkgroup::TEST_ARRAY_32_3,
+        group_secret_params,
+        auth_credential,
+    );
+
+    let auth_only_bytes = bincode::serialize(&server_secret_params.auth_only()).unwrap();
+    let auth_only: zkgroup::AuthOnlyServerParams = bincode::deserialize(&auth_only_bytes).unwrap();
+    assert!(auth_only_bytes.len() < bincode::serialize(&server_secret_params).unwrap().len());
+
+    for params in [group_public_params, other_group_public_params] {
+        assert_eq!(
+            server_secret_params
+                .verify_auth_credential_presentation(params, &presentation)
+                .is_ok(),
+            auth_only
+                .verify_auth_credential_presentation(params, &presentation)
+                .is_ok()
+        );
+    }
+    auth_only
+        .verify_auth_credential_presentation(group_public_params, &presentation)
+        .unwrap();
+
+    assert_eq!(
+        1,
+        server_secret_params
+            .verify_auth_credential_presentation_any_group(
+                &[other_group_public_params, group_public_params],
+                &presentation
+            )
+            .unwrap()
+    );
+    assert!(server_secret_params
+        .verify_auth_credential_presentation_any_group(&[other_group_public_params], &presentation)
+        .is_err());
+    assert!(server_secret_params
+        .verify_auth_credential_presentation_any_group(&[], &presentation)
+        .is_err());
+
+    let presentation_v1 = server_public_params.create_auth_credential_presentation_v1(
+        zkgroup::TEST_ARRAY_32_4,
+        group_secret_params,
+        auth_credential,
+    );
+    let presentations: [&dyn zkgroup::VerifiablePresentation; 2] =
+        [&presentation, &presentation_v1];
+    for presentation in presentations {
+        server_secret_params
+            .verify_dyn(group_public_params, presentation)
+            .unwrap();
+        assert!(server_secret_params
+            .verify_dyn(other_group_public_params, presentation)
+            .is_err());
+    }
+
+    let message = b"edge config";
+    let signature = server_secret_params.sign(zkgroup::TEST_ARRAY_32_4, message);
+    auth_only.verify_signature(message, signature).unwrap();
+}
+
+#[test]
+fn test_reserved_uid_rejected() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let randomness = zkgroup::TEST_ARRAY_32_2;
+    let reserved_uid = [0u8; zkgroup::UUID_LEN];
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+
+    assert!(server_secret_params
+        .issue_auth_credential(randomness, reserved_uid, redemption_time)
+        .is_err());
+    let auth_credential_response = server_secret_params
+        .issue_auth_credential(randomness, uid, redemption_time)
+        .unwrap();
+    assert!(server_public_params
+        .receive_auth_credential(reserved_uid, redemption_time, &auth_credential_response)
+        .is_err());
+
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+    let context = server_public_params.create_profile_key_credential_request_context(
+        randomness,
+        reserved_uid,
+        profile_key,
+    );
+    assert!(server_secret_params
+        .issue_profile_key_credential(
+            randomness,
+            &context.get_request(),
+            reserved_uid,
+            profile_key.get_commitment(reserved_uid),
+        )
+        .is_err());
+
+    let context = server_public_params.create_pni_credential_request_context(
+        randomness,
+        uid,
+        reserved_uid,
+        profile_key,
+    );
+    assert!(server_secret_params
+        .issue_pni_credential(
+            randomness,
+            &context.get_request(),
+            uid,
+            reserved_uid,
+            profile_key.get_commitment(uid),
+        )
+        .is_err());
+}
+
+#[test]
+fn test_profile_key_presentation_version() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let other_group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_5);
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+    let credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_3,
+    );
+    let presentation = server_public_params.create_profile_key_credential_presentation_v2(
+        zkgroup::TEST_ARRAY_32_4,
+        group_secret_params,
+        credential,
+    );
+
+    let version = server_secret_params
+        .verify_profile_key_credential_presentation_and_version(group_secret_params, &presentation)
+        .unwrap();
+    assert_eq!(
+        bincode::serialize(&profile_key.get_profile_key_version(uid)).unwrap(),
+        bincode::serialize(&version).unwrap()
+    );
+
+    assert!(server_secret_params
+        .verify_profile_key_credential_presentation_and_version(
+            other_group_secret_params,
+            &presentation,
+        )
+        .is_err());
+
+    let other_presentation = server_public_params.create_profile_key_credential_presentation_v2(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        credential,
+    );
+    assert!(server_secret_params
+        .presentations_same_member(group_secret_params, &presentation, &other_presentation)
+        .unwrap());
+
+    let other_member_credential = receive_profile_key_credential(
+        &server_secret_params,
+        zkgroup::TEST_ARRAY_16_1,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_3,
+    );
+    let other_member_presentation = server_public_params
+        .create_profile_key_credential_presentation_v2(
+            zkgroup::TEST_ARRAY_32_2,
+            group_secret_params,
+            other_member_credential,
+        );
+    assert!(!server_secret_params
+        .presentations_same_member(
+            group_secret_params,
+            &presentation,
+            &other_member_presentation
+        )
+        .unwrap());
+}
+
+#[test]
+fn test_profile_key_equality() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let master_key = zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1);
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::derive_from_master_key(master_key);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+    let other_profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_2);
+
+    let old_credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_3,
+    );
+    let new_credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_4,
+    );
+    let other_credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        other_profile_key,
+        zkgroup::TEST_ARRAY_32_4,
+    );
+
+    let presentation = server_public_params.create_profile_key_equality_presentation(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        old_credential,
+        new_credential,
+    );
+    server_secret_params
+        .verify_profile_key_equality_presentation(group_public_params, &presentation)
+        .unwrap();
+
+    server_secret_params
+        .verify_dyn(group_public_params, &presentation)
+        .unwrap();
+
+    let presentation = server_public_params.create_profile_key_equality_presentation(
+        zkgroup::TEST_ARRAY_32_2,
+        group_secret_params,
+        old_credential,
+        other_credential,
+    );
+    server_secret_params
+        .verify_profile_key_equality_presentation(group_public_params, &presentation)
+        .expect_err("profile keys differ");
+}
+
 #[test]
 fn test_server_sigs() {
     let server_secret_params =
@@ -697,6 +1330,16 @@ fn test_server_sigs() {
     server_public_params
         .verify_signature(&message, signature)
         .unwrap();
+
+    let sig_public_key = server_secret_params.signing_public_key_bytes();
+    assert_eq!(
+        sig_public_key,
+        server_public_params.signing_public_key_bytes()
+    );
+    let public_params_bytes = bincode::serialize(&server_public_params).unwrap();
+    assert!(public_params_bytes
+        .windows(sig_public_key.len())
+        .any(|window| window == sig_public_key));
 }
 
 #[test]
@@ -729,3 +1372,2590 @@ fn test_blob_encryption() {
     }
     assert!(calc_ciphertext_vec == ciphertext_vec);
 }
+
+#[test]
+fn test_auth_credential_epoch_revocation() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let master_key = zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1);
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::derive_from_master_key(master_key);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+
+    let auth_credential_response = server_secret_params
+        .issue_auth_credential_with_epoch(zkgroup::TEST_ARRAY_32_1, uid, redemption_time, 1)
+        .unwrap();
+    let auth_credential = server_public_params
+        .receive_auth_credential_with_epoch(uid, redemption_time, 1, &auth_credential_response)
+        .unwrap();
+    assert_eq!(auth_credential.get_epoch(), 1);
+
+    // The epoch is part of the issuance proof.
+    assert!(server_public_params
+        .receive_auth_credential_with_epoch(uid, redemption_time, 2, &auth_credential_response)
+        .is_err());
+
+    let presentation = server_public_params.create_auth_credential_with_epoch_presentation(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        auth_credential,
+    );
+    assert_eq!(presentation.get_epoch(), 1);
+    assert_eq!(presentation.get_redemption_time(), redemption_time);
+
+    server_secret_params
+        .verify_auth_credential_with_epoch_presentation(group_public_params, &presentation, 0)
+        .unwrap();
+    server_secret_params
+        .verify_auth_credential_with_epoch_presentation(group_public_params, &presentation, 1)
+        .unwrap();
+
+    // After the epoch is bumped, the old presentation is rejected.
+    assert!(server_secret_params
+        .verify_auth_credential_with_epoch_presentation(group_public_params, &presentation, 2)
+        .is_err());
+
+    // Claiming a newer epoch than the credential was issued for breaks the proof.
+    let mut forged_bytes = bincode::serialize(&presentation).unwrap();
+    let epoch_offset = forged_bytes.len() - 4;
+    forged_bytes[epoch_offset..].copy_from_slice(&2u32.to_le_bytes());
+    let forged: zkgroup::auth::AuthCredentialWithEpochPresentation =
+        bincode::deserialize(&forged_bytes).unwrap();
+    assert_eq!(forged.get_epoch(), 2);
+    assert!(server_secret_params
+        .verify_auth_credential_with_epoch_presentation(group_public_params, &forged, 2)
+        .is_err());
+}
+
+#[test]
+fn test_receive_profile_key_credential_checked() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+    let context = server_public_params.create_profile_key_credential_request_context(
+        zkgroup::TEST_ARRAY_32_2,
+        uid,
+        profile_key,
+    );
+    let issue = |randomness| {
+        server_secret_params
+            .issue_profile_key_credential(
+                randomness,
+                &context.get_request(),
+                uid,
+                profile_key.get_commitment(uid),
+            )
+            .unwrap()
+    };
+    let response = issue(zkgroup::TEST_ARRAY_32_3);
+    let other_response = issue(zkgroup::TEST_ARRAY_32_4);
+
+    server_public_params
+        .receive_profile_key_credential_checked(
+            &context,
+            &response,
+            &response.get_blinded_credential(),
+        )
+        .unwrap();
+    assert!(server_public_params
+        .receive_profile_key_credential_checked(
+            &context,
+            &response,
+            &other_response.get_blinded_credential(),
+        )
+        .is_err());
+}
+
+#[test]
+fn test_receive_profile_key_credentials_batch() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+    let uids = [zkgroup::TEST_ARRAY_16, zkgroup::TEST_ARRAY_16_1];
+    let contexts: Vec<_> = uids
+        .iter()
+        .map(|uid| {
+            server_public_params.create_profile_key_credential_request_context(
+                zkgroup::TEST_ARRAY_32_2,
+                *uid,
+                profile_key,
+            )
+        })
+        .collect();
+    let responses: Vec<_> = uids
+        .iter()
+        .zip(&contexts)
+        .map(|(uid, context)| {
+            server_secret_params
+                .issue_profile_key_credential(
+                    zkgroup::TEST_ARRAY_32_3,
+                    &context.get_request(),
+                    *uid,
+                    profile_key.get_commitment(*uid),
+                )
+                .unwrap()
+        })
+        .collect();
+
+    let results = server_public_params.receive_profile_key_credentials_batch(&[
+        (&contexts[0], &responses[0]),
+        (&contexts[1], &responses[1]),
+        // Mismatched: only this entry should fail.
+        (&contexts[0], &responses[1]),
+    ]);
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_ok());
+    assert!(results[2].is_err());
+}
+
+#[test]
+fn test_group_identifier_matches_public_params() {
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+    let group_public_params: zkgroup::groups::GroupPublicParams = bincode::deserialize(
+        &bincode::serialize(&group_secret_params.get_public_params()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        group_secret_params.get_group_identifier(),
+        group_public_params.get_group_identifier()
+    );
+
+    let other_group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_2),
+    );
+    assert_ne!(
+        group_secret_params.get_group_identifier(),
+        other_group_secret_params.get_group_identifier()
+    );
+}
+
+#[test]
+fn test_bare_auth_presentation() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let auth_credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_1,
+    );
+
+    let presentation = server_public_params
+        .create_bare_auth_presentation(zkgroup::TEST_ARRAY_32_2, auth_credential);
+    assert_eq!(presentation.get_redemption_time(), redemption_time);
+    server_secret_params
+        .verify_bare_auth_presentation(&presentation)
+        .unwrap();
+    server_secret_params
+        .auth_only()
+        .verify_bare_auth_presentation(&presentation)
+        .unwrap();
+
+    // A different server's key doesn't accept it.
+    let other_server_secret_params =
+        zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    assert!(other_server_secret_params
+        .verify_bare_auth_presentation(&presentation)
+        .is_err());
+
+    // Neither does a presentation claiming a different redemption time.
+    let mut bytes = bincode::serialize(&presentation).unwrap();
+    let redemption_time_offset = bytes.len() - 4;
+    bytes[redemption_time_offset..].copy_from_slice(&(redemption_time + 1).to_le_bytes());
+    let altered: zkgroup::auth::AuthCredentialBarePresentation =
+        bincode::deserialize(&bytes).unwrap();
+    assert!(server_secret_params
+        .verify_bare_auth_presentation(&altered)
+        .is_err());
+}
+
+#[test]
+fn test_profile_key_commitment_is_deterministic() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+
+    let commitment_bytes = bincode::serialize(&profile_key.get_commitment(uid)).unwrap();
+    assert_eq!(
+        commitment_bytes,
+        bincode::serialize(&profile_key.get_commitment(uid)).unwrap()
+    );
+    assert_ne!(
+        commitment_bytes,
+        bincode::serialize(&profile_key.get_commitment(zkgroup::TEST_ARRAY_16_1)).unwrap()
+    );
+    let other_profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_2);
+    assert_ne!(
+        commitment_bytes,
+        bincode::serialize(&other_profile_key.get_commitment(uid)).unwrap()
+    );
+
+    // Request contexts built from unrelated randomness are all checked against the one stored
+    // commitment.
+    let stored_commitment: zkgroup::profiles::ProfileKeyCommitment =
+        bincode::deserialize(&commitment_bytes).unwrap();
+    for randomness in [zkgroup::TEST_ARRAY_32_3, zkgroup::TEST_ARRAY_32_4] {
+        let context = server_public_params.create_profile_key_credential_request_context(
+            randomness,
+            uid,
+            profile_key,
+        );
+        server_secret_params
+            .issue_profile_key_credential(
+                zkgroup::TEST_ARRAY_32_5,
+                &context.get_request(),
+                uid,
+                stored_commitment,
+            )
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_auth_presentation_with_nonce() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let master_key = zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1);
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::derive_from_master_key(master_key);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let auth_credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+
+    let first_nonce = b"first challenge";
+    let second_nonce = b"second challenge";
+    let presentation = server_public_params.create_auth_credential_presentation_v2_with_nonce(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        auth_credential,
+        first_nonce,
+    );
+
+    server_secret_params
+        .verify_auth_credential_presentation_v2_with_nonce(
+            group_public_params,
+            &presentation,
+            first_nonce,
+        )
+        .unwrap();
+
+    // Replaying the presentation against the next challenge fails.
+    assert!(server_secret_params
+        .verify_auth_credential_presentation_v2_with_nonce(
+            group_public_params,
+            &presentation,
+            second_nonce,
+        )
+        .is_err());
+    assert!(server_secret_params
+        .verify_auth_credential_presentation_v2_with_nonce(group_public_params, &presentation, b"")
+        .is_err());
+
+    // Nonce-bound and plain presentations aren't interchangeable.
+    assert!(server_secret_params
+        .verify_auth_credential_presentation_v2(group_public_params, &presentation)
+        .is_err());
+    let plain_presentation = server_public_params.create_auth_credential_presentation_v2(
+        zkgroup::TEST_ARRAY_32_4,
+        group_secret_params,
+        auth_credential,
+    );
+    assert!(server_secret_params
+        .verify_auth_credential_presentation_v2_with_nonce(
+            group_public_params,
+            &plain_presentation,
+            b"",
+        )
+        .is_err());
+}
+
+#[test]
+fn test_check_profile_key_credential_mac() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+    let credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+
+    server_secret_params
+        .check_profile_key_credential_mac(&credential)
+        .unwrap();
+
+    // After a key rotation the stored credential is no longer valid.
+    let rotated_server_secret_params =
+        zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    assert!(rotated_server_secret_params
+        .check_profile_key_credential_mac(&credential)
+        .is_err());
+}
+
+#[test]
+fn test_verify_auth_credential_presentations_streaming() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let auth_credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_1,
+    );
+
+    let groups: Vec<_> = [zkgroup::TEST_ARRAY_32_2, zkgroup::TEST_ARRAY_32_3]
+        .iter()
+        .map(|master_key| {
+            zkgroup::groups::GroupSecretParams::derive_from_master_key(
+                zkgroup::groups::GroupMasterKey::new(*master_key),
+            )
+        })
+        .collect();
+    // Presentations are produced lazily, one per group, and never collected.
+    let presentations = || {
+        groups
+            .iter()
+            .zip([zkgroup::TEST_ARRAY_32_4, zkgroup::TEST_ARRAY_32_5])
+            .map(|(group_secret_params, randomness)| {
+                let presentation = server_public_params.create_auth_credential_presentation_v2(
+                    randomness,
+                    *group_secret_params,
+                    auth_credential,
+                );
+                let bytes = bincode::serialize(&presentation).unwrap();
+                (
+                    group_secret_params.get_public_params(),
+                    zkgroup::auth::AnyAuthCredentialPresentation::new(&bytes).unwrap(),
+                )
+            })
+    };
+
+    server_secret_params
+        .verify_auth_credential_presentations(presentations())
+        .unwrap();
+    server_secret_params
+        .verify_auth_credential_presentations(std::iter::empty::<(
+            zkgroup::groups::GroupPublicParams,
+            zkgroup::auth::AnyAuthCredentialPresentation,
+        )>())
+        .unwrap();
+
+    // Pairing each presentation with the wrong group fails the whole stream.
+    let wrong_group_public_params = groups[0].get_public_params();
+    assert!(server_secret_params
+        .verify_auth_credential_presentations(
+            presentations().map(|(_, presentation)| (wrong_group_public_params, presentation))
+        )
+        .is_err());
+}
+
+#[test]
+fn test_reencrypt_uuid() {
+    let old_group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+    let new_group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_2),
+    );
+    let uid = zkgroup::TEST_ARRAY_16;
+
+    let old_ciphertext = old_group_secret_params.encrypt_uuid(uid);
+    let new_ciphertext = old_group_secret_params
+        .reencrypt_uuid(old_ciphertext, &new_group_secret_params)
+        .unwrap();
+    assert_eq!(
+        uid,
+        new_group_secret_params
+            .decrypt_uuid(new_ciphertext)
+            .unwrap()
+    );
+    assert_eq!(
+        bincode::serialize(&new_ciphertext).unwrap(),
+        bincode::serialize(&new_group_secret_params.encrypt_uuid(uid)).unwrap()
+    );
+    assert!(old_group_secret_params
+        .decrypt_uuid(new_ciphertext)
+        .is_err());
+
+    // The new ciphertext doesn't belong to the old group, so it can't be re-encrypted from there.
+    assert!(old_group_secret_params
+        .reencrypt_uuid(new_ciphertext, &new_group_secret_params)
+        .is_err());
+}
+
+#[test]
+fn test_verify_and_decode() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let master_key = zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1);
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::derive_from_master_key(master_key);
+    let group_public_params = group_secret_params.get_public_params();
+    let other_group_public_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_2),
+    )
+    .get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let auth_credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_3,
+    );
+    let auth_presentation = server_public_params.create_auth_credential_presentation_v1(
+        zkgroup::TEST_ARRAY_32_4,
+        group_secret_params,
+        auth_credential,
+    );
+
+    let decoded = server_secret_params
+        .verify_and_decode(group_public_params, &auth_presentation)
+        .unwrap();
+    assert_eq!(
+        uid,
+        group_secret_params
+            .decrypt_uuid(decoded.get_uuid_ciphertext().unwrap())
+            .unwrap()
+    );
+    assert!(decoded.get_profile_key_ciphertext().is_none());
+    assert!(decoded.get_pni_ciphertext().is_none());
+    assert_eq!(Some(zkgroup::PRESENTATION_VERSION_1), decoded.get_version());
+    assert!(server_secret_params
+        .verify_and_decode(other_group_public_params, &auth_presentation)
+        .is_err());
+
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_5);
+    let profile_key_credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_3,
+    );
+    let profile_key_presentation = server_public_params
+        .create_profile_key_credential_presentation_v2(
+            zkgroup::TEST_ARRAY_32_5,
+            group_secret_params,
+            profile_key_credential,
+        );
+    let decoded = server_secret_params
+        .verify_and_decode(group_public_params, &profile_key_presentation)
+        .unwrap();
+    assert_eq!(
+        uid,
+        group_secret_params
+            .decrypt_uuid(decoded.get_uuid_ciphertext().unwrap())
+            .unwrap()
+    );
+    assert_eq!(
+        profile_key.get_bytes(),
+        group_secret_params
+            .decrypt_profile_key(decoded.get_profile_key_ciphertext().unwrap(), uid)
+            .unwrap()
+            .get_bytes()
+    );
+    assert!(decoded.get_pni_ciphertext().is_none());
+    assert_eq!(Some(zkgroup::PRESENTATION_VERSION_2), decoded.get_version());
+
+    // Unversioned presentations that need only the group params.
+    let hiding_presentation = server_public_params
+        .create_profile_key_credential_presentation_hiding_pk(
+            zkgroup::TEST_ARRAY_32_2,
+            group_secret_params,
+            profile_key_credential,
+        );
+    let device_tagged_presentation = server_public_params
+        .create_auth_credential_presentation_v2_with_device_tag(
+            zkgroup::TEST_ARRAY_32_1,
+            group_secret_params,
+            auth_credential,
+            b"device",
+        );
+    for presentation in [
+        &hiding_presentation as &dyn zkgroup::VerifiablePresentation,
+        &device_tagged_presentation,
+    ] {
+        let decoded = server_secret_params
+            .verify_and_decode(group_public_params, presentation)
+            .unwrap();
+        assert_eq!(
+            uid,
+            group_secret_params
+                .decrypt_uuid(decoded.get_uuid_ciphertext().unwrap())
+                .unwrap()
+        );
+        assert!(decoded.get_profile_key_ciphertext().is_none());
+        assert_eq!(None, decoded.get_version());
+        assert!(server_secret_params
+            .verify_and_decode(other_group_public_params, presentation)
+            .is_err());
+    }
+
+    let bare_presentation =
+        server_public_params.create_bare_auth_presentation(zkgroup::TEST_ARRAY_32, auth_credential);
+    let decoded = server_secret_params
+        .verify_and_decode(other_group_public_params, &bare_presentation)
+        .unwrap();
+    assert!(decoded.get_uuid_ciphertext().is_none());
+    assert_eq!(None, decoded.get_version());
+}
+
+#[test]
+fn test_server_secret_params_self_check() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    server_secret_params.self_check().unwrap();
+
+    // The serialized params start with a reserved byte, followed by the auth credentials key
+    // pair's w and w' scalars.
+    let serialized = bincode::serialize(&server_secret_params).unwrap();
+
+    let mut zeroed = serialized.clone();
+    zeroed[1..33].copy_from_slice(&[0u8; 32]);
+    let zeroed: zkgroup::ServerSecretParams = bincode::deserialize(&zeroed).unwrap();
+    assert_eq!(
+        "auth_credentials_key_pair",
+        zeroed.self_check().unwrap_err().0
+    );
+
+    let mut duplicated = serialized;
+    duplicated.copy_within(1..33, 33);
+    let duplicated: zkgroup::ServerSecretParams = bincode::deserialize(&duplicated).unwrap();
+    assert!(duplicated.self_check().is_err());
+}
+
+#[test]
+fn test_presentation_with_context_label() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let master_key = zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1);
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::derive_from_master_key(master_key);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_2);
+    let profile_key_credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_3,
+    );
+
+    let presentation = server_public_params
+        .create_profile_key_credential_presentation_v2_with_context(
+            zkgroup::TEST_ARRAY_32_5,
+            group_secret_params,
+            profile_key_credential,
+            Some(b"/v1/groups/"),
+        );
+    server_secret_params
+        .verify_profile_key_credential_presentation_v2_with_context(
+            group_public_params,
+            &presentation,
+            Some(b"/v1/groups/"),
+        )
+        .unwrap();
+    assert!(server_secret_params
+        .verify_profile_key_credential_presentation_v2_with_context(
+            group_public_params,
+            &presentation,
+            Some(b"/v1/groups/joined_at"),
+        )
+        .is_err());
+    assert!(server_secret_params
+        .verify_profile_key_credential_presentation_v2(group_public_params, &presentation)
+        .is_err());
+
+    // Without a label, presentations are unchanged.
+    let unlabeled = server_public_params
+        .create_profile_key_credential_presentation_v2_with_context(
+            zkgroup::TEST_ARRAY_32_4,
+            group_secret_params,
+            profile_key_credential,
+            None,
+        );
+    let plain = server_public_params.create_profile_key_credential_presentation_v2(
+        zkgroup::TEST_ARRAY_32_4,
+        group_secret_params,
+        profile_key_credential,
+    );
+    assert_eq!(
+        bincode::serialize(&unlabeled).unwrap(),
+        bincode::serialize(&plain).unwrap()
+    );
+    assert!(server_secret_params
+        .verify_profile_key_credential_presentation_v2_with_context(
+            group_public_params,
+            &plain,
+            Some(b""),
+        )
+        .is_err());
+}
+
+#[test]
+fn test_verify_full_flow() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+
+    let session = |uid, profile_key, randomness| {
+        let context = server_public_params.create_profile_key_credential_request_context(
+            zkgroup::TEST_ARRAY_32_2,
+            uid,
+            profile_key,
+        );
+        let response = server_secret_params
+            .issue_profile_key_credential(
+                zkgroup::TEST_ARRAY_32_3,
+                &context.get_request(),
+                uid,
+                profile_key.get_commitment(uid),
+            )
+            .unwrap();
+        let credential = server_public_params
+            .receive_profile_key_credential(&context, &response)
+            .unwrap();
+        let presentation = server_public_params.create_profile_key_credential_presentation_v2(
+            randomness,
+            group_secret_params,
+            credential,
+        );
+        let presentation = zkgroup::profiles::AnyProfileKeyCredentialPresentation::new(
+            &bincode::serialize(&presentation).unwrap(),
+        )
+        .unwrap();
+        (context, response, presentation)
+    };
+
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_5);
+    let (context, response, presentation) = session(
+        zkgroup::TEST_ARRAY_16,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_4,
+    );
+    server_secret_params
+        .verify_full_flow(&context, &response, group_secret_params, &presentation)
+        .unwrap();
+
+    // A presentation of someone else's credential is caught.
+    let (_, _, substituted) = session(
+        zkgroup::TEST_ARRAY_16_1,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_5,
+    );
+    server_secret_params
+        .verify_profile_key_credential_presentation(
+            group_secret_params.get_public_params(),
+            &substituted,
+        )
+        .unwrap();
+    assert!(server_secret_params
+        .verify_full_flow(&context, &response, group_secret_params, &substituted)
+        .is_err());
+}
+
+#[test]
+fn test_build_profile_key_issuance_proof() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+    let context = server_public_params.create_profile_key_credential_request_context(
+        zkgroup::TEST_ARRAY_32_2,
+        uid,
+        profile_key,
+    );
+
+    let (proof, blinded_credential) = server_secret_params
+        .build_profile_key_issuance_proof(
+            zkgroup::TEST_ARRAY_32_3,
+            &context.get_request(),
+            uid,
+            profile_key.get_commitment(uid),
+        )
+        .unwrap();
+    let response = server_secret_params
+        .issue_profile_key_credential(
+            zkgroup::TEST_ARRAY_32_3,
+            &context.get_request(),
+            uid,
+            profile_key.get_commitment(uid),
+        )
+        .unwrap();
+
+    // The response is exactly the reserved byte, the blinded credential, and the proof.
+    let composed = bincode::serialize(&(0u8, blinded_credential, proof)).unwrap();
+    assert_eq!(bincode::serialize(&response).unwrap(), composed);
+
+    let composed_response: zkgroup::profiles::ProfileKeyCredentialResponse =
+        bincode::deserialize(&composed).unwrap();
+    server_public_params
+        .receive_profile_key_credential(&context, &composed_response)
+        .unwrap();
+
+    assert!(server_secret_params
+        .build_profile_key_issuance_proof(
+            zkgroup::TEST_ARRAY_32_3,
+            &context.get_request(),
+            uid,
+            zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_4).get_commitment(uid),
+        )
+        .is_err());
+}
+
+#[test]
+fn test_auth_presentation_in_range() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let auth_credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let presentation = server_public_params.create_auth_credential_presentation_v2(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        auth_credential,
+    );
+    let presentation = zkgroup::auth::AnyAuthCredentialPresentation::new(
+        &bincode::serialize(&presentation).unwrap(),
+    )
+    .unwrap();
+
+    let verify = |group_public_params, start_day, end_day| {
+        server_secret_params.verify_auth_credential_presentation_in_range(
+            group_public_params,
+            &presentation,
+            start_day,
+            end_day,
+        )
+    };
+    verify(group_public_params, redemption_time, redemption_time).unwrap();
+    verify(
+        group_public_params,
+        redemption_time - 7,
+        redemption_time + 7,
+    )
+    .unwrap();
+    assert!(matches!(
+        verify(
+            group_public_params,
+            redemption_time + 1,
+            redemption_time + 7
+        ),
+        Err(zkgroup::ZkGroupCheckFailure::RedemptionTimeOutOfRange)
+    ));
+    assert!(matches!(
+        verify(
+            group_public_params,
+            redemption_time - 7,
+            redemption_time - 1
+        ),
+        Err(zkgroup::ZkGroupCheckFailure::RedemptionTimeOutOfRange)
+    ));
+
+    let other_group_public_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_2),
+    )
+    .get_public_params();
+    assert!(matches!(
+        verify(other_group_public_params, redemption_time, redemption_time),
+        Err(zkgroup::ZkGroupCheckFailure::Verification)
+    ));
+}
+
+#[test]
+fn test_auth_presentation_not_future() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let auth_credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let presentation = server_public_params.create_auth_credential_presentation(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        auth_credential,
+    );
+
+    let verify = |now_day| {
+        server_secret_params.verify_auth_credential_presentation_not_future(
+            group_public_params,
+            &presentation,
+            now_day,
+        )
+    };
+    verify(redemption_time).unwrap();
+    verify(redemption_time + 7).unwrap();
+    // The presentation is for now + 1.
+    verify(redemption_time - 1).unwrap();
+    // The presentation is for now + skew + 1.
+    assert!(matches!(
+        verify(redemption_time - zkgroup::REDEMPTION_TIME_FUTURE_SKEW_DAYS - 1),
+        Err(zkgroup::ZkGroupCheckFailure::RedemptionTimeOutOfRange)
+    ));
+}
+
+#[test]
+fn test_profile_key_credential_with_tag() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+    let credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+
+    let key = zkgroup::TEST_ARRAY_32_4;
+    let stored = credential.serialize_with_tag(&key);
+    let restored =
+        zkgroup::profiles::ProfileKeyCredential::deserialize_with_tag(&stored, &key).unwrap();
+    assert_eq!(
+        bincode::serialize(&credential).unwrap(),
+        bincode::serialize(&restored).unwrap()
+    );
+
+    assert!(
+        zkgroup::profiles::ProfileKeyCredential::deserialize_with_tag(
+            &stored,
+            &zkgroup::TEST_ARRAY_32_5
+        )
+        .is_err()
+    );
+    for i in [0, stored.len() / 2, stored.len() - 1] {
+        let mut tampered = stored.clone();
+        tampered[i] ^= 1;
+        assert!(
+            zkgroup::profiles::ProfileKeyCredential::deserialize_with_tag(&tampered, &key).is_err()
+        );
+    }
+    assert!(
+        zkgroup::profiles::ProfileKeyCredential::deserialize_with_tag(
+            &stored[..stored.len() - 1],
+            &key
+        )
+        .is_err()
+    );
+    assert!(zkgroup::profiles::ProfileKeyCredential::deserialize_with_tag(&[], &key).is_err());
+}
+
+#[test]
+fn test_profile_key_credential_presentation_hiding_pk() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_2);
+    let credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_3,
+    );
+
+    let presentation = server_public_params.create_profile_key_credential_presentation_hiding_pk(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        credential,
+    );
+    server_secret_params
+        .verify_profile_key_credential_presentation_hiding_pk(group_public_params, &presentation)
+        .unwrap();
+    assert_eq!(
+        uid,
+        group_secret_params
+            .decrypt_uuid(presentation.get_uuid_ciphertext())
+            .unwrap()
+    );
+
+    // The presentation carries no profile key ciphertext, in any form.
+    let full_presentation = server_public_params.create_profile_key_credential_presentation_v2(
+        zkgroup::TEST_ARRAY_32_4,
+        group_secret_params,
+        credential,
+    );
+    let presentation_bytes = bincode::serialize(&presentation).unwrap();
+    let profile_key_ciphertext =
+        bincode::serialize(&full_presentation.get_profile_key_ciphertext()).unwrap();
+    assert!(!presentation_bytes
+        .windows(profile_key_ciphertext.len() - 1)
+        .any(|w| w == &profile_key_ciphertext[1..]));
+    assert!(presentation_bytes.len() < bincode::serialize(&full_presentation).unwrap().len());
+
+    let other_group_public_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_2),
+    )
+    .get_public_params();
+    assert!(server_secret_params
+        .verify_profile_key_credential_presentation_hiding_pk(
+            other_group_public_params,
+            &presentation
+        )
+        .is_err());
+}
+
+#[test]
+fn test_group_params_index() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let group_secret_params: Vec<_> = [zkgroup::TEST_ARRAY_32_1, zkgroup::TEST_ARRAY_32_2]
+        .iter()
+        .map(|master_key| {
+            zkgroup::groups::GroupSecretParams::derive_from_master_key(
+                zkgroup::groups::GroupMasterKey::new(*master_key),
+            )
+        })
+        .collect();
+    let index: zkgroup::groups::GroupParamsIndex = group_secret_params
+        .iter()
+        .map(|params| params.get_public_params())
+        .collect();
+    assert_eq!(2, index.len());
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let auth_credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_3,
+    );
+    let presentation = server_public_params.create_auth_credential_presentation_v2(
+        zkgroup::TEST_ARRAY_32_4,
+        group_secret_params[1],
+        auth_credential,
+    );
+    let presentation = zkgroup::auth::AnyAuthCredentialPresentation::new(
+        &bincode::serialize(&presentation).unwrap(),
+    )
+    .unwrap();
+
+    let group_id = group_secret_params[1].get_group_identifier();
+    index
+        .verify_auth_presentation(&server_secret_params, &group_id, &presentation)
+        .unwrap();
+    assert!(matches!(
+        index.verify_auth_presentation(
+            &server_secret_params,
+            &group_secret_params[0].get_group_identifier(),
+            &presentation
+        ),
+        Err(zkgroup::ZkGroupCheckFailure::Verification)
+    ));
+    assert!(matches!(
+        index.verify_auth_presentation(&server_secret_params, &[0u8; 32], &presentation),
+        Err(zkgroup::ZkGroupCheckFailure::UnknownGroup)
+    ));
+}
+
+#[test]
+fn test_credential_log_id() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let receive = |randomness| {
+        let response = server_secret_params
+            .issue_auth_credential(randomness, uid, redemption_time)
+            .unwrap();
+        server_public_params
+            .receive_auth_credential(uid, redemption_time, &response)
+            .unwrap()
+    };
+    let credential = receive(zkgroup::TEST_ARRAY_32_1);
+    let restored: zkgroup::auth::AuthCredential =
+        bincode::deserialize(&bincode::serialize(&credential).unwrap()).unwrap();
+    assert_eq!(credential.credential_log_id(), restored.credential_log_id());
+
+    // A second credential for the same uid gets a different id.
+    let other_credential = receive(zkgroup::TEST_ARRAY_32_2);
+    assert_ne!(
+        credential.credential_log_id(),
+        other_credential.credential_log_id()
+    );
+
+    // The id doesn't contain the uid.
+    let log_id = credential.credential_log_id();
+    assert_ne!(&log_id[..], &uid[..]);
+
+    // Nor does it depend on the secret scalar t, which follows the version byte.
+    let mut bytes = bincode::serialize(&credential).unwrap();
+    bytes[1..33].copy_from_slice(&[0u8; 32]);
+    let without_t: zkgroup::auth::AuthCredential = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(log_id, without_t.credential_log_id());
+}
+
+#[test]
+fn test_verify_profile_key_credential_presentation_multi() {
+    let old_server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let new_server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let unrelated_server_secret_params =
+        zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32_2);
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_3),
+    );
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_4);
+    let credential = receive_profile_key_credential(
+        &old_server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_5,
+    );
+    let presentation = old_server_secret_params
+        .get_public_params()
+        .create_profile_key_credential_presentation_v2(
+            zkgroup::TEST_ARRAY_32_5,
+            group_secret_params,
+            credential,
+        );
+    let presentation = zkgroup::profiles::AnyProfileKeyCredentialPresentation::new(
+        &bincode::serialize(&presentation).unwrap(),
+    )
+    .unwrap();
+
+    assert!(new_server_secret_params
+        .verify_profile_key_credential_presentation(group_public_params, &presentation)
+        .is_err());
+    new_server_secret_params
+        .verify_profile_key_credential_presentation_multi(
+            group_public_params,
+            &presentation,
+            &[&unrelated_server_secret_params, &old_server_secret_params],
+        )
+        .unwrap();
+    old_server_secret_params
+        .verify_profile_key_credential_presentation_multi(group_public_params, &presentation, &[])
+        .unwrap();
+    assert!(new_server_secret_params
+        .verify_profile_key_credential_presentation_multi(
+            group_public_params,
+            &presentation,
+            &[&unrelated_server_secret_params],
+        )
+        .is_err());
+}
+
+#[test]
+fn test_verify_profile_key_credential_presentation_allowed() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_2);
+    let other_profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_3);
+    let credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_4,
+    );
+    let presentation = server_public_params.create_profile_key_credential_presentation(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        credential,
+    );
+
+    let verify = |group_secret_params, allowed: &[zkgroup::profiles::ProfileKeyVersion]| {
+        server_secret_params.verify_profile_key_credential_presentation_allowed(
+            group_secret_params,
+            &presentation,
+            allowed,
+        )
+    };
+    let version = profile_key.get_profile_key_version(uid);
+    let other_version = other_profile_key.get_profile_key_version(uid);
+    verify(group_secret_params, &[other_version, version]).unwrap();
+    assert!(matches!(
+        verify(group_secret_params, &[other_version]),
+        Err(zkgroup::ZkGroupCheckFailure::ProfileKeyVersionNotAllowed)
+    ));
+    assert!(matches!(
+        verify(group_secret_params, &[]),
+        Err(zkgroup::ZkGroupCheckFailure::ProfileKeyVersionNotAllowed)
+    ));
+
+    let other_group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_2),
+    );
+    assert!(matches!(
+        verify(other_group_secret_params, &[version]),
+        Err(zkgroup::ZkGroupCheckFailure::Verification)
+    ));
+}
+
+#[test]
+fn test_server_secret_params_extensible_serialization() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let legacy_bytes = bincode::serialize(&server_secret_params).unwrap();
+    let bytes = server_secret_params.serialize_extensible();
+
+    let round_tripped = zkgroup::ServerSecretParams::deserialize_extensible(&bytes).unwrap();
+    assert_eq!(legacy_bytes, bincode::serialize(&round_tripped).unwrap());
+
+    // An entry from a newer version is skipped.
+    let mut with_unknown_entry = bytes.clone();
+    with_unknown_entry.extend_from_slice(&[0xff, 0, 0, 0, 3, 1, 2, 3]);
+    let round_tripped =
+        zkgroup::ServerSecretParams::deserialize_extensible(&with_unknown_entry).unwrap();
+    assert_eq!(legacy_bytes, bincode::serialize(&round_tripped).unwrap());
+
+    // Every key pair known to this version is required.
+    let first_entry_len = 5 + u32::from_be_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]) as usize;
+    assert!(
+        zkgroup::ServerSecretParams::deserialize_extensible(&bytes[first_entry_len..]).is_err()
+    );
+
+    let mut repeated_entry = bytes.clone();
+    repeated_entry.extend_from_slice(&bytes[..first_entry_len]);
+    assert!(zkgroup::ServerSecretParams::deserialize_extensible(&repeated_entry).is_err());
+
+    assert!(
+        zkgroup::ServerSecretParams::deserialize_extensible(&bytes[..bytes.len() - 1]).is_err()
+    );
+    assert!(zkgroup::ServerSecretParams::deserialize_extensible(&legacy_bytes).is_err());
+}
+
+#[test]
+fn test_multi_presentation_verifier() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let auth_credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let auth_presentation = server_public_params.create_auth_credential_presentation(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        auth_credential,
+    );
+
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_3);
+    let profile_key_credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_4,
+    );
+    let profile_key_presentation = server_public_params.create_profile_key_credential_presentation(
+        zkgroup::TEST_ARRAY_32_1,
+        group_secret_params,
+        profile_key_credential,
+    );
+
+    let present_receipt = |server_secret_params: &zkgroup::ServerSecretParams, randomness| {
+        let server_public_params = server_secret_params.get_public_params();
+        let context = server_public_params
+            .create_receipt_credential_request_context(zkgroup::TEST_ARRAY_32_2, [0x84u8; 16]);
+        let response = server_secret_params.issue_receipt_credential(
+            zkgroup::TEST_ARRAY_32_3,
+            &context.get_request(),
+            86400 * 30,
+            3,
+        );
+        let credential = server_public_params
+            .receive_receipt_credential(&context, &response)
+            .unwrap();
+        server_public_params.create_receipt_credential_presentation(randomness, &credential)
+    };
+    let receipt_presentation = present_receipt(&server_secret_params, zkgroup::TEST_ARRAY_32_4);
+
+    let verifier =
+        zkgroup::MultiPresentationVerifier::new(&server_secret_params, group_public_params);
+    verifier
+        .verify(
+            &auth_presentation,
+            &profile_key_presentation,
+            &receipt_presentation,
+        )
+        .unwrap();
+
+    let other_server_secret_params =
+        zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let failure = verifier
+        .verify(
+            &auth_presentation,
+            &profile_key_presentation,
+            &present_receipt(&other_server_secret_params, zkgroup::TEST_ARRAY_32_3),
+        )
+        .unwrap_err();
+    assert!(matches!(
+        failure,
+        zkgroup::ZkGroupCheckFailure::Presentations {
+            auth: false,
+            profile_key: false,
+            receipt: true,
+        }
+    ));
+
+    let other_group_public_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_2),
+    )
+    .get_public_params();
+    let failure =
+        zkgroup::MultiPresentationVerifier::new(&server_secret_params, other_group_public_params)
+            .verify(
+                &auth_presentation,
+                &profile_key_presentation,
+                &receipt_presentation,
+            )
+            .unwrap_err();
+    assert!(matches!(
+        failure,
+        zkgroup::ZkGroupCheckFailure::Presentations {
+            auth: true,
+            profile_key: true,
+            receipt: false,
+        }
+    ));
+}
+
+#[test]
+fn test_auth_credential_non_membership() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let auth_credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let presentation = server_public_params.create_auth_credential_presentation(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        auth_credential,
+    );
+
+    let other_member = group_secret_params.encrypt_uuid(zkgroup::TEST_ARRAY_16_1);
+    let verify = |group_public_params, members: &[zkgroup::groups::UuidCiphertext]| {
+        server_secret_params.verify_auth_credential_non_membership(
+            group_public_params,
+            &presentation,
+            members,
+        )
+    };
+    verify(group_public_params, &[]).unwrap();
+    verify(group_public_params, &[other_member]).unwrap();
+    assert!(matches!(
+        verify(
+            group_public_params,
+            &[other_member, group_secret_params.encrypt_uuid(uid)]
+        ),
+        Err(zkgroup::ZkGroupCheckFailure::Member)
+    ));
+
+    let other_group_public_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_2),
+    )
+    .get_public_params();
+    assert!(matches!(
+        verify(other_group_public_params, &[other_member]),
+        Err(zkgroup::ZkGroupCheckFailure::Verification)
+    ));
+}
+
+#[test]
+fn test_verify_profile_key_credential_presentation_and_decrypt_key() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_2);
+    let credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_3,
+    );
+    let presentation = server_public_params.create_profile_key_credential_presentation(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        credential,
+    );
+
+    let decrypted_profile_key = server_secret_params
+        .verify_profile_key_credential_presentation_and_decrypt_key(
+            group_secret_params,
+            &presentation,
+        )
+        .unwrap();
+    assert_eq!(profile_key.get_bytes(), decrypted_profile_key.get_bytes());
+
+    let other_group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_2),
+    );
+    assert!(server_secret_params
+        .verify_profile_key_credential_presentation_and_decrypt_key(
+            other_group_secret_params,
+            &presentation,
+        )
+        .is_err());
+}
+
+#[test]
+fn test_auth_credential_timestamped_presentation() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let auth_credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+
+    let client_time = 1_650_000_000u64;
+    let window = 300;
+    let presentation = server_public_params.create_auth_credential_presentation_v2_timestamped(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        auth_credential,
+        client_time,
+    );
+    assert_eq!(client_time, presentation.get_client_time());
+
+    let verify = |presentation: &zkgroup::auth::AuthCredentialTimestampedPresentation,
+                  server_time| {
+        server_secret_params.verify_auth_credential_timestamped_presentation(
+            group_public_params,
+            presentation,
+            server_time,
+            window,
+        )
+    };
+    verify(&presentation, client_time).unwrap();
+    verify(&presentation, client_time + window - 1).unwrap();
+    verify(&presentation, client_time - window + 1).unwrap();
+    assert!(matches!(
+        verify(&presentation, client_time + window),
+        Err(zkgroup::ZkGroupCheckFailure::ClientTimeOutOfWindow)
+    ));
+    assert!(matches!(
+        verify(&presentation, client_time - window),
+        Err(zkgroup::ZkGroupCheckFailure::ClientTimeOutOfWindow)
+    ));
+
+    // The client time is covered by the proof, so it can't be moved forward after the fact.
+    let mut presentation_bytes = bincode::serialize(&presentation).unwrap();
+    let client_time_offset = presentation_bytes.len() - 8;
+    presentation_bytes[client_time_offset..].copy_from_slice(&(client_time + window).to_le_bytes());
+    let forwarded: zkgroup::auth::AuthCredentialTimestampedPresentation =
+        bincode::deserialize(&presentation_bytes).unwrap();
+    assert_eq!(client_time + window, forwarded.get_client_time());
+    assert!(matches!(
+        verify(&forwarded, client_time + window),
+        Err(zkgroup::ZkGroupCheckFailure::Verification)
+    ));
+}
+
+#[test]
+fn test_ciphertexts_reject_off_subgroup_encodings() {
+    // Edwards encodings of the nonzero small-order points, then non-canonical and otherwise invalid
+    // Ristretto encodings. None of these may decode to a group element.
+    let invalid_encodings = [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "0000000000000000000000000000000000000000000000000000000000000080",
+        "26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc05",
+        "26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc85",
+        "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac037a",
+        "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac03fa",
+        "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "01ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "26948d35ca62e643e26a83177332e6b6afeb9d08e4268b650f1f5bbd8d81d371",
+    ];
+
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_2);
+    let uuid_ciphertext_bytes = bincode::serialize(&group_secret_params.encrypt_uuid(uid)).unwrap();
+    let profile_key_ciphertext_bytes =
+        bincode::serialize(&group_secret_params.encrypt_profile_key(profile_key, uid)).unwrap();
+
+    // Each ciphertext is a reserved byte followed by two compressed points.
+    for point_offset in [1, 33].iter() {
+        for encoding in invalid_encodings.iter() {
+            let encoding = hex::decode(encoding).unwrap();
+
+            let mut bytes = uuid_ciphertext_bytes.clone();
+            bytes[*point_offset..*point_offset + 32].copy_from_slice(&encoding);
+            assert!(bincode::deserialize::<zkgroup::groups::UuidCiphertext>(&bytes).is_err());
+
+            let mut bytes = profile_key_ciphertext_bytes.clone();
+            bytes[*point_offset..*point_offset + 32].copy_from_slice(&encoding);
+            assert!(bincode::deserialize::<zkgroup::groups::ProfileKeyCiphertext>(&bytes).is_err());
+        }
+    }
+}
+
+#[test]
+fn test_verify_auth_credential_presentation_with_pseudonym() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+    let other_group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_5);
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let present = |randomness, group_secret_params| {
+        server_public_params.create_auth_credential_presentation(
+            randomness,
+            group_secret_params,
+            credential,
+        )
+    };
+
+    let presentation = present(zkgroup::TEST_ARRAY_32_3, group_secret_params);
+    let pseudonym = server_secret_params
+        .verify_auth_credential_presentation_with_pseudonym(group_public_params, &presentation)
+        .unwrap();
+    assert_eq!(
+        pseudonym,
+        presentation.derive_rate_limit_token(group_public_params)
+    );
+
+    // Stable across presentations, including V1 ones.
+    let presentation_v1 = server_public_params.create_auth_credential_presentation_v1(
+        zkgroup::TEST_ARRAY_32_4,
+        group_secret_params,
+        credential,
+    );
+    let presentation_v1 = zkgroup::auth::AnyAuthCredentialPresentation::new(
+        &bincode::serialize(&presentation_v1).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        pseudonym,
+        server_secret_params
+            .verify_auth_credential_presentation_with_pseudonym(
+                group_public_params,
+                &presentation_v1
+            )
+            .unwrap()
+    );
+
+    // Unlinkable across groups.
+    let other_group_public_params = other_group_secret_params.get_public_params();
+    let other_pseudonym = server_secret_params
+        .verify_auth_credential_presentation_with_pseudonym(
+            other_group_public_params,
+            &present(zkgroup::TEST_ARRAY_32_5, other_group_secret_params),
+        )
+        .unwrap();
+    assert_ne!(pseudonym, other_pseudonym);
+
+    assert!(server_secret_params
+        .verify_auth_credential_presentation_with_pseudonym(
+            other_group_public_params,
+            &presentation
+        )
+        .is_err());
+}
+
+#[test]
+fn test_profile_key_credential_try_recover() {
+    use zkgroup::profiles::ProfileKeyCredential;
+    use zkgroup::ZkGroupRecoveryFailure;
+
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+    let credential = receive_profile_key_credential(
+        &server_secret_params,
+        uid,
+        profile_key,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let bytes = bincode::serialize(&credential).unwrap();
+    assert_eq!(zkgroup::PROFILE_KEY_CREDENTIAL_LEN, bytes.len());
+
+    let recovered = ProfileKeyCredential::try_recover(&bytes).unwrap();
+    assert_eq!(bytes, bincode::serialize(&recovered).unwrap());
+
+    for (len, field) in [
+        (0, "reserved"),
+        (1, "t"),
+        (40, "U"),
+        (96, "V"),
+        (97, "uid"),
+        (bytes.len() - 1, "profile key"),
+    ]
+    .iter()
+    {
+        assert!(matches!(
+            ProfileKeyCredential::try_recover(&bytes[..*len]),
+            Err(ZkGroupRecoveryFailure::Truncated(name)) if name == *field
+        ));
+    }
+
+    let mut with_trailing_bytes = bytes.clone();
+    with_trailing_bytes.push(0);
+    assert!(matches!(
+        ProfileKeyCredential::try_recover(&with_trailing_bytes),
+        Err(ZkGroupRecoveryFailure::TrailingBytes)
+    ));
+
+    // A non-canonical scalar and an invalid point encoding.
+    let mut corrupt_t = bytes.clone();
+    corrupt_t[1..33].copy_from_slice(&[0xff; 32]);
+    assert!(matches!(
+        ProfileKeyCredential::try_recover(&corrupt_t),
+        Err(ZkGroupRecoveryFailure::Corrupt("t"))
+    ));
+    let mut corrupt_v = bytes;
+    corrupt_v[65..97].copy_from_slice(&[0xff; 32]);
+    assert!(matches!(
+        ProfileKeyCredential::try_recover(&corrupt_v),
+        Err(ZkGroupRecoveryFailure::Corrupt("V"))
+    ));
+}
+
+#[test]
+fn test_membership_token() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+    let other_group_public_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_5).get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let presentation = server_public_params.create_auth_credential_presentation(
+        zkgroup::TEST_ARRAY_32_3,
+        group_secret_params,
+        credential,
+    );
+
+    let expiration = 1_000_000u64;
+    let token = server_secret_params
+        .issue_membership_token(
+            zkgroup::TEST_ARRAY_32_4,
+            group_public_params,
+            &presentation,
+            expiration,
+        )
+        .unwrap();
+    assert_eq!(token.get_expiration(), expiration);
+    assert_eq!(
+        token.get_pseudonym(),
+        presentation.derive_rate_limit_token(group_public_params)
+    );
+
+    server_secret_params
+        .verify_membership_token(group_public_params, &token, expiration - 1)
+        .unwrap();
+    assert!(matches!(
+        server_secret_params.verify_membership_token(group_public_params, &token, expiration),
+        Err(zkgroup::ZkGroupCheckFailure::Expired)
+    ));
+    assert!(matches!(
+        server_secret_params.verify_membership_token(other_group_public_params, &token, 0),
+        Err(zkgroup::ZkGroupCheckFailure::Verification)
+    ));
+
+    // The expiration is covered by the MAC.
+    let mut token_bytes = bincode::serialize(&token).unwrap();
+    let expiration_offset = token_bytes.len() - 8;
+    token_bytes[expiration_offset..].copy_from_slice(&(expiration * 2).to_le_bytes());
+    let extended: zkgroup::auth::MembershipToken = bincode::deserialize(&token_bytes).unwrap();
+    assert!(matches!(
+        server_secret_params.verify_membership_token(group_public_params, &extended, 0),
+        Err(zkgroup::ZkGroupCheckFailure::Verification)
+    ));
+
+    // The token key pair is derived again when params are loaded.
+    let reloaded: zkgroup::ServerSecretParams =
+        bincode::deserialize(&bincode::serialize(&server_secret_params).unwrap()).unwrap();
+    reloaded
+        .verify_membership_token(group_public_params, &token, 0)
+        .unwrap();
+    zkgroup::ServerSecretParams::deserialize_extensible(
+        &server_secret_params.serialize_extensible(),
+    )
+    .unwrap()
+    .verify_membership_token(group_public_params, &token, 0)
+    .unwrap();
+    assert!(matches!(
+        server_secret_params
+            .with_service_context(b"other service")
+            .verify_membership_token(group_public_params, &token, 0),
+        Err(zkgroup::ZkGroupCheckFailure::Verification)
+    ));
+
+    // Only the issuing server can check the token.
+    let other_server_secret_params =
+        zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    assert!(matches!(
+        other_server_secret_params.verify_membership_token(group_public_params, &token, 0),
+        Err(zkgroup::ZkGroupCheckFailure::Verification)
+    ));
+
+    // A presentation for another group is not accepted in the first place.
+    assert!(server_secret_params
+        .issue_membership_token(
+            zkgroup::TEST_ARRAY_32_4,
+            other_group_public_params,
+            &presentation,
+            expiration,
+        )
+        .is_err());
+}
+
+#[test]
+fn test_auth_presentation_min_version() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+
+    let presentation_v2 = server_public_params.create_auth_credential_presentation(
+        zkgroup::TEST_ARRAY_32_3,
+        group_secret_params,
+        credential,
+    );
+    assert_eq!(
+        presentation_v2.get_version(),
+        zkgroup::PresentationVersion::AuthV2
+    );
+    let presentation_v1 = server_public_params.create_auth_credential_presentation_v1(
+        zkgroup::TEST_ARRAY_32_4,
+        group_secret_params,
+        credential,
+    );
+    let presentation_v1 = zkgroup::auth::AnyAuthCredentialPresentation::new(
+        &bincode::serialize(&presentation_v1).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        presentation_v1.get_version(),
+        zkgroup::PresentationVersion::AuthV1
+    );
+
+    for presentation in [&presentation_v1, &presentation_v2] {
+        server_secret_params
+            .verify_auth_credential_presentation_min_version(
+                group_public_params,
+                presentation,
+                zkgroup::PresentationVersion::AuthV1,
+            )
+            .unwrap();
+    }
+    server_secret_params
+        .verify_auth_credential_presentation_min_version(
+            group_public_params,
+            &presentation_v2,
+            zkgroup::PresentationVersion::AuthV2,
+        )
+        .unwrap();
+    assert!(matches!(
+        server_secret_params.verify_auth_credential_presentation_min_version(
+            group_public_params,
+            &presentation_v1,
+            zkgroup::PresentationVersion::AuthV2,
+        ),
+        Err(zkgroup::ZkGroupCheckFailure::VersionTooLow)
+    ));
+
+    // A new-enough presentation must still verify.
+    let other_group_public_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_5).get_public_params();
+    assert!(matches!(
+        server_secret_params.verify_auth_credential_presentation_min_version(
+            other_group_public_params,
+            &presentation_v2,
+            zkgroup::PresentationVersion::AuthV2,
+        ),
+        Err(zkgroup::ZkGroupCheckFailure::Verification)
+    ));
+}
+
+#[test]
+fn test_verify_from_snapshot() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let presentation = server_public_params.create_auth_credential_presentation(
+        zkgroup::TEST_ARRAY_32_3,
+        group_secret_params,
+        credential,
+    );
+
+    let snapshot = bincode::serialize(&server_secret_params).unwrap();
+    zkgroup::ServerSecretParams::verify_from_snapshot(
+        &snapshot,
+        group_public_params,
+        &presentation,
+    )
+    .unwrap();
+
+    let mut trailing = snapshot.clone();
+    trailing.push(0);
+    assert!(matches!(
+        zkgroup::ServerSecretParams::verify_from_snapshot(
+            &trailing,
+            group_public_params,
+            &presentation
+        ),
+        Err(zkgroup::ZkGroupCheckFailure::SnapshotNotCanonical)
+    ));
+
+    // Zero the auth key pair's first secret scalar, just after the reserved byte.
+    let mut weak = snapshot.clone();
+    weak[1..33].copy_from_slice(&[0u8; 32]);
+    assert!(matches!(
+        zkgroup::ServerSecretParams::verify_from_snapshot(
+            &weak,
+            group_public_params,
+            &presentation
+        ),
+        Err(zkgroup::ZkGroupCheckFailure::SnapshotSelfCheck(
+            "auth_credentials_key_pair"
+        ))
+    ));
+
+    let other_snapshot = bincode::serialize(&zkgroup::ServerSecretParams::generate(
+        zkgroup::TEST_ARRAY_32_1,
+    ))
+    .unwrap();
+    assert!(matches!(
+        zkgroup::ServerSecretParams::verify_from_snapshot(
+            &other_snapshot,
+            group_public_params,
+            &presentation
+        ),
+        Err(zkgroup::ZkGroupCheckFailure::Verification)
+    ));
+}
+
+#[test]
+fn test_profile_key_commitment_refers_to_same_key() {
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+    let commitment = profile_key.get_commitment(uid);
+    assert!(commitment.refers_to_same_key(&profile_key.get_commitment(uid)));
+
+    // A different version byte does not change what is committed to.
+    let mut bytes = bincode::serialize(&commitment).unwrap();
+    bytes[0] = 3;
+    let reencoded: zkgroup::profiles::ProfileKeyCommitment = bincode::deserialize(&bytes).unwrap();
+    assert!(commitment.refers_to_same_key(&reencoded));
+
+    let other_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_2);
+    assert!(!commitment.refers_to_same_key(&other_key.get_commitment(uid)));
+    assert!(!commitment.refers_to_same_key(&profile_key.get_commitment(zkgroup::TEST_ARRAY_16_1)));
+}
+
+#[test]
+fn test_auth_presentation_with_ttl() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let presentation = server_public_params.create_auth_credential_presentation(
+        zkgroup::TEST_ARRAY_32_3,
+        group_secret_params,
+        credential,
+    );
+    let ttl = |now_day| {
+        server_secret_params.verify_auth_credential_presentation_with_ttl(
+            group_public_params,
+            &presentation,
+            now_day,
+        )
+    };
+
+    let window = zkgroup::REDEMPTION_TIME_WINDOW_DAYS;
+    assert_eq!(
+        ttl(redemption_time).unwrap(),
+        u64::from(window) * zkgroup::SECONDS_PER_DAY
+    );
+    assert_eq!(
+        ttl(redemption_time + 1).unwrap(),
+        u64::from(window - 1) * zkgroup::SECONDS_PER_DAY
+    );
+    assert_eq!(
+        ttl(redemption_time - zkgroup::REDEMPTION_TIME_FUTURE_SKEW_DAYS).unwrap(),
+        u64::from(window + zkgroup::REDEMPTION_TIME_FUTURE_SKEW_DAYS) * zkgroup::SECONDS_PER_DAY
+    );
+    assert_eq!(ttl(redemption_time + window).unwrap(), 0);
+
+    assert!(matches!(
+        ttl(redemption_time + window + 1),
+        Err(zkgroup::ZkGroupCheckFailure::RedemptionTimeOutOfRange)
+    ));
+    assert!(matches!(
+        ttl(redemption_time - zkgroup::REDEMPTION_TIME_FUTURE_SKEW_DAYS - 1),
+        Err(zkgroup::ZkGroupCheckFailure::RedemptionTimeOutOfRange)
+    ));
+}
+
+#[test]
+fn test_service_context_separates_credentials() {
+    let shared_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let service_a = shared_params.with_service_context(b"service A");
+    let service_b = shared_params.with_service_context(b"service B");
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+
+    // The default context leaves the params as they were.
+    assert_eq!(
+        bincode::serialize(&shared_params.with_service_context(b"")).unwrap(),
+        bincode::serialize(&shared_params).unwrap()
+    );
+    // Derivation is deterministic.
+    assert_eq!(
+        bincode::serialize(&shared_params.with_service_context(b"service A")).unwrap(),
+        bincode::serialize(&service_a).unwrap()
+    );
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let present = |server_secret_params: &zkgroup::ServerSecretParams, randomness| {
+        let server_public_params = server_secret_params.get_public_params();
+        let response = server_secret_params
+            .issue_auth_credential(zkgroup::TEST_ARRAY_32_2, uid, redemption_time)
+            .unwrap();
+        let credential = server_public_params
+            .receive_auth_credential(uid, redemption_time, &response)
+            .unwrap();
+        server_public_params.create_auth_credential_presentation(
+            randomness,
+            group_secret_params,
+            credential,
+        )
+    };
+
+    let presentation_a = present(&service_a, zkgroup::TEST_ARRAY_32_3);
+    service_a
+        .verify_auth_credential_presentation(group_public_params, &presentation_a)
+        .unwrap();
+    assert!(service_b
+        .verify_auth_credential_presentation(group_public_params, &presentation_a)
+        .is_err());
+    assert!(shared_params
+        .verify_auth_credential_presentation(group_public_params, &presentation_a)
+        .is_err());
+
+    let presentation_default = present(&shared_params, zkgroup::TEST_ARRAY_32_4);
+    assert!(service_a
+        .verify_auth_credential_presentation(group_public_params, &presentation_default)
+        .is_err());
+
+    // Responses from one service are rejected by clients of another.
+    let response_a = service_a
+        .issue_auth_credential(zkgroup::TEST_ARRAY_32_2, uid, redemption_time)
+        .unwrap();
+    assert!(service_b
+        .get_public_params()
+        .receive_auth_credential(uid, redemption_time, &response_a)
+        .is_err());
+
+    // Profile key credentials are separated too.
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_4);
+    let context = service_a
+        .get_public_params()
+        .create_profile_key_credential_request_context(zkgroup::TEST_ARRAY_32_5, uid, profile_key);
+    let response = service_a
+        .issue_profile_key_credential(
+            zkgroup::TEST_ARRAY_32_2,
+            &context.get_request(),
+            uid,
+            profile_key.get_commitment(uid),
+        )
+        .unwrap();
+    let credential = service_a
+        .get_public_params()
+        .receive_profile_key_credential(&context, &response)
+        .unwrap();
+    service_a
+        .check_profile_key_credential_mac(&credential)
+        .unwrap();
+    assert!(service_b
+        .check_profile_key_credential_mac(&credential)
+        .is_err());
+}
+
+#[test]
+fn test_verify_file() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+    let other_group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_5);
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let present = |randomness, group_secret_params| {
+        bincode::serialize(&server_public_params.create_auth_credential_presentation(
+            randomness,
+            group_secret_params,
+            credential,
+        ))
+        .unwrap()
+    };
+    let good = present(zkgroup::TEST_ARRAY_32_3, group_secret_params);
+    let wrong_group = present(zkgroup::TEST_ARRAY_32_4, other_group_secret_params);
+
+    let mut bytes = vec![];
+    zkgroup::write_framed_presentation(&mut bytes, &good).unwrap();
+    let wrong_group_offset = bytes.len() as u64;
+    zkgroup::write_framed_presentation(&mut bytes, &wrong_group).unwrap();
+    let garbage_offset = bytes.len() as u64;
+    zkgroup::write_framed_presentation(&mut bytes, &good[1..]).unwrap();
+    zkgroup::write_framed_presentation(&mut bytes, &good).unwrap();
+
+    let path = std::env::temp_dir().join(format!(
+        "zkgroup_test_verify_file_{}.bin",
+        std::process::id()
+    ));
+    std::fs::write(&path, &bytes).unwrap();
+    let report = server_secret_params.verify_file(group_public_params, &path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        report.unwrap(),
+        zkgroup::VerificationReport {
+            total: 4,
+            verified: 2,
+            failure_offsets: vec![wrong_group_offset, garbage_offset],
+        }
+    );
+
+    assert!(server_secret_params
+        .verify_file(group_public_params, &path)
+        .is_err());
+}
+
+#[test]
+fn test_auth_credential_device_tagged_presentation() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_1),
+    );
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let auth_credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+
+    let present = |randomness, group_secret_params, device_tag: &[u8]| {
+        server_public_params.create_auth_credential_presentation_v2_with_device_tag(
+            randomness,
+            group_secret_params,
+            auth_credential,
+            device_tag,
+        )
+    };
+    let verify = |presentation: &zkgroup::auth::AuthCredentialDeviceTaggedPresentation| {
+        server_secret_params
+            .verify_auth_credential_device_tagged_presentation(group_public_params, presentation)
+    };
+
+    // The same device gets the same commitment across presentations; other devices don't.
+    let phone = verify(&present(
+        zkgroup::TEST_ARRAY_32_3,
+        group_secret_params,
+        b"phone",
+    ))
+    .unwrap();
+    let phone_again = verify(&present(
+        zkgroup::TEST_ARRAY_32_4,
+        group_secret_params,
+        b"phone",
+    ))
+    .unwrap();
+    let laptop = verify(&present(
+        zkgroup::TEST_ARRAY_32_5,
+        group_secret_params,
+        b"laptop",
+    ))
+    .unwrap();
+    assert_eq!(phone, phone_again);
+    assert_ne!(phone, laptop);
+
+    // The commitment depends on the group, so it can't be linked across groups.
+    let other_group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new(zkgroup::TEST_ARRAY_32_5),
+    );
+    let other_group = present(
+        zkgroup::TEST_ARRAY_32_2,
+        other_group_secret_params,
+        b"phone",
+    );
+    assert_ne!(phone, other_group.get_device_tag_commitment());
+
+    // The commitment is covered by the proof, so it can't be swapped for another device's.
+    let presentation = present(zkgroup::TEST_ARRAY_32_3, group_secret_params, b"phone");
+    let mut presentation_bytes = bincode::serialize(&presentation).unwrap();
+    let commitment_offset = presentation_bytes.len() - 32;
+    presentation_bytes[commitment_offset..].copy_from_slice(&laptop);
+    let swapped: zkgroup::auth::AuthCredentialDeviceTaggedPresentation =
+        bincode::deserialize(&presentation_bytes).unwrap();
+    assert_eq!(laptop, swapped.get_device_tag_commitment());
+    assert!(verify(&swapped).is_err());
+
+    // Nor does it verify as a presentation without a device tag.
+    let untagged: zkgroup::auth::AuthCredentialPresentationV2 =
+        bincode::deserialize(&presentation_bytes[..presentation_bytes.len() - 32]).unwrap();
+    assert!(server_secret_params
+        .verify_auth_credential_presentation_v2(group_public_params, &untagged)
+        .is_err());
+}
+
+#[test]
+fn test_auth_presentation_monotonic() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let presentation = server_public_params.create_auth_credential_presentation(
+        zkgroup::TEST_ARRAY_32_3,
+        group_secret_params,
+        credential,
+    );
+    let verify = |last_seen| {
+        server_secret_params.verify_auth_credential_presentation_monotonic(
+            group_public_params,
+            &presentation,
+            last_seen,
+        )
+    };
+
+    assert_eq!(redemption_time, verify(None).unwrap());
+    assert_eq!(redemption_time, verify(Some(redemption_time - 3)).unwrap());
+    // The same day's credential may be presented again.
+    assert_eq!(redemption_time, verify(Some(redemption_time)).unwrap());
+
+    // A lag of exactly the grace period is accepted; one more day is not.
+    let grace = zkgroup::REDEMPTION_TIME_MONOTONIC_GRACE_DAYS;
+    assert_eq!(
+        redemption_time + grace,
+        verify(Some(redemption_time + grace)).unwrap()
+    );
+    assert!(matches!(
+        verify(Some(redemption_time + grace + 1)),
+        Err(zkgroup::ZkGroupCheckFailure::RedemptionTimeOutOfRange)
+    ));
+
+    // The proof is checked before the redemption time is trusted.
+    let other_group_public_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_4).get_public_params();
+    assert!(matches!(
+        server_secret_params.verify_auth_credential_presentation_monotonic(
+            other_group_public_params,
+            &presentation,
+            None,
+        ),
+        Err(zkgroup::ZkGroupCheckFailure::Verification)
+    ));
+}
+
+#[test]
+fn test_auth_credential_batch() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_times: Vec<u32> = (123456..123463).collect();
+    let response = server_secret_params
+        .issue_auth_credential_batch(zkgroup::TEST_ARRAY_32_2, uid, &redemption_times)
+        .unwrap();
+    assert_eq!(redemption_times.len(), response.len());
+
+    let credentials = server_public_params
+        .receive_auth_credential_batch(uid, &redemption_times, &response)
+        .unwrap();
+    for (i, (credential, &redemption_time)) in credentials.iter().zip(&redemption_times).enumerate()
+    {
+        let presentation = server_public_params.create_auth_credential_presentation(
+            [i as u8; 32],
+            group_secret_params,
+            *credential,
+        );
+        assert_eq!(redemption_time, presentation.get_redemption_time());
+        server_secret_params
+            .verify_auth_credential_presentation(group_public_params, &presentation)
+            .unwrap();
+    }
+
+    // One shared proof is much smaller than a proof per credential.
+    let single_response = server_secret_params
+        .issue_auth_credential(zkgroup::TEST_ARRAY_32_2, uid, redemption_times[0])
+        .unwrap();
+    let batch_len = bincode::serialize(&response).unwrap().len();
+    let single_len = bincode::serialize(&single_response).unwrap().len();
+    assert!(batch_len < redemption_times.len() * single_len / 2);
+
+    // The proof covers each credential's redemption time, uid, and position.
+    let mut reordered = redemption_times.clone();
+    reordered.swap(0, 1);
+    assert!(server_public_params
+        .receive_auth_credential_batch(uid, &reordered, &response)
+        .is_err());
+    assert!(server_public_params
+        .receive_auth_credential_batch(zkgroup::TEST_ARRAY_16_1, &redemption_times, &response)
+        .is_err());
+    assert!(server_public_params
+        .receive_auth_credential_batch(uid, &redemption_times[1..], &response)
+        .is_err());
+
+    let full: Vec<u32> = (0..zkgroup::MAX_AUTH_CREDENTIAL_BATCH_SIZE as u32).collect();
+    let full_response = server_secret_params
+        .issue_auth_credential_batch(zkgroup::TEST_ARRAY_32_2, uid, &full)
+        .unwrap();
+    assert!(server_public_params
+        .receive_auth_credential_batch(uid, &full, &full_response)
+        .is_ok());
+
+    let too_many = vec![123456u32; zkgroup::MAX_AUTH_CREDENTIAL_BATCH_SIZE + 1];
+    assert!(server_secret_params
+        .issue_auth_credential_batch(zkgroup::TEST_ARRAY_32_2, uid, &too_many)
+        .is_err());
+    assert!(server_secret_params
+        .issue_auth_credential_batch(zkgroup::TEST_ARRAY_32_2, uid, &[])
+        .is_err());
+}
+
+#[test]
+fn test_server_params_derived_from_shared_secret() {
+    let secret = zkgroup::TEST_ARRAY_32;
+    let member_a = zkgroup::ServerSecretParams::derive_from_shared_secret(&secret);
+    let member_b = zkgroup::ServerSecretParams::derive_from_shared_secret(&secret);
+    assert_eq!(
+        bincode::serialize(&member_a).unwrap(),
+        bincode::serialize(&member_b).unwrap()
+    );
+    member_a.self_check().unwrap();
+
+    // Domain-separated from random generation and from other secrets.
+    let generated = zkgroup::ServerSecretParams::generate(secret);
+    assert_ne!(
+        bincode::serialize(&member_a).unwrap(),
+        bincode::serialize(&generated).unwrap()
+    );
+    let other = zkgroup::ServerSecretParams::derive_from_shared_secret(&zkgroup::TEST_ARRAY_32_1);
+    assert_ne!(
+        bincode::serialize(&member_a).unwrap(),
+        bincode::serialize(&other).unwrap()
+    );
+
+    // A credential issued by one member is verified by another.
+    let server_public_params = member_b.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_2);
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let response = member_a
+        .issue_auth_credential(zkgroup::TEST_ARRAY_32_3, uid, redemption_time)
+        .unwrap();
+    let credential = server_public_params
+        .receive_auth_credential(uid, redemption_time, &response)
+        .unwrap();
+    let presentation = server_public_params.create_auth_credential_presentation(
+        zkgroup::TEST_ARRAY_32_4,
+        group_secret_params,
+        credential,
+    );
+    member_b
+        .verify_auth_credential_presentation(group_secret_params.get_public_params(), &presentation)
+        .unwrap();
+}
+
+#[test]
+fn test_issuance_summary_signature() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+
+    let summary = zkgroup::IssuanceSummary {
+        batch_nonce: zkgroup::TEST_ARRAY_32_1,
+        auth: 7,
+        profile_key: 2,
+        pni: 0,
+        receipt: 1,
+    };
+    let signature = server_secret_params.sign_issuance_summary(zkgroup::TEST_ARRAY_32_2, &summary);
+    server_public_params
+        .verify_issuance_summary(&summary, signature)
+        .unwrap();
+
+    let inflated = zkgroup::IssuanceSummary {
+        receipt: 2,
+        ..summary
+    };
+    assert!(server_public_params
+        .verify_issuance_summary(&inflated, signature)
+        .is_err());
+    let replayed = zkgroup::IssuanceSummary {
+        batch_nonce: zkgroup::TEST_ARRAY_32_3,
+        ..summary
+    };
+    assert!(server_public_params
+        .verify_issuance_summary(&replayed, signature)
+        .is_err());
+
+    // A summary signature is not a signature over the summary's bytes, or vice versa.
+    assert!(server_public_params
+        .verify_signature(&summary.to_bytes(), signature)
+        .is_err());
+    let raw_signature = server_secret_params.sign(zkgroup::TEST_ARRAY_32_2, &summary.to_bytes());
+    assert!(server_public_params
+        .verify_issuance_summary(&summary, raw_signature)
+        .is_err());
+
+    // Nor can the server be made to sign a summary through `sign`, even given the exact message.
+    let mut signed_message = b"Signal_ZKGroup_20220301_IssuanceSummary".to_vec();
+    signed_message.extend_from_slice(&summary.to_bytes());
+    let forged = server_secret_params.sign(zkgroup::TEST_ARRAY_32_2, &signed_message);
+    server_public_params
+        .verify_signature(&signed_message, forged)
+        .unwrap();
+    assert!(server_public_params
+        .verify_issuance_summary(&summary, forged)
+        .is_err());
+
+    let mut expected = zkgroup::TEST_ARRAY_32_1.to_vec();
+    for count in &[7u64, 2, 0, 1] {
+        expected.extend_from_slice(&count.to_be_bytes());
+    }
+    assert_eq!(expected, summary.to_bytes().to_vec());
+}
+
+#[test]
+fn test_profile_key_credential_request_context_validate() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let uid = zkgroup::TEST_ARRAY_16;
+    let profile_key = zkgroup::profiles::ProfileKey::create(zkgroup::TEST_ARRAY_32_1);
+
+    let context = server_public_params.create_profile_key_credential_request_context(
+        zkgroup::TEST_ARRAY_32_2,
+        uid,
+        profile_key,
+    );
+    context.validate().unwrap();
+    server_public_params
+        .validate_profile_key_credential_request_context(&context)
+        .unwrap();
+
+    // Flip a bit in the stored profile key, just after the reserved byte and uid.
+    let mut context_bytes = bincode::serialize(&context).unwrap();
+    context_bytes[1 + zkgroup::UUID_LEN] ^= 1;
+    let corrupted: zkgroup::profiles::ProfileKeyCredentialRequestContext =
+        bincode::deserialize(&context_bytes).unwrap();
+    assert!(corrupted.validate().is_err());
+
+    // A personalized deployment needs its own params to validate.
+    let personalized_public_params =
+        zkgroup::ServerSecretParams::generate_with_personalization(zkgroup::TEST_ARRAY_32, b"salt")
+            .get_public_params();
+    let context = personalized_public_params.create_profile_key_credential_request_context(
+        zkgroup::TEST_ARRAY_32_2,
+        uid,
+        profile_key,
+    );
+    assert!(context.validate().is_err());
+    personalized_public_params
+        .validate_profile_key_credential_request_context(&context)
+        .unwrap();
+}
+
+#[test]
+fn test_verify_auth_credential_presentation_canonical() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+
+    let presentation_v1 = server_public_params.create_auth_credential_presentation_v1(
+        zkgroup::TEST_ARRAY_32_3,
+        group_secret_params,
+        credential,
+    );
+    let presentation_v2 = server_public_params.create_auth_credential_presentation_v2(
+        zkgroup::TEST_ARRAY_32_4,
+        group_secret_params,
+        credential,
+    );
+    for bytes in &[
+        bincode::serialize(&presentation_v1).unwrap(),
+        bincode::serialize(&presentation_v2).unwrap(),
+    ] {
+        let presentation = zkgroup::auth::AnyAuthCredentialPresentation::new(bytes).unwrap();
+        let canonical = server_secret_params
+            .verify_auth_credential_presentation_canonical(group_public_params, &presentation)
+            .unwrap();
+        assert_eq!(bytes, &canonical);
+
+        let reparsed = zkgroup::auth::AnyAuthCredentialPresentation::new(&canonical).unwrap();
+        assert_eq!(
+            canonical,
+            server_secret_params
+                .verify_auth_credential_presentation_canonical(group_public_params, &reparsed)
+                .unwrap()
+        );
+    }
+
+    let other_group_public_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_5).get_public_params();
+    let presentation = zkgroup::auth::AnyAuthCredentialPresentation::V2(presentation_v2);
+    assert!(server_secret_params
+        .verify_auth_credential_presentation_canonical(other_group_public_params, &presentation)
+        .is_err());
+}
+
+#[test]
+fn test_server_secret_params_ct_eq() {
+    let secret = zkgroup::TEST_ARRAY_32;
+    let params = zkgroup::ServerSecretParams::derive_from_shared_secret(&secret);
+    let rederived = zkgroup::ServerSecretParams::derive_from_shared_secret(&secret);
+    assert!(params.ct_eq(&rederived));
+
+    let reloaded: zkgroup::ServerSecretParams =
+        bincode::deserialize(&bincode::serialize(&params).unwrap()).unwrap();
+    assert!(params.ct_eq(&reloaded.with_personalization(b"deployment")));
+
+    let other = zkgroup::ServerSecretParams::derive_from_shared_secret(&zkgroup::TEST_ARRAY_32_1);
+    assert!(!params.ct_eq(&other));
+    assert!(!params.ct_eq(&zkgroup::ServerSecretParams::generate(secret)));
+
+    // A change to any single key pair is detected. Credential key pairs are 32-byte fields: four
+    // scalars and three points, plus the attribute scalars.
+    let serialized = bincode::serialize(&params).unwrap();
+    let other_serialized = bincode::serialize(&other).unwrap();
+    let key_pair_lens = [32 * (7 + 4), 32 * (7 + 4), 64, 32 * (7 + 4), 32 * (7 + 6)];
+    assert_eq!(serialized.len(), 1 + key_pair_lens.iter().sum::<usize>());
+    let mut start = 1;
+    for len in &key_pair_lens {
+        let mut mixed = serialized.clone();
+        mixed[start..start + len].copy_from_slice(&other_serialized[start..start + len]);
+        let mixed: zkgroup::ServerSecretParams = bincode::deserialize(&mixed).unwrap();
+        assert!(!params.ct_eq(&mixed));
+        start += len;
+    }
+}
+
+#[test]
+fn test_redacted_presentation() {
+    let server_secret_params = zkgroup::ServerSecretParams::generate(zkgroup::TEST_ARRAY_32);
+    let server_public_params = server_secret_params.get_public_params();
+    let group_secret_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_1);
+    let group_public_params = group_secret_params.get_public_params();
+
+    let uid = zkgroup::TEST_ARRAY_16;
+    let redemption_time = 123456u32;
+    let credential = receive_auth_credential(
+        &server_secret_params,
+        uid,
+        redemption_time,
+        zkgroup::TEST_ARRAY_32_2,
+    );
+    let presentation = server_public_params.create_auth_credential_presentation(
+        zkgroup::TEST_ARRAY_32_3,
+        group_secret_params,
+        credential,
+    );
+
+    let redacted = presentation.redact_for_sharing(group_public_params);
+    assert_eq!(
+        presentation.derive_rate_limit_token(group_public_params),
+        redacted.get_pseudonym()
+    );
+    let redacted: zkgroup::auth::RedactedPresentation =
+        bincode::deserialize(&bincode::serialize(&redacted).unwrap()).unwrap();
+    server_secret_params
+        .verify_redacted_presentation(group_public_params, &redacted)
+        .unwrap();
+
+    // The group can still decrypt the member.
+    let ciphertext = redacted.get_presentation().unwrap().get_uuid_ciphertext();
+    assert_eq!(uid, group_secret_params.decrypt_uuid(ciphertext).unwrap());
+
+    // A pseudonym from another group doesn't match.
+    let other_group_public_params =
+        zkgroup::groups::GroupSecretParams::generate(zkgroup::TEST_ARRAY_32_5).get_public_params();
+    let mismatched = presentation.redact_for_sharing(other_group_public_params);
+    assert!(server_secret_params
+        .verify_redacted_presentation(group_public_params, &mismatched)
+        .is_err());
+}
@@ -10,8 +10,8 @@ use zkgroup::crypto::proofs::{ReceiptCredentialIssuanceProof, ReceiptCredentialP
 use zkgroup::crypto::receipt_credential_request;
 use zkgroup::crypto::receipt_struct::ReceiptStruct;
 use zkgroup::{
-    RandomnessBytes, ReceiptExpirationTime, ReceiptLevel, ReceiptSerialBytes, ServerSecretParams,
-    RANDOMNESS_LEN, RECEIPT_SERIAL_LEN,
+    CompactSerializable, RandomnessBytes, ReceiptExpirationTime, ReceiptLevel, ReceiptSerialBytes,
+    ServerSecretParams, RANDOMNESS_LEN, RECEIPT_SERIAL_LEN,
 };
 
 #[test]
@@ -52,6 +52,7 @@ fn test_request_response() {
         blinded_receipt_credential,
         receipt_expiration_time,
         receipt_level,
+        &[],
         &mut sho,
     );
 
@@ -65,6 +66,7 @@ fn test_request_response() {
             given_to_server_ciphertext,
             given_to_client_blinded_receipt_credential,
             receipt_struct,
+            &[],
         )
         .expect("issuance proof validity check failed");
     let receipt_credential = client_key_pair
@@ -72,12 +74,13 @@ fn test_request_response() {
     let receipt_credential_presentation_proof = ReceiptCredentialPresentationProof::new(
         server_key_pair.get_public_key(),
         receipt_credential,
+        &[],
         &mut sho,
     );
 
     // server verification of the credential presentation
     receipt_credential_presentation_proof
-        .verify(server_key_pair, receipt_struct)
+        .verify(server_key_pair, receipt_struct, &[])
         .expect("presentation proof validity check failed");
 }
 
@@ -140,6 +143,18 @@ fn test_api() {
         bincode::serialize(&presentation).unwrap().len(),
     );
 
+    let compact_credential = credential.serialize_compact();
+    assert_eq!(
+        zkgroup::common::constants::RECEIPT_CREDENTIAL_LEN - 1,
+        compact_credential.len(),
+    );
+    let restored_credential =
+        zkgroup::receipts::ReceiptCredential::deserialize_compact(&compact_credential).unwrap();
+    assert_eq!(
+        bincode::serialize(&credential).unwrap(),
+        bincode::serialize(&restored_credential).unwrap(),
+    );
+
     let mut presentation_bytes = bincode::serialize(&presentation).unwrap();
     // change it ever so slightly; maybe try a higher level for instance
     let i = presentation_bytes.len() - 17;
@@ -150,3 +165,192 @@ fn test_api() {
         .verify_receipt_credential_presentation(&bad_presentation)
         .expect_err("This Presentation Should Be Bad");
 }
+
+#[test]
+fn test_spent_serial_set() {
+    let server_secret_params = ServerSecretParams::generate([0x42u8; RANDOMNESS_LEN]);
+    let server_public_params = server_secret_params.get_public_params();
+    let present = |receipt_serial_bytes: ReceiptSerialBytes| {
+        let context = server_public_params.create_receipt_credential_request_context(
+            [0x43u8; RANDOMNESS_LEN],
+            receipt_serial_bytes,
+        );
+        let response = server_secret_params.issue_receipt_credential(
+            [0x44u8; RANDOMNESS_LEN],
+            &context.get_request(),
+            31337,
+            3,
+        );
+        let credential = server_public_params
+            .receive_receipt_credential(&context, &response)
+            .unwrap();
+        server_public_params
+            .create_receipt_credential_presentation([0x45u8; RANDOMNESS_LEN], &credential)
+    };
+
+    let mut spent = zkgroup::receipts::SpentSerialSet::new();
+    assert!(spent.is_empty());
+
+    let presentation = present([0x84u8; RECEIPT_SERIAL_LEN]);
+    server_secret_params
+        .verify_receipt_credential_presentation(&presentation)
+        .unwrap();
+    assert!(!spent.contains(&presentation.get_receipt_serial_bytes()));
+    assert!(spent.insert(presentation.get_receipt_serial_bytes()));
+
+    // Redeeming the same receipt again is caught.
+    let replayed = present([0x84u8; RECEIPT_SERIAL_LEN]);
+    assert!(spent.contains(&replayed.get_receipt_serial_bytes()));
+    assert!(!spent.insert(replayed.get_receipt_serial_bytes()));
+
+    spent.extend(vec![
+        [0x85u8; RECEIPT_SERIAL_LEN],
+        [0x86u8; RECEIPT_SERIAL_LEN],
+        [0x85u8; RECEIPT_SERIAL_LEN],
+    ]);
+    assert_eq!(3, spent.len());
+    assert!(spent.contains(&[0x86u8; RECEIPT_SERIAL_LEN]));
+    assert!(!spent.contains(&[0x87u8; RECEIPT_SERIAL_LEN]));
+}
+
+#[test]
+fn test_replaced_receipt_key() {
+    let server_secret_params = ServerSecretParams::generate([0x42u8; RANDOMNESS_LEN]);
+    let mut sho = Sho::new(b"Test_Replaced_Receipt_Key", b"");
+    let campaign_key_pair =
+        credentials::KeyPair::<credentials::ReceiptCredential>::generate(&mut sho);
+    let campaign_secret_params = server_secret_params.with_receipt_key_pair(campaign_key_pair);
+    let campaign_public_params = server_secret_params
+        .get_public_params()
+        .with_receipt_key(campaign_key_pair.get_public_key());
+
+    let original_bytes = bincode::serialize(&server_secret_params.get_public_params()).unwrap();
+    let campaign_bytes = bincode::serialize(&campaign_public_params).unwrap();
+    assert_eq!(
+        campaign_bytes,
+        bincode::serialize(&campaign_secret_params.get_public_params()).unwrap()
+    );
+    assert_ne!(original_bytes, campaign_bytes);
+    assert_eq!(
+        server_secret_params.signing_public_key_bytes(),
+        campaign_public_params.signing_public_key_bytes()
+    );
+
+    let receipt_serial_bytes = [0x84u8; RECEIPT_SERIAL_LEN];
+    let context = campaign_public_params
+        .create_receipt_credential_request_context([0x43u8; RANDOMNESS_LEN], receipt_serial_bytes);
+    let response = campaign_secret_params.issue_receipt_credential(
+        [0x44u8; RANDOMNESS_LEN],
+        &context.get_request(),
+        31337,
+        3,
+    );
+    let credential = campaign_public_params
+        .receive_receipt_credential(&context, &response)
+        .unwrap();
+    assert!(server_secret_params
+        .get_public_params()
+        .receive_receipt_credential(&context, &response)
+        .is_err());
+
+    let presentation = campaign_public_params
+        .create_receipt_credential_presentation([0x45u8; RANDOMNESS_LEN], &credential);
+    campaign_secret_params
+        .verify_receipt_credential_presentation(&presentation)
+        .unwrap();
+    assert!(server_secret_params
+        .verify_receipt_credential_presentation(&presentation)
+        .is_err());
+}
+
+#[test]
+fn test_verify_and_redeem() {
+    let server_secret_params = ServerSecretParams::generate([0x42u8; RANDOMNESS_LEN]);
+    let server_public_params = server_secret_params.get_public_params();
+    let receipt_expiration_time: ReceiptExpirationTime = 86400 * 30;
+    let present = |receipt_serial_bytes: ReceiptSerialBytes, randomness: RandomnessBytes| {
+        let context = server_public_params.create_receipt_credential_request_context(
+            [0x43u8; RANDOMNESS_LEN],
+            receipt_serial_bytes,
+        );
+        let response = server_secret_params.issue_receipt_credential(
+            [0x44u8; RANDOMNESS_LEN],
+            &context.get_request(),
+            receipt_expiration_time,
+            3,
+        );
+        let credential = server_public_params
+            .receive_receipt_credential(&context, &response)
+            .unwrap();
+        server_public_params.create_receipt_credential_presentation(randomness, &credential)
+    };
+    let now = receipt_expiration_time - 86400;
+    let mut spent = zkgroup::receipts::SpentSerialSet::new();
+
+    let presentation = present([0x84u8; RECEIPT_SERIAL_LEN], [0x45u8; RANDOMNESS_LEN]);
+    let receipt = server_secret_params
+        .verify_and_redeem(&presentation, now, &mut spent)
+        .unwrap();
+    assert!(
+        receipt == ReceiptStruct::new([0x84u8; RECEIPT_SERIAL_LEN], receipt_expiration_time, 3)
+    );
+    assert!(spent.contains(&[0x84u8; RECEIPT_SERIAL_LEN]));
+
+    assert!(matches!(
+        server_secret_params.verify_and_redeem(&presentation, now, &mut spent),
+        Err(zkgroup::ZkGroupCheckFailure::AlreadySpent)
+    ));
+
+    // Failed redemptions leave the set untouched.
+    let presentation = present([0x85u8; RECEIPT_SERIAL_LEN], [0x46u8; RANDOMNESS_LEN]);
+    assert!(matches!(
+        server_secret_params.verify_and_redeem(&presentation, receipt_expiration_time, &mut spent),
+        Err(zkgroup::ZkGroupCheckFailure::Expired)
+    ));
+    let mut presentation_bytes = bincode::serialize(&presentation).unwrap();
+    let i = presentation_bytes.len() - 17;
+    presentation_bytes[i] += 1;
+    let bad_presentation =
+        bincode::deserialize::<ReceiptCredentialPresentation>(&presentation_bytes).unwrap();
+    assert!(matches!(
+        server_secret_params.verify_and_redeem(&bad_presentation, now, &mut spent),
+        Err(zkgroup::ZkGroupCheckFailure::Verification)
+    ));
+    assert_eq!(1, spent.len());
+
+    server_secret_params
+        .verify_and_redeem(&presentation, now, &mut spent)
+        .unwrap();
+    assert_eq!(2, spent.len());
+}
+
+#[test]
+fn test_diagnose_receipt_credential() {
+    let server_secret_params = ServerSecretParams::generate([0x42u8; RANDOMNESS_LEN]);
+    let server_public_params = server_secret_params.get_public_params();
+    let context = server_public_params.create_receipt_credential_request_context(
+        [0x43u8; RANDOMNESS_LEN],
+        [0x84u8; RECEIPT_SERIAL_LEN],
+    );
+    let response = server_secret_params.issue_receipt_credential(
+        [0x44u8; RANDOMNESS_LEN],
+        &context.get_request(),
+        31337,
+        3,
+    );
+    let credential = server_public_params
+        .receive_receipt_credential(&context, &response)
+        .unwrap();
+    let bytes = bincode::serialize(&credential).unwrap();
+
+    let report = zkgroup::diagnose_credential(&bytes);
+    assert_eq!(Some(zkgroup::CredentialKind::Receipt), report.kind);
+    assert!(report.is_well_formed());
+    assert_eq!(Some(31337), report.receipt_expiration_time);
+    assert_eq!(Some(3), report.receipt_level);
+    assert_eq!(None, report.redemption_time);
+
+    // The serial is never reported, even though it's in the blob.
+    let serial = format!("{:?}", [0x84u8; RECEIPT_SERIAL_LEN]);
+    assert!(!format!("{} {:?}", report, report).contains(&serial[1..serial.len() - 1]));
+}